@@ -0,0 +1,23 @@
+CREATE TABLE IF NOT EXISTS challenge (
+  record_id       TEXT PRIMARY KEY,
+  guild_id        TEXT NOT NULL,
+  challenge_name  TEXT NOT NULL,
+  start_date      DATE NOT NULL,
+  end_date        DATE NOT NULL,
+  goal_type       TEXT NOT NULL,
+  goal_target     INTEGER NOT NULL,
+  reward_role     TEXT,
+  created_at      TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE UNIQUE INDEX ON challenge (guild_id, LOWER(challenge_name));
+
+CREATE TABLE IF NOT EXISTS challenge_participant (
+  record_id     TEXT PRIMARY KEY,
+  challenge_id  TEXT NOT NULL REFERENCES challenge (record_id) ON DELETE CASCADE,
+  user_id       TEXT NOT NULL,
+  guild_id      TEXT NOT NULL,
+  joined_at     TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  completed_at  TIMESTAMP WITH TIME ZONE,
+  UNIQUE (challenge_id, user_id)
+);
@@ -0,0 +1,25 @@
+CREATE TABLE IF NOT EXISTS challenge_team (
+  record_id     TEXT PRIMARY KEY,
+  challenge_id  TEXT NOT NULL REFERENCES challenge (record_id) ON DELETE CASCADE,
+  team_name     TEXT NOT NULL,
+  created_at    TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE UNIQUE INDEX ON challenge_team (challenge_id, LOWER(team_name));
+
+ALTER TABLE challenge_participant ADD COLUMN team_id TEXT REFERENCES challenge_team (record_id) ON DELETE SET NULL;
+
+ALTER TABLE challenge ADD COLUMN standings_channel_id TEXT;
+ALTER TABLE challenge ADD COLUMN standings_message_id TEXT;
+ALTER TABLE challenge ADD COLUMN results_posted_at TIMESTAMP WITH TIME ZONE;
+
+CREATE TABLE IF NOT EXISTS challenge_team_result (
+  record_id      TEXT PRIMARY KEY,
+  challenge_id   TEXT NOT NULL REFERENCES challenge (record_id) ON DELETE CASCADE,
+  team_id        TEXT NOT NULL REFERENCES challenge_team (record_id) ON DELETE CASCADE,
+  placement      INTEGER NOT NULL,
+  total_minutes  BIGINT NOT NULL,
+  member_count   INTEGER NOT NULL,
+  recorded_at    TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+  UNIQUE (challenge_id, team_id)
+);
@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS buddies (
+  record_id         TEXT PRIMARY KEY,
+  guild_id          TEXT NOT NULL,
+  user_id           TEXT NOT NULL,
+  buddy_id          TEXT NOT NULL,
+  status            TEXT NOT NULL DEFAULT 'pending',
+  notifications     TEXT NOT NULL DEFAULT 'off',
+  last_notified_at  TIMESTAMP WITH TIME ZONE,
+  created_at        TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+  UNIQUE (guild_id, user_id, buddy_id)
+);
+
+CREATE INDEX ON buddies (guild_id, buddy_id);
@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS badge (
+  record_id    TEXT PRIMARY KEY,
+  guild_id     TEXT NOT NULL,
+  user_id      TEXT NOT NULL,
+  badge_key    TEXT NOT NULL,
+  unlocked_at  TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  UNIQUE (guild_id, user_id, badge_key)
+);
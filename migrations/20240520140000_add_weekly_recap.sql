@@ -0,0 +1,2 @@
+ALTER TABLE tracking_profile ADD COLUMN weekly_recap BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE tracking_profile ADD COLUMN last_recap_at TIMESTAMP WITH TIME ZONE;
@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS starboard_channel (
+  record_id    TEXT PRIMARY KEY,
+  guild_id     TEXT NOT NULL,
+  channel_id   TEXT NOT NULL,
+  min_stars    INTEGER,
+  excluded     BOOLEAN NOT NULL DEFAULT FALSE,
+  UNIQUE (guild_id, channel_id)
+);
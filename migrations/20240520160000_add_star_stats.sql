@@ -0,0 +1,9 @@
+-- Rows created before this migration have no guild or author and are filled in by the backfill job
+ALTER TABLE star
+  ADD COLUMN IF NOT EXISTS guild_id TEXT,
+  ADD COLUMN IF NOT EXISTS author_id TEXT,
+  ADD COLUMN IF NOT EXISTS star_count INTEGER NOT NULL DEFAULT 0,
+  ADD COLUMN IF NOT EXISTS created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  ADD COLUMN IF NOT EXISTS updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL;
+
+CREATE INDEX IF NOT EXISTS star_guild_id_idx ON star (guild_id);
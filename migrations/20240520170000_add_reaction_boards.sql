@@ -0,0 +1,16 @@
+CREATE TABLE IF NOT EXISTS reaction_board (
+  record_id      TEXT PRIMARY KEY,
+  guild_id       TEXT NOT NULL,
+  board_name     TEXT NOT NULL,
+  emoji          TEXT NOT NULL,
+  channel_id     TEXT NOT NULL,
+  min_reactions  INTEGER NOT NULL,
+  color          INTEGER,
+  footer_label   TEXT,
+  UNIQUE (guild_id, board_name)
+);
+
+-- Existing entries all belong to the starboard, which is configured in code rather than stored
+ALTER TABLE star ADD COLUMN IF NOT EXISTS board_id TEXT NOT NULL DEFAULT 'starboard';
+ALTER TABLE star DROP CONSTRAINT IF EXISTS star_starred_message_id_key;
+ALTER TABLE star ADD CONSTRAINT star_board_id_starred_message_id_key UNIQUE (board_id, starred_message_id);
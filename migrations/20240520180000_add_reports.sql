@@ -0,0 +1,27 @@
+CREATE TABLE IF NOT EXISTS reports (
+  record_id          TEXT PRIMARY KEY,
+  guild_id           TEXT NOT NULL,
+  case_number        INTEGER NOT NULL,
+  reporter_id        TEXT NOT NULL,
+  author_id          TEXT NOT NULL,
+  channel_id         TEXT NOT NULL,
+  message_id         TEXT NOT NULL,
+  message_content    TEXT NOT NULL,
+  source             TEXT NOT NULL,
+  status             TEXT NOT NULL DEFAULT 'open',
+  assignee_id        TEXT,
+  resolution_notes   TEXT,
+  report_message_id  TEXT,
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  updated_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  UNIQUE (guild_id, case_number)
+);
+
+CREATE INDEX IF NOT EXISTS reports_guild_id_status_idx ON reports (guild_id, status);
+
+-- The last case number given out in each guild. Case numbers are taken from here rather than
+-- from the reports themselves, so that concurrent reports can't get the same number.
+CREATE TABLE IF NOT EXISTS report_case_counter (
+  guild_id     TEXT PRIMARY KEY,
+  last_number  INTEGER NOT NULL
+);
@@ -0,0 +1,20 @@
+CREATE TABLE IF NOT EXISTS report_reporter (
+  record_id    TEXT PRIMARY KEY,
+  guild_id     TEXT NOT NULL,
+  report_id    TEXT NOT NULL REFERENCES reports (record_id) ON DELETE CASCADE,
+  reporter_id  TEXT NOT NULL,
+  created_at   TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  UNIQUE (report_id, reporter_id)
+);
+
+CREATE INDEX IF NOT EXISTS report_reporter_guild_id_reporter_id_idx ON report_reporter (guild_id, reporter_id, created_at);
+
+-- Every existing report has its original reporter
+INSERT INTO report_reporter (record_id, guild_id, report_id, reporter_id, created_at)
+SELECT record_id, guild_id, record_id, reporter_id, created_at FROM reports
+ON CONFLICT DO NOTHING;
+
+-- A message has at most one open or claimed case, so that reports made at the same time are
+-- merged instead of opening a case each
+CREATE UNIQUE INDEX IF NOT EXISTS reports_active_message_idx
+  ON reports (guild_id, message_id) WHERE status IN ('open', 'claimed');
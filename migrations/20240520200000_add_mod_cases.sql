@@ -0,0 +1,40 @@
+CREATE TABLE IF NOT EXISTS mod_case (
+  record_id          TEXT PRIMARY KEY,
+  guild_id           TEXT NOT NULL,
+  case_number        INTEGER NOT NULL,
+  user_id            TEXT NOT NULL,
+  moderator_id       TEXT,
+  action             TEXT NOT NULL,
+  reason             TEXT,
+  evidence_link      TEXT,
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  UNIQUE (guild_id, case_number)
+);
+
+CREATE INDEX IF NOT EXISTS mod_case_guild_id_user_id_idx ON mod_case (guild_id, user_id);
+
+-- Erases become cases in the moderation log. Erases without a date keep the epoch, which is
+-- shown as not available.
+INSERT INTO mod_case (record_id, guild_id, case_number, user_id, action, evidence_link, created_at)
+SELECT
+  record_id,
+  guild_id,
+  ROW_NUMBER() OVER (PARTITION BY guild_id ORDER BY occurred_at NULLS FIRST, record_id),
+  user_id,
+  'erase',
+  message_link,
+  COALESCE(occurred_at, TIMESTAMP WITH TIME ZONE 'epoch')
+FROM erases;
+
+-- The last case number given out in each guild. Case numbers are taken from here rather than
+-- from the cases themselves, so that concurrent actions can't get the same number.
+CREATE TABLE IF NOT EXISTS mod_case_counter (
+  guild_id     TEXT PRIMARY KEY,
+  last_number  INTEGER NOT NULL
+);
+
+INSERT INTO mod_case_counter (guild_id, last_number)
+SELECT guild_id, MAX(case_number) FROM mod_case GROUP BY guild_id;
+
+-- Kept until the migrated cases have been checked, to be dropped in a later release
+ALTER TABLE erases RENAME TO erases_legacy;
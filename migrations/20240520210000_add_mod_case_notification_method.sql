@@ -0,0 +1 @@
+ALTER TABLE mod_case ADD COLUMN IF NOT EXISTS notification_method TEXT;
@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS mod_reason (
+  record_id          TEXT PRIMARY KEY,
+  guild_id           TEXT NOT NULL,
+  reason_key         TEXT NOT NULL,
+  reason_text        TEXT NOT NULL,
+  rule_link          TEXT,
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  UNIQUE (guild_id, reason_key)
+);
+
+ALTER TABLE mod_case ADD COLUMN IF NOT EXISTS reason_key TEXT;
+
+CREATE INDEX IF NOT EXISTS mod_case_guild_id_reason_key_idx ON mod_case (guild_id, reason_key);
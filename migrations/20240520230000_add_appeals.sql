@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS appeal (
+  record_id          TEXT PRIMARY KEY,
+  guild_id           TEXT NOT NULL,
+  case_id            TEXT NOT NULL REFERENCES mod_case (record_id) ON DELETE CASCADE,
+  user_id            TEXT NOT NULL,
+  appeal_text        TEXT NOT NULL,
+  status             TEXT NOT NULL DEFAULT 'pending',
+  reviewer_id        TEXT,
+  decision_notes     TEXT,
+  staff_message_id   TEXT,
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  updated_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  UNIQUE (case_id)
+);
@@ -0,0 +1,13 @@
+-- Embeddings from different models can have different sizes, so the column is no longer tied to
+-- one dimensionality. Searches only compare embeddings from the same model.
+ALTER TABLE term ALTER COLUMN embedding TYPE vector;
+
+ALTER TABLE term ADD COLUMN IF NOT EXISTS embedding_model TEXT;
+ALTER TABLE term ADD COLUMN IF NOT EXISTS embedding_dimensions INTEGER;
+ALTER TABLE term ADD COLUMN IF NOT EXISTS embedding_hash TEXT;
+
+-- Existing embeddings were created from the term name with OpenAI. They have no input hash, so
+-- they are recomputed from the embedding template by the reindex job.
+UPDATE term
+SET embedding_model = 'text-embedding-ada-002', embedding_dimensions = vector_dims(embedding)
+WHERE embedding IS NOT NULL;
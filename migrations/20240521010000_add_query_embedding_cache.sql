@@ -0,0 +1,11 @@
+-- Embeddings of glossary search queries, so repeated searches don't call the embeddings provider.
+-- Queries are normalized before they are stored, and embeddings are kept per model.
+CREATE TABLE IF NOT EXISTS query_embedding (
+  query_text         TEXT NOT NULL,
+  embedding_model    TEXT NOT NULL,
+  embedding          vector NOT NULL,
+  hit_count          INTEGER NOT NULL DEFAULT 0,
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  last_used_at       TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  PRIMARY KEY (embedding_model, query_text)
+);
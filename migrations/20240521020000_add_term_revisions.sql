@@ -0,0 +1,26 @@
+-- Every change to a glossary term, with a snapshot of the term after the change. A removal keeps
+-- the term as it was before it was removed, so that it can be restored. Revisions are numbered per
+-- term name, which outlives the term's record ID when a term is removed and added again.
+CREATE TABLE IF NOT EXISTS term_revision (
+  record_id          TEXT PRIMARY KEY,
+  guild_id           TEXT NOT NULL,
+  term_name          TEXT NOT NULL,
+  revision_number    INTEGER NOT NULL,
+  action             TEXT NOT NULL,
+  meaning            TEXT NOT NULL,
+  usage              TEXT,
+  links              TEXT[] DEFAULT ARRAY[]::TEXT[],
+  category           TEXT,
+  aliases            TEXT[] DEFAULT ARRAY[]::TEXT[],
+  editor_id          TEXT,
+  reverted_to        INTEGER,
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS term_revision_term_idx
+  ON term_revision (guild_id, LOWER(term_name), revision_number);
+
+-- Existing terms start with a revision for their current state, without an editor.
+INSERT INTO term_revision (record_id, guild_id, term_name, revision_number, action, meaning, usage, links, category, aliases)
+SELECT record_id, guild_id, term_name, 1, 'add', meaning, usage, links, category, aliases
+FROM term;
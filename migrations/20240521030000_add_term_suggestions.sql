@@ -0,0 +1,18 @@
+CREATE TABLE IF NOT EXISTS term_suggestion (
+  record_id          TEXT PRIMARY KEY,
+  guild_id           TEXT NOT NULL,
+  term_name          TEXT NOT NULL,
+  meaning            TEXT,
+  suggester_id       TEXT NOT NULL,
+  status             TEXT NOT NULL DEFAULT 'pending',
+  reviewer_id        TEXT,
+  decision_notes     TEXT,
+  staff_message_id   TEXT,
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  updated_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+-- Only one suggestion for a term can wait for review at a time
+CREATE UNIQUE INDEX IF NOT EXISTS term_suggestion_pending_idx
+  ON term_suggestion (guild_id, LOWER(term_name))
+  WHERE status = 'pending';
@@ -0,0 +1,31 @@
+-- Guilds without a channel don't get a term of the day
+CREATE TABLE IF NOT EXISTS term_of_the_day_channel (
+  guild_id     TEXT PRIMARY KEY,
+  channel_id   TEXT NOT NULL
+);
+
+-- Every term posted or skipped, numbered by the pass through the glossary it belongs to. A term
+-- is not chosen again until every term has been posted or skipped in the current cycle.
+CREATE TABLE IF NOT EXISTS term_of_the_day (
+  record_id    TEXT PRIMARY KEY,
+  guild_id     TEXT NOT NULL,
+  term_name    TEXT NOT NULL,
+  cycle        INTEGER NOT NULL,
+  skipped      BOOLEAN NOT NULL DEFAULT FALSE,
+  message_id   TEXT,
+  posted_at    TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS term_of_the_day_cycle_idx ON term_of_the_day (guild_id, cycle);
+
+-- Terms staff have queued to be posted next, oldest first
+CREATE TABLE IF NOT EXISTS term_of_the_day_pin (
+  record_id    TEXT PRIMARY KEY,
+  guild_id     TEXT NOT NULL,
+  term_name    TEXT NOT NULL,
+  pinned_by    TEXT NOT NULL,
+  pinned_at    TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS term_of_the_day_pin_term_idx
+  ON term_of_the_day_pin (guild_id, LOWER(term_name));
@@ -0,0 +1,427 @@
+use crate::config::{BloomBotEmbed, CHANNELS};
+use crate::database::{AppealData, AppealStatus, DatabaseHandler, ModCase};
+use crate::reports::{ephemeral_response, is_staff};
+use anyhow::Result;
+use poise::serenity_prelude::{
+  self as serenity, builder::*, ActionRowComponent, ButtonStyle, ComponentInteraction,
+  InputTextStyle, Mentionable, ModalInteraction,
+};
+use poise::ChoiceParameter;
+
+/// Custom IDs of the appeal buttons and modals start with this prefix, followed by the action and
+/// an ID. The Appeal button and the appeal modal use the case ID, e.g. `appeal:open:<case id>`,
+/// while the staff buttons use the appeal ID, e.g. `appeal:accept:<appeal id>`.
+pub const APPEAL_PREFIX: &str = "appeal:";
+
+fn appeal_custom_id(action: &str, id: &str) -> String {
+  format!("{}{}:{}", APPEAL_PREFIX, action, id)
+}
+
+/// Splits an appeal custom ID into its action and ID.
+fn parse_custom_id(custom_id: &str) -> Option<(&str, &str)> {
+  custom_id.strip_prefix(APPEAL_PREFIX)?.split_once(':')
+}
+
+/// The Appeal button attached to the notification sent to a member for a case.
+pub fn appeal_button(case_id: &str) -> Vec<CreateActionRow> {
+  vec![CreateActionRow::Buttons(vec![CreateButton::new(
+    appeal_custom_id("open", case_id),
+  )
+  .label("Appeal")
+  .style(ButtonStyle::Secondary)])]
+}
+
+async fn appeal_embed(
+  ctx: &serenity::Context,
+  appeal: &AppealData,
+  case: &ModCase,
+) -> Result<CreateEmbed> {
+  let user = appeal.user_id.to_user(ctx).await?;
+
+  let status = match appeal.reviewer_id {
+    Some(reviewer_id) if appeal.status != AppealStatus::Pending => {
+      format!("{} by {}", appeal.status.name(), reviewer_id.mention())
+    }
+    _ => appeal.status.name().to_string(),
+  };
+
+  let mut embed = BloomBotEmbed::new()
+    .title(format!(
+      "Appeal for Case #{} — {}",
+      case.case_number,
+      case.action.name()
+    ))
+    .author(CreateEmbedAuthor::new(user.name.clone()).icon_url(user.face()))
+    .description(appeal.appeal_text.clone())
+    .field(
+      "Original Reason",
+      case.reason.as_deref().unwrap_or("No reason provided."),
+      false,
+    );
+
+  if let Some(evidence_link) = &case.evidence_link {
+    embed = embed.field("Evidence", evidence_link, false);
+  }
+
+  embed = embed
+    .field("Status", status, false)
+    .footer(CreateEmbedFooter::new(format!("User ID: {}", user.id)))
+    .timestamp(serenity::Timestamp::from(appeal.created_at));
+
+  if let Some(decision_notes) = &appeal.decision_notes {
+    embed = embed.field("Decision Notes", decision_notes, false);
+  }
+
+  embed = match appeal.status {
+    AppealStatus::Accepted => embed.color(serenity::Color::DARK_GREEN),
+    AppealStatus::Rejected => embed.color(serenity::Color::LIGHT_GREY),
+    AppealStatus::Pending => embed,
+  };
+
+  Ok(embed)
+}
+
+/// Buttons for an appeal that is waiting for a decision. Decided appeals have no buttons.
+fn appeal_buttons(appeal: &AppealData) -> Vec<CreateActionRow> {
+  match appeal.status {
+    AppealStatus::Pending => vec![CreateActionRow::Buttons(vec![
+      CreateButton::new(appeal_custom_id("accept", &appeal.id))
+        .label("Accept")
+        .style(ButtonStyle::Success),
+      CreateButton::new(appeal_custom_id("reject", &appeal.id))
+        .label("Reject")
+        .style(ButtonStyle::Danger),
+    ])],
+    _ => vec![],
+  }
+}
+
+/// Returns the value of a text input in a submitted modal, if it isn't blank.
+fn input_value(submit: &ModalInteraction, custom_id: &str) -> Option<String> {
+  submit
+    .data
+    .components
+    .iter()
+    .flat_map(|row| row.components.iter())
+    .find_map(|component| match component {
+      ActionRowComponent::InputText(input) if input.custom_id == custom_id => input.value.clone(),
+      _ => None,
+    })
+    .filter(|value| !value.trim().is_empty())
+}
+
+/// Handles the Appeal button on a member's notification, which opens the appeal form, and the
+/// Accept and Reject buttons on an appeal, which ask staff for decision notes.
+pub async fn handle_button(
+  ctx: &serenity::Context,
+  database: &DatabaseHandler,
+  press: &ComponentInteraction,
+) -> Result<()> {
+  let (action, id) = match parse_custom_id(&press.data.custom_id) {
+    Some(parsed) => parsed,
+    None => return Ok(()),
+  };
+
+  let mut transaction = database.start_transaction_with_retry(5).await?;
+
+  match action {
+    "open" => {
+      let case = match DatabaseHandler::get_mod_case(&mut transaction, id).await? {
+        Some(case) if case.user_id == press.user.id => case,
+        Some(_) => {
+          press
+            .create_response(
+              ctx,
+              ephemeral_response(":x: You can only appeal your own cases."),
+            )
+            .await?;
+          return Ok(());
+        }
+        None => {
+          press
+            .create_response(ctx, ephemeral_response(":x: This case no longer exists."))
+            .await?;
+          return Ok(());
+        }
+      };
+
+      if DatabaseHandler::appeal_exists(&mut transaction, &case.id).await? {
+        press
+          .create_response(
+            ctx,
+            ephemeral_response(":x: You have already appealed this case."),
+          )
+          .await?;
+        return Ok(());
+      }
+
+      press
+        .create_response(
+          ctx,
+          CreateInteractionResponse::Modal(
+            CreateModal::new(
+              appeal_custom_id("submit", &case.id),
+              format!("Appeal Case #{}", case.case_number),
+            )
+            .components(vec![CreateActionRow::InputText(
+              CreateInputText::new(
+                InputTextStyle::Paragraph,
+                "Why should this be reconsidered?",
+                "appeal",
+              )
+              .required(true)
+              .max_length(1000),
+            )]),
+          ),
+        )
+        .await?;
+    }
+    "accept" | "reject" => {
+      if !is_staff(ctx, press.guild_id, &press.user).await? {
+        press
+          .create_response(
+            ctx,
+            ephemeral_response(":x: Only staff can decide appeals."),
+          )
+          .await?;
+        return Ok(());
+      }
+
+      match DatabaseHandler::get_appeal(&mut transaction, id).await? {
+        Some(appeal) if appeal.status == AppealStatus::Pending => {}
+        Some(_) => {
+          press
+            .create_response(
+              ctx,
+              ephemeral_response(":x: This appeal has already been decided."),
+            )
+            .await?;
+          return Ok(());
+        }
+        None => {
+          press
+            .create_response(ctx, ephemeral_response(":x: Appeal does not exist."))
+            .await?;
+          return Ok(());
+        }
+      };
+
+      let title = match action {
+        "accept" => "Accept Appeal",
+        _ => "Reject Appeal",
+      };
+
+      press
+        .create_response(
+          ctx,
+          CreateInteractionResponse::Modal(
+            CreateModal::new(appeal_custom_id(action, id), title).components(vec![
+              CreateActionRow::InputText(
+                CreateInputText::new(
+                  InputTextStyle::Paragraph,
+                  "Decision notes (sent to the member)",
+                  "notes",
+                )
+                .required(false)
+                .max_length(1000),
+              ),
+            ]),
+          ),
+        )
+        .await?;
+    }
+    _ => {}
+  }
+
+  Ok(())
+}
+
+/// Handles a submitted appeal, which is posted to the appeal channel for staff, and the decision
+/// notes submitted when accepting or rejecting an appeal, which are sent to the member.
+pub async fn handle_modal(
+  ctx: &serenity::Context,
+  database: &DatabaseHandler,
+  submit: &ModalInteraction,
+) -> Result<()> {
+  let (action, id) = match parse_custom_id(&submit.data.custom_id) {
+    Some(parsed) => parsed,
+    None => return Ok(()),
+  };
+
+  match action {
+    "submit" => submit_appeal(ctx, database, submit, id).await,
+    "accept" => decide_appeal(ctx, database, submit, id, AppealStatus::Accepted).await,
+    "reject" => decide_appeal(ctx, database, submit, id, AppealStatus::Rejected).await,
+    _ => Ok(()),
+  }
+}
+
+async fn submit_appeal(
+  ctx: &serenity::Context,
+  database: &DatabaseHandler,
+  submit: &ModalInteraction,
+  case_id: &str,
+) -> Result<()> {
+  let appeal_text = input_value(submit, "appeal").unwrap_or_default();
+
+  let mut transaction = database.start_transaction_with_retry(5).await?;
+  let case = match DatabaseHandler::get_mod_case(&mut transaction, case_id).await? {
+    Some(case) if case.user_id == submit.user.id => case,
+    _ => {
+      submit
+        .create_response(
+          ctx,
+          ephemeral_response(":x: This case can no longer be appealed."),
+        )
+        .await?;
+      return Ok(());
+    }
+  };
+
+  let appeal = match DatabaseHandler::add_appeal(
+    &mut transaction,
+    &case.guild_id,
+    &case.id,
+    &submit.user.id,
+    &appeal_text,
+  )
+  .await?
+  {
+    Some(appeal) => appeal,
+    None => {
+      submit
+        .create_response(
+          ctx,
+          ephemeral_response(":x: You have already appealed this case."),
+        )
+        .await?;
+      return Ok(());
+    }
+  };
+
+  let staff_message = serenity::ChannelId::new(CHANNELS.appealchannel)
+    .send_message(
+      ctx,
+      CreateMessage::new()
+        .embed(appeal_embed(ctx, &appeal, &case).await?)
+        .components(appeal_buttons(&appeal)),
+    )
+    .await?;
+
+  DatabaseHandler::set_appeal_message(&mut transaction, &appeal.id, &staff_message.id).await?;
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  submit
+    .create_response(
+      ctx,
+      ephemeral_response(
+        "Your appeal has been sent to the moderation team. You will be notified of their decision.",
+      ),
+    )
+    .await?;
+
+  Ok(())
+}
+
+async fn decide_appeal(
+  ctx: &serenity::Context,
+  database: &DatabaseHandler,
+  submit: &ModalInteraction,
+  appeal_id: &str,
+  status: AppealStatus,
+) -> Result<()> {
+  if !is_staff(ctx, submit.guild_id, &submit.user).await? {
+    submit
+      .create_response(
+        ctx,
+        ephemeral_response(":x: Only staff can decide appeals."),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let notes = input_value(submit, "notes");
+
+  let mut transaction = database.start_transaction_with_retry(5).await?;
+  let appeal = match DatabaseHandler::get_appeal(&mut transaction, appeal_id).await? {
+    Some(appeal) if appeal.status == AppealStatus::Pending => appeal,
+    Some(_) => {
+      submit
+        .create_response(
+          ctx,
+          ephemeral_response(":x: This appeal has already been decided."),
+        )
+        .await?;
+      return Ok(());
+    }
+    None => {
+      submit
+        .create_response(ctx, ephemeral_response(":x: Appeal does not exist."))
+        .await?;
+      return Ok(());
+    }
+  };
+  let case = match DatabaseHandler::get_mod_case(&mut transaction, &appeal.case_id).await? {
+    Some(case) => case,
+    None => {
+      submit
+        .create_response(ctx, ephemeral_response(":x: Case does not exist."))
+        .await?;
+      return Ok(());
+    }
+  };
+
+  DatabaseHandler::update_appeal_status(
+    &mut transaction,
+    &appeal.id,
+    status,
+    &submit.user.id,
+    notes.as_deref(),
+  )
+  .await?;
+
+  let appeal = DatabaseHandler::get_appeal(&mut transaction, &appeal.id)
+    .await?
+    .unwrap_or(appeal);
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  submit
+    .create_response(
+      ctx,
+      CreateInteractionResponse::UpdateMessage(
+        CreateInteractionResponseMessage::new()
+          .embed(appeal_embed(ctx, &appeal, &case).await?)
+          .components(appeal_buttons(&appeal)),
+      ),
+    )
+    .await?;
+
+  let mut dm_embed = BloomBotEmbed::new().title(format!(
+    "Your appeal for case #{} has been {}.",
+    case.case_number,
+    status.as_str()
+  ));
+  if let Some(notes) = &appeal.decision_notes {
+    dm_embed = dm_embed.description(format!("**Notes**: {}", notes));
+  }
+
+  if appeal
+    .user_id
+    .create_dm_channel(ctx)
+    .await?
+    .send_message(ctx, CreateMessage::new().embed(dm_embed))
+    .await
+    .is_err()
+  {
+    submit
+      .create_followup(
+        ctx,
+        CreateInteractionResponseFollowup::new()
+          .content(":warning: Could not notify the member of the decision via DM.")
+          .ephemeral(true),
+      )
+      .await?;
+  }
+
+  Ok(())
+}
@@ -0,0 +1,161 @@
+use crate::database::{BadgeStats, DatabaseHandler};
+use crate::Context;
+use anyhow::Result;
+use poise::serenity_prelude::{self as serenity, Mentionable};
+use poise::CreateReply;
+
+/// Badges recognize milestones without using up Discord roles. Each badge has an unlock
+/// predicate that is evaluated against the user's [`BadgeStats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Badge {
+  FirstSit,
+  HundredSessions,
+  EarlyBird,
+  LongSit,
+  ChallengeSeeker,
+}
+
+impl Badge {
+  pub const ALL: [Badge; 5] = [
+    Badge::FirstSit,
+    Badge::HundredSessions,
+    Badge::EarlyBird,
+    Badge::LongSit,
+    Badge::ChallengeSeeker,
+  ];
+
+  /// The key stored in the database. Must never change once a badge has been released.
+  pub fn key(&self) -> &'static str {
+    match self {
+      Badge::FirstSit => "first_sit",
+      Badge::HundredSessions => "hundred_sessions",
+      Badge::EarlyBird => "early_bird",
+      Badge::LongSit => "long_sit",
+      Badge::ChallengeSeeker => "challenge_seeker",
+    }
+  }
+
+  pub fn from_key(key: &str) -> Option<Badge> {
+    Badge::ALL.into_iter().find(|badge| badge.key() == key)
+  }
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      Badge::FirstSit => "First Sit",
+      Badge::HundredSessions => "Centurion",
+      Badge::EarlyBird => "Early Bird",
+      Badge::LongSit => "Deep Dive",
+      Badge::ChallengeSeeker => "Challenge Seeker",
+    }
+  }
+
+  pub fn emoji(&self) -> &'static str {
+    match self {
+      Badge::FirstSit => "🌱",
+      Badge::HundredSessions => "💯",
+      Badge::EarlyBird => "🌅",
+      Badge::LongSit => "🌊",
+      Badge::ChallengeSeeker => "🏔️",
+    }
+  }
+
+  pub fn description(&self) -> &'static str {
+    match self {
+      Badge::FirstSit => "Log your first meditation session",
+      Badge::HundredSessions => "Log 100 meditation sessions",
+      Badge::EarlyBird => "Meditate before 7am on 7 days in a row",
+      Badge::LongSit => "Meditate for 60 minutes in a single session",
+      Badge::ChallengeSeeker => "Join 3 challenges",
+    }
+  }
+
+  pub fn is_unlocked(&self, stats: &BadgeStats) -> bool {
+    match self {
+      Badge::FirstSit => stats.session_count >= 1,
+      Badge::HundredSessions => stats.session_count >= 100,
+      Badge::EarlyBird => longest_run(&stats.early_days) >= 7,
+      Badge::LongSit => stats.longest_session >= 60,
+      Badge::ChallengeSeeker => stats.challenges_joined >= 3,
+    }
+  }
+}
+
+/// Returns the length of the longest run of consecutive days. Expects the days to be sorted
+/// and distinct.
+fn longest_run(days: &[chrono::NaiveDate]) -> usize {
+  let mut longest = 0;
+  let mut current = 0;
+  let mut previous: Option<chrono::NaiveDate> = None;
+
+  for day in days {
+    current = match previous {
+      Some(previous) if *day - previous == chrono::Duration::days(1) => current + 1,
+      _ => 1,
+    };
+    longest = longest.max(current);
+    previous = Some(*day);
+  }
+
+  longest
+}
+
+/// Evaluates every badge the user has not unlocked yet, storing and returning the ones that
+/// are now unlocked. The transaction is not committed.
+pub async fn evaluate_badges(
+  transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+  guild_id: &serenity::GuildId,
+  user_id: &serenity::UserId,
+) -> Result<Vec<Badge>> {
+  let unlocked = DatabaseHandler::get_user_badges(transaction, guild_id, user_id).await?;
+  let stats = DatabaseHandler::get_badge_stats(transaction, guild_id, user_id).await?;
+
+  let mut new_badges = Vec::new();
+
+  for badge in Badge::ALL {
+    if unlocked
+      .iter()
+      .any(|unlocked| unlocked.badge_key == badge.key())
+    {
+      continue;
+    }
+
+    if badge.is_unlocked(&stats) {
+      DatabaseHandler::add_user_badge(transaction, guild_id, user_id, badge.key()).await?;
+      new_badges.push(badge);
+    }
+  }
+
+  Ok(new_badges)
+}
+
+/// Checks for newly unlocked badges after an event, such as adding time or joining a
+/// challenge, and congratulates the user on any new ones.
+pub async fn check_badges(
+  ctx: Context<'_>,
+  guild_id: serenity::GuildId,
+  user_id: serenity::UserId,
+  privacy: bool,
+) -> Result<()> {
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let new_badges = evaluate_badges(&mut transaction, &guild_id, &user_id).await?;
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  for badge in new_badges {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            ":tada: Congrats to {}, you've unlocked the {} **{}** badge: {}! See all of your badges with `/badges`.",
+            user_id.mention(),
+            badge.emoji(),
+            badge.name(),
+            badge.description().to_lowercase()
+          ))
+          .allowed_mentions(serenity::CreateAllowedMentions::new())
+          .ephemeral(privacy),
+      )
+      .await?;
+  }
+
+  Ok(())
+}
@@ -1,3 +1,6 @@
+use crate::badges::check_badges;
+use crate::commands::buddy::nudge_buddies;
+use crate::commands::challenge::check_challenge_completion;
 use crate::commands::{commit_and_say, MessageType};
 use crate::config::{BloomBotEmbed, StreakRoles, TimeSumRoles, CHANNELS};
 use crate::database::{DatabaseHandler, TrackingProfile};
@@ -409,6 +412,17 @@ pub async fn add(
         log_channel
           .send_message(ctx, CreateMessage::new().embed(log_embed))
           .await?;
+
+        // The entry is already saved, so a failure here shouldn't fail the command
+        if let Err(err) = check_challenge_completion(ctx, guild_id, user_id, privacy).await {
+          error!("Error checking challenge completion: {}", err);
+        }
+        if let Err(err) = nudge_buddies(ctx, guild_id, user_id, minutes, privacy).await {
+          error!("Error nudging buddies: {}", err);
+        }
+        if let Err(err) = check_badges(ctx, guild_id, user_id, privacy).await {
+          error!("Error checking badges: {}", err);
+        }
       }
 
       return Ok(());
@@ -443,6 +457,17 @@ pub async fn add(
     ctx.say(format!("Awesome sauce! This server has collectively generated {} hours of realmbreaking meditation!", time_in_hours)).await?;
   }
 
+  // The entry is already saved, so a failure here shouldn't fail the command
+  if let Err(err) = check_challenge_completion(ctx, guild_id, user_id, privacy).await {
+    error!("Error checking challenge completion: {}", err);
+  }
+  if let Err(err) = nudge_buddies(ctx, guild_id, user_id, minutes, privacy).await {
+    error!("Error nudging buddies: {}", err);
+  }
+  if let Err(err) = check_badges(ctx, guild_id, user_id, privacy).await {
+    error!("Error checking badges: {}", err);
+  }
+
   let guild = ctx.guild().unwrap().clone();
   let member = guild.member(ctx, user_id).await?;
 
@@ -0,0 +1,100 @@
+use crate::badges::Badge;
+use crate::config::{BloomBotEmbed, ROLES};
+use crate::database::{DatabaseHandler, TrackingProfile};
+use crate::Context;
+use anyhow::Result;
+use poise::serenity_prelude::{self as serenity, builder::*};
+use poise::CreateReply;
+
+/// See your meditation badges
+///
+/// Shows the badges you have unlocked and the ones still waiting for you.
+///
+/// Can also be used to check another member's badges, unless their stats are set to private.
+#[poise::command(slash_command, category = "Meditation Tracking", guild_only)]
+pub async fn badges(
+  ctx: Context<'_>,
+  #[description = "The user to show the badges of (Defaults to you)"] user: Option<serenity::User>,
+) -> Result<()> {
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+
+  let user = user.unwrap_or_else(|| ctx.author().clone());
+  let user_nick_or_name = match user.nick_in(&ctx, guild_id).await {
+    Some(nick) => nick,
+    None => user.name.clone(),
+  };
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+
+  let tracking_profile =
+    match DatabaseHandler::get_tracking_profile(&mut transaction, &guild_id, &user.id).await? {
+      Some(tracking_profile) => tracking_profile,
+      None => TrackingProfile {
+        ..Default::default()
+      },
+    };
+
+  if ctx.author().id != user.id
+    && tracking_profile.stats_private
+    && !ctx.author().has_role(&ctx, guild_id, ROLES.staff).await?
+  {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "Sorry, {}'s stats are set to private.",
+            user_nick_or_name
+          ))
+          .ephemeral(true)
+          .allowed_mentions(serenity::CreateAllowedMentions::new()),
+      )
+      .await?;
+
+    return Ok(());
+  }
+
+  let unlocked = DatabaseHandler::get_user_badges(&mut transaction, &guild_id, &user.id).await?;
+
+  let mut embed = BloomBotEmbed::new()
+    .author(CreateEmbedAuthor::new(format!("{}'s Badges", user_nick_or_name)).icon_url(user.face()))
+    .description(format!(
+      "Unlocked {} of {} badges.",
+      unlocked.len(),
+      Badge::ALL.len()
+    ));
+
+  for badge in Badge::ALL {
+    match unlocked
+      .iter()
+      .find(|unlocked| unlocked.badge_key == badge.key())
+    {
+      Some(unlocked) => {
+        embed = embed.field(
+          format!("{} {}", badge.emoji(), badge.name()),
+          format!(
+            "{}\nUnlocked {}",
+            badge.description(),
+            unlocked.unlocked_at.format("%Y-%m-%d")
+          ),
+          true,
+        );
+      }
+      None => {
+        embed = embed.field(format!("🔒 {}", badge.name()), badge.description(), true);
+      }
+    }
+  }
+
+  ctx
+    .send(
+      CreateReply::default()
+        .embed(embed)
+        .ephemeral(tracking_profile.stats_private),
+    )
+    .await?;
+
+  Ok(())
+}
@@ -0,0 +1,252 @@
+use crate::commands::starboard::autocomplete_board;
+use crate::commands::{commit_and_say, MessageType};
+use crate::config::{BloomBotEmbed, EMBED_COLOR};
+use crate::database::{DatabaseHandler, ReactionBoard};
+use crate::reaction_boards;
+use crate::Context;
+use anyhow::Result;
+use poise::serenity_prelude::{self as serenity, Mentionable};
+use poise::CreateReply;
+use ulid::Ulid;
+
+/// Commands for managing reaction boards
+///
+/// Commands to add, remove, or list reaction boards. A reaction board works like the starboard, but with its own emoji, channel, threshold and embed styling.
+///
+/// Requires `Manage Roles` permissions.
+#[poise::command(
+  slash_command,
+  required_permissions = "MANAGE_ROLES",
+  default_member_permissions = "MANAGE_ROLES",
+  category = "Moderator Commands",
+  subcommands("add", "remove", "list"),
+  subcommand_required,
+  //hide_in_help,
+  guild_only
+)]
+pub async fn boards(_: Context<'_>) -> Result<()> {
+  Ok(())
+}
+
+/// Parses an emoji given as a unicode emoji or a custom emoji like `<:name:id>`.
+fn parse_emoji(emoji: &str) -> Option<serenity::ReactionType> {
+  match serenity::ReactionType::try_from(emoji.trim()) {
+    Ok(serenity::ReactionType::Unicode(emoji))
+      if emoji
+        .chars()
+        .any(|c| c.is_whitespace() || c.is_ascii_alphanumeric()) =>
+    {
+      None
+    }
+    Ok(emoji) => Some(emoji),
+    Err(_) => None,
+  }
+}
+
+/// Parses a hex color like `#FDAC2E`.
+fn parse_color(color: &str) -> Option<u32> {
+  u32::from_str_radix(color.trim().trim_start_matches('#'), 16)
+    .ok()
+    .filter(|color| *color <= 0xFFFFFF)
+}
+
+/// Add a reaction board
+///
+/// Adds a reaction board. Messages are posted to the board's channel once they receive enough of the board's emoji, not counting the author's own reaction or reactions from bots.
+#[poise::command(slash_command)]
+pub async fn add(
+  ctx: Context<'_>,
+  #[description = "Name of the board"] board_name: String,
+  #[description = "The emoji that posts messages to the board"] emoji: String,
+  #[description = "The channel to post messages to"]
+  #[channel_types("Text")]
+  channel: serenity::GuildChannel,
+  #[description = "Reactions needed to reach the board"]
+  #[min = 1]
+  threshold: i32,
+  #[description = "Embed color as a hex code (Defaults to the bot's color)"] color: Option<String>,
+  #[description = "Label for the reaction count (Defaults to \"Times reacted\")"]
+  footer_label: Option<String>,
+) -> Result<()> {
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+
+  let emoji = match parse_emoji(&emoji) {
+    Some(emoji) => emoji,
+    None => {
+      ctx
+        .send(
+          CreateReply::default()
+            .content(":x: Invalid emoji. Please use a single emoji.")
+            .ephemeral(true),
+        )
+        .await?;
+      return Ok(());
+    }
+  };
+
+  let color = match color {
+    Some(color) => match parse_color(&color) {
+      Some(color) => color,
+      None => {
+        ctx
+          .send(
+            CreateReply::default()
+              .content(":x: Invalid color. Please use a hex code like `#FDAC2E`.")
+              .ephemeral(true),
+          )
+          .await?;
+        return Ok(());
+      }
+    },
+    None => EMBED_COLOR,
+  };
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+
+  let boards = reaction_boards::get_boards(&mut transaction, &guild_id).await?;
+
+  if boards
+    .iter()
+    .any(|board| board.name.eq_ignore_ascii_case(board_name.trim()))
+  {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(":x: A board with that name already exists.")
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  if boards.iter().any(|board| board.matches(&emoji)) {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(":x: Another board already uses that emoji.")
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let board = ReactionBoard {
+    id: Ulid::new().to_string(),
+    name: board_name.trim().to_string(),
+    emoji,
+    channel_id: channel.id,
+    min_reactions: u64::try_from(threshold).unwrap_or(1),
+    color,
+    footer_label: footer_label.unwrap_or("Times reacted".to_string()),
+  };
+
+  DatabaseHandler::add_reaction_board(&mut transaction, &guild_id, &board).await?;
+
+  commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      ":white_check_mark: Board has been added. Messages with {} {} reactions will be posted to {}.",
+      board.min_reactions,
+      board.emoji,
+      channel.mention()
+    )),
+    true,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Remove a reaction board
+///
+/// Removes a reaction board and its entries. Messages already posted to the board's channel are left in place.
+#[poise::command(slash_command)]
+pub async fn remove(
+  ctx: Context<'_>,
+  #[description = "Name of the board"]
+  #[autocomplete = "autocomplete_board"]
+  board_name: String,
+) -> Result<()> {
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+
+  if board_name
+    .trim()
+    .eq_ignore_ascii_case(&ReactionBoard::starboard().name)
+  {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(":x: The starboard can't be removed.")
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+
+  if !DatabaseHandler::remove_reaction_board(&mut transaction, &guild_id, board_name.trim()).await?
+  {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(":x: Board does not exist.")
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(":white_check_mark: Board has been removed.".to_string()),
+    true,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// List the reaction boards
+///
+/// Lists the reaction boards, including the starboard.
+#[poise::command(slash_command)]
+pub async fn list(ctx: Context<'_>) -> Result<()> {
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+  let boards = reaction_boards::get_boards(&mut transaction, &guild_id).await?;
+
+  let mut embed = BloomBotEmbed::new().title("Reaction Boards");
+
+  for board in boards {
+    embed = embed.field(
+      board.name.clone(),
+      format!(
+        "{} in {}\nThreshold: {}\nFooter: {}",
+        board.emoji,
+        board.channel_id.mention(),
+        board.min_reactions,
+        board.footer(board.min_reactions)
+      ),
+      false,
+    );
+  }
+
+  ctx
+    .send(CreateReply::default().embed(embed).ephemeral(true))
+    .await?;
+
+  Ok(())
+}
@@ -0,0 +1,453 @@
+use crate::commands::{commit_and_say, MessageType};
+use crate::config::BloomBotEmbed;
+use crate::database::{BuddyNotifications, DatabaseHandler, TrackingProfile};
+use crate::Context;
+use anyhow::Result;
+use log::error;
+use poise::serenity_prelude::{self as serenity, builder::*, Mentionable};
+use poise::{ChoiceParameter, CreateReply};
+
+/// Commands for meditation buddies
+///
+/// Pair up with an accountability partner, see how you compare, and choose how you are notified when your buddy meditates.
+#[poise::command(
+  slash_command,
+  category = "Meditation Tracking",
+  subcommands("request", "remove", "status", "notifications"),
+  subcommand_required,
+  guild_only
+)]
+pub async fn buddy(_: Context<'_>) -> Result<()> {
+  Ok(())
+}
+
+/// Ask another member to be your meditation buddy
+///
+/// Sends a buddy request that the other member can accept or decline. Requests expire after 24 hours.
+#[poise::command(slash_command)]
+pub async fn request(
+  ctx: Context<'_>,
+  #[description = "The member you want to pair up with"] user: serenity::User,
+) -> Result<()> {
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+  let user_id = ctx.author().id;
+
+  if user.id == user_id || user.bot {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(":x: You can't be buddies with yourself or a bot.")
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+
+  if DatabaseHandler::buddy_request_exists(&mut transaction, &guild_id, &user_id, &user.id).await? {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            ":x: You are already buddies with {}, or there is a pending request between you.",
+            user.mention()
+          ))
+          .allowed_mentions(serenity::CreateAllowedMentions::new())
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  DatabaseHandler::add_buddy_request(&mut transaction, &guild_id, &user_id, &user.id).await?;
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  let ctx_id = ctx.id();
+  let accept_id = format!("{}accept", ctx_id);
+  let decline_id = format!("{}decline", ctx_id);
+
+  let request_message = ctx
+    .send(
+      CreateReply::default()
+        .content(format!(
+          "{}, {} would like to be your meditation buddy! Buddies can compare progress with `/buddy status` and get notified when the other meditates.",
+          user.mention(),
+          ctx.author().mention()
+        ))
+        .allowed_mentions(serenity::CreateAllowedMentions::new().users(vec![user.id]))
+        .components(vec![CreateActionRow::Buttons(vec![
+          CreateButton::new(accept_id.clone())
+            .label("Accept")
+            .style(serenity::ButtonStyle::Success),
+          CreateButton::new(decline_id.clone())
+            .label("Decline")
+            .style(serenity::ButtonStyle::Danger),
+        ])]),
+    )
+    .await?;
+
+  // Loop through incoming interactions with the buttons
+  while let Some(press) = serenity::ComponentInteractionCollector::new(ctx)
+    // We defined our button IDs to start with `ctx_id`. If they don't, some other command's
+    // button was pressed
+    .filter(move |press| press.data.custom_id.starts_with(&ctx_id.to_string()))
+    // Timeout when the request has not been answered in 24 hours, when it also expires in the
+    // database
+    .timeout(std::time::Duration::from_secs(3600 * 24))
+    .await
+  {
+    if press.data.custom_id != accept_id && press.data.custom_id != decline_id {
+      // This is an unrelated button interaction
+      continue;
+    }
+
+    if press.user.id != user.id {
+      press
+        .create_response(
+          ctx,
+          CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::new()
+              .content(":x: Only the member who was asked can answer this request.")
+              .ephemeral(true),
+          ),
+        )
+        .await?;
+      continue;
+    }
+
+    let accepted = press.data.custom_id == accept_id;
+    let mut transaction = data.db.start_transaction_with_retry(5).await?;
+
+    let response = if accepted {
+      if DatabaseHandler::accept_buddy_request(&mut transaction, &guild_id, &user_id, &user.id)
+        .await?
+      {
+        format!(
+          ":handshake: {} and {} are now meditation buddies! Use `/buddy notifications` to choose how you hear about each other's sessions.",
+          ctx.author().mention(),
+          user.mention()
+        )
+      } else {
+        "This buddy request is no longer available.".to_string()
+      }
+    } else {
+      DatabaseHandler::remove_buddy(&mut transaction, &guild_id, &user_id, &user.id).await?;
+      format!(
+        "{} has declined the buddy request from {}.",
+        user.mention(),
+        ctx.author().mention()
+      )
+    };
+
+    DatabaseHandler::commit_transaction(transaction).await?;
+
+    press
+      .create_response(
+        ctx,
+        CreateInteractionResponse::UpdateMessage(
+          CreateInteractionResponseMessage::new()
+            .content(response)
+            .allowed_mentions(serenity::CreateAllowedMentions::new())
+            .components(Vec::new()),
+        ),
+      )
+      .await?;
+
+    return Ok(());
+  }
+
+  // The request was not answered in time, so remove it again
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+  DatabaseHandler::remove_buddy(&mut transaction, &guild_id, &user_id, &user.id).await?;
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  request_message
+    .edit(
+      ctx,
+      CreateReply::default()
+        .content(format!(
+          "The buddy request from {} to {} has expired.",
+          ctx.author().mention(),
+          user.mention()
+        ))
+        .allowed_mentions(serenity::CreateAllowedMentions::new())
+        .components(Vec::new()),
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// Stop being meditation buddies with someone
+///
+/// Removes a member from your meditation buddies.
+#[poise::command(slash_command)]
+pub async fn remove(
+  ctx: Context<'_>,
+  #[description = "The buddy you want to remove"] user: serenity::User,
+) -> Result<()> {
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+  let user_id = ctx.author().id;
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+
+  if DatabaseHandler::remove_buddy(&mut transaction, &guild_id, &user_id, &user.id).await? == 0 {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(":x: That member is not your buddy.")
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      ":white_check_mark: You are no longer buddies with {}.",
+      user.mention()
+    )),
+    true,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Choose how you hear about your buddy's meditation
+///
+/// Get a DM nudge whenever your buddy adds a session, a daily digest of their sessions, or no notifications at all.
+#[poise::command(slash_command)]
+pub async fn notifications(
+  ctx: Context<'_>,
+  #[description = "How you want to be notified"] notifications: BuddyNotifications,
+  #[description = "The buddy to change notifications for (Defaults to all buddies)"] user: Option<
+    serenity::User,
+  >,
+) -> Result<()> {
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+  let user_id = ctx.author().id;
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+
+  let updated = DatabaseHandler::set_buddy_notifications(
+    &mut transaction,
+    &guild_id,
+    &user_id,
+    user.as_ref().map(|user| &user.id),
+    notifications,
+  )
+  .await?;
+
+  if updated == 0 {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(match user {
+            Some(_) => ":x: That member is not your buddy.",
+            None => ":x: You don't have any buddies yet. Use `/buddy request` to find one!",
+          })
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      ":white_check_mark: Buddy notifications set to **{}**.",
+      notifications.name()
+    )),
+    true,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Compare your progress with your buddies
+///
+/// Compares your current streak and minutes over the last 7 days with your buddies. Values your buddy has set to private are hidden.
+#[poise::command(slash_command)]
+pub async fn status(
+  ctx: Context<'_>,
+  #[description = "The buddy to compare with (Defaults to all buddies)"] user: Option<
+    serenity::User,
+  >,
+) -> Result<()> {
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+  let user_id = ctx.author().id;
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+
+  let buddies: Vec<serenity::UserId> =
+    DatabaseHandler::get_buddies(&mut transaction, &guild_id, &user_id)
+      .await?
+      .into_iter()
+      .map(|buddy| buddy.buddy_id)
+      .filter(|buddy_id| match &user {
+        Some(user) => *buddy_id == user.id,
+        None => true,
+      })
+      .collect();
+
+  if buddies.is_empty() {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(match user {
+            Some(_) => ":x: That member is not your buddy.",
+            None => ":x: You don't have any buddies yet. Use `/buddy request` to find one!",
+          })
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let end_time = chrono::Utc::now();
+  let start_time = end_time - chrono::Duration::days(7);
+
+  let own_streak = DatabaseHandler::get_streak(&mut transaction, &guild_id, &user_id).await?;
+  let own_minutes = DatabaseHandler::get_user_meditation_sum_between(
+    &mut transaction,
+    &guild_id,
+    &user_id,
+    start_time,
+    end_time,
+  )
+  .await?;
+
+  let mut embed = BloomBotEmbed::new()
+    .title("Buddy Status")
+    .description(format!(
+      "**You**\nStreak: {} days\nLast 7 days: {} minutes",
+      own_streak, own_minutes
+    ));
+
+  for buddy_id in buddies {
+    let tracking_profile =
+      match DatabaseHandler::get_tracking_profile(&mut transaction, &guild_id, &buddy_id).await? {
+        Some(tracking_profile) => tracking_profile,
+        None => TrackingProfile {
+          ..Default::default()
+        },
+      };
+
+    let streak = match tracking_profile.streaks_private {
+      true => "Private".to_string(),
+      false => format!(
+        "{} days",
+        DatabaseHandler::get_streak(&mut transaction, &guild_id, &buddy_id).await?
+      ),
+    };
+
+    let minutes = match tracking_profile.stats_private {
+      true => "Private".to_string(),
+      false => format!(
+        "{} minutes",
+        DatabaseHandler::get_user_meditation_sum_between(
+          &mut transaction,
+          &guild_id,
+          &buddy_id,
+          start_time,
+          end_time,
+        )
+        .await?
+      ),
+    };
+
+    let buddy_name = match buddy_id.to_user(ctx).await {
+      Ok(buddy) => match buddy.nick_in(&ctx, guild_id).await {
+        Some(nick) => nick,
+        None => buddy.name,
+      },
+      Err(_) => buddy_id.to_string(),
+    };
+
+    embed = embed.field(
+      buddy_name,
+      format!("Streak: {}\nLast 7 days: {}", streak, minutes),
+      true,
+    );
+  }
+
+  ctx
+    .send(CreateReply::default().embed(embed).ephemeral(true))
+    .await?;
+
+  Ok(())
+}
+
+/// Sends a DM to every buddy of the user who asked to be nudged when they meditate
+///
+/// The number of minutes is left out if the entry was added privately, or if the user keeps
+/// their stats private or tracks anonymously.
+pub async fn nudge_buddies(
+  ctx: Context<'_>,
+  guild_id: serenity::GuildId,
+  user_id: serenity::UserId,
+  minutes: i32,
+  privacy: bool,
+) -> Result<()> {
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let buddies =
+    DatabaseHandler::get_buddies_to_nudge(&mut transaction, &guild_id, &user_id).await?;
+
+  if buddies.is_empty() {
+    return Ok(());
+  }
+
+  let stats_private =
+    match DatabaseHandler::get_tracking_profile(&mut transaction, &guild_id, &user_id).await? {
+      Some(tracking_profile) => {
+        tracking_profile.stats_private || tracking_profile.anonymous_tracking
+      }
+      None => false,
+    };
+  drop(transaction);
+
+  let author_name = match ctx.author_member().await {
+    Some(member) => member.display_name().to_string(),
+    None => ctx.author().name.clone(),
+  };
+
+  let nudge = match privacy || stats_private {
+    true => format!(
+      ":bell: Your buddy **{}** just logged a meditation session. Have you meditated today?",
+      author_name
+    ),
+    false => format!(
+      ":bell: Your buddy **{}** just meditated for {} minutes. Have you meditated today?",
+      author_name, minutes
+    ),
+  };
+
+  for buddy_id in buddies {
+    if let Err(err) = buddy_id
+      .direct_message(ctx, CreateMessage::new().content(&nudge))
+      .await
+    {
+      error!("Error sending buddy nudge: {}", err);
+    }
+  }
+
+  Ok(())
+}
@@ -1,7 +1,13 @@
-use crate::config::ROLES;
+use crate::badges::check_badges;
+use crate::commands::{commit_and_say, MessageType};
+use crate::config::{BloomBotEmbed, ROLES};
+use crate::database::{ChallengeData, ChallengeStanding, DatabaseHandler, TeamStanding};
+use crate::pagination::{PageRowRef, Pagination};
 use crate::Context;
 use anyhow::Result;
 use chrono;
+use log::error;
+use poise::serenity_prelude::{self as serenity, builder::*, Mentionable};
 use poise::CreateReply;
 
 #[derive(poise::ChoiceParameter)]
@@ -12,13 +18,181 @@ pub enum ChallengeChoices {
   YearRound,
 }
 
+async fn autocomplete_open_challenge(ctx: Context<'_>, partial: &str) -> Vec<String> {
+  let guild_id = match ctx.guild_id() {
+    Some(guild_id) => guild_id,
+    None => return Vec::new(),
+  };
+
+  let mut transaction = match ctx.data().db.start_transaction_with_retry(5).await {
+    Ok(transaction) => transaction,
+    Err(_) => return Vec::new(),
+  };
+
+  let challenges = DatabaseHandler::get_open_challenges(&mut transaction, &guild_id)
+    .await
+    .unwrap_or_default();
+
+  filter_challenge_names(challenges, partial)
+}
+
+async fn autocomplete_any_challenge(ctx: Context<'_>, partial: &str) -> Vec<String> {
+  let guild_id = match ctx.guild_id() {
+    Some(guild_id) => guild_id,
+    None => return Vec::new(),
+  };
+
+  let mut transaction = match ctx.data().db.start_transaction_with_retry(5).await {
+    Ok(transaction) => transaction,
+    Err(_) => return Vec::new(),
+  };
+
+  let challenges = DatabaseHandler::get_all_challenges(&mut transaction, &guild_id)
+    .await
+    .unwrap_or_default();
+
+  filter_challenge_names(challenges, partial)
+}
+
+/// Finds the value given so far for another string option of the command being autocompleted,
+/// looking inside subcommands.
+fn partial_string_arg(ctx: Context<'_>, name: &str) -> Option<String> {
+  fn find(options: &[serenity::CommandDataOption], name: &str) -> Option<String> {
+    options.iter().find_map(|option| match &option.value {
+      serenity::CommandDataOptionValue::SubCommand(options)
+      | serenity::CommandDataOptionValue::SubCommandGroup(options) => find(options, name),
+      serenity::CommandDataOptionValue::String(value) if option.name == name => Some(value.clone()),
+      _ => None,
+    })
+  }
+
+  match ctx {
+    poise::Context::Application(ctx) => find(&ctx.interaction.data.options, name),
+    poise::Context::Prefix(_) => None,
+  }
+}
+
+/// Suggests the teams of the custom challenge chosen in the same command.
+async fn autocomplete_team(ctx: Context<'_>, partial: &str) -> Vec<String> {
+  let guild_id = match ctx.guild_id() {
+    Some(guild_id) => guild_id,
+    None => return Vec::new(),
+  };
+
+  // Only custom challenges have teams
+  let challenge_name = match partial_string_arg(ctx, "custom") {
+    Some(challenge_name) => challenge_name,
+    None => return Vec::new(),
+  };
+
+  let mut transaction = match ctx.data().db.start_transaction_with_retry(5).await {
+    Ok(transaction) => transaction,
+    Err(_) => return Vec::new(),
+  };
+
+  let challenge =
+    match DatabaseHandler::get_challenge(&mut transaction, &guild_id, &challenge_name).await {
+      Ok(Some(challenge)) => challenge,
+      _ => return Vec::new(),
+    };
+
+  let partial = partial.to_lowercase();
+
+  DatabaseHandler::get_challenge_teams(&mut transaction, &challenge.id)
+    .await
+    .unwrap_or_default()
+    .into_iter()
+    .map(|team| team.team_name)
+    .filter(|name| name.to_lowercase().contains(&partial))
+    .take(25)
+    .collect()
+}
+
+fn filter_challenge_names(challenges: Vec<ChallengeData>, partial: &str) -> Vec<String> {
+  let partial = partial.to_lowercase();
+
+  challenges
+    .into_iter()
+    .map(|challenge| challenge.challenge_name)
+    .filter(|name| name.to_lowercase().contains(&partial))
+    .take(25)
+    .collect()
+}
+
+/// Checks the user's active custom challenges after time has been added
+///
+/// Any challenge whose goal has been reached is marked as complete, and the reward role is
+/// awarded if the challenge has one.
+pub async fn check_challenge_completion(
+  ctx: Context<'_>,
+  guild_id: serenity::GuildId,
+  user_id: serenity::UserId,
+  privacy: bool,
+) -> Result<()> {
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let completed: Vec<ChallengeData> =
+    DatabaseHandler::get_user_challenges(&mut transaction, &guild_id, &user_id)
+      .await?
+      .into_iter()
+      .filter(|(challenge, progress)| {
+        challenge.is_active()
+          && progress.completed_at.is_none()
+          && progress.value(challenge.goal_type) >= i64::from(challenge.goal_target)
+      })
+      .map(|(challenge, _)| challenge)
+      .collect();
+
+  if completed.is_empty() {
+    return Ok(());
+  }
+
+  for challenge in &completed {
+    DatabaseHandler::complete_challenge(&mut transaction, &user_id, &challenge.id).await?;
+  }
+
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  let member = guild_id.member(ctx, user_id).await?;
+
+  for challenge in completed {
+    let reward = match challenge.reward_role {
+      Some(reward_role) => match member.add_role(ctx, reward_role).await {
+        Ok(_) => format!(" You've earned the <@&{}> role!", reward_role),
+        Err(err) => {
+          error!("Error adding challenge reward role: {}", err);
+          String::new()
+        }
+      },
+      None => String::new(),
+    };
+
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            ":tada: Congrats to {}, you've completed the **{}** challenge by reaching {}!{}",
+            member.mention(),
+            challenge.challenge_name,
+            challenge.describe_goal(),
+            reward
+          ))
+          .allowed_mentions(serenity::CreateAllowedMentions::new())
+          .ephemeral(privacy),
+      )
+      .await?;
+  }
+
+  Ok(())
+}
+
 /// Join or leave a meditation challenge
 ///
-/// Join or leave the monthly or 365-day meditation challenge.
+/// Join or leave the monthly or 365-day meditation challenge, or any custom challenge created by staff. See the standings with `progress`.
 #[poise::command(
   slash_command,
   category = "Meditation Tracking",
-  subcommands("join", "leave"),
+  subcommands("join", "leave", "progress"),
   guild_only
 )]
 pub async fn challenge(_: Context<'_>) -> Result<()> {
@@ -27,15 +201,26 @@ pub async fn challenge(_: Context<'_>) -> Result<()> {
 
 /// Join a meditation challenge
 ///
-/// Join the monthly or 365-day meditation challenge.
+/// Join the monthly or 365-day meditation challenge, or a custom challenge by name.
 #[poise::command(slash_command)]
 pub async fn join(
   ctx: Context<'_>,
   #[description = "Challenge you wish to join (Defaults to monthly)"] challenge: Option<
     ChallengeChoices,
   >,
+  #[description = "Name of a custom challenge you wish to join"]
+  #[autocomplete = "autocomplete_open_challenge"]
+  custom: Option<String>,
+  #[description = "Team you wish to join (Required for team challenges)"]
+  #[autocomplete = "autocomplete_team"]
+  team: Option<String>,
 ) -> Result<()> {
   let guild_id = ctx.guild_id().unwrap();
+
+  if let Some(custom) = custom {
+    return join_custom(ctx, guild_id, custom, team).await;
+  }
+
   let member = guild_id.member(ctx, ctx.author().id).await?;
 
   match challenge {
@@ -127,17 +312,207 @@ pub async fn join(
   }
 }
 
+async fn join_custom(
+  ctx: Context<'_>,
+  guild_id: serenity::GuildId,
+  challenge_name: String,
+  team_name: Option<String>,
+) -> Result<()> {
+  let user_id = ctx.author().id;
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let challenge =
+    match DatabaseHandler::get_challenge(&mut transaction, &guild_id, &challenge_name).await? {
+      Some(challenge) => challenge,
+      None => {
+        ctx
+          .send(
+            CreateReply::default()
+              .content(":x: Challenge does not exist.")
+              .ephemeral(true),
+          )
+          .await?;
+
+        return Ok(());
+      }
+    };
+
+  if challenge.has_ended() {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "The **{}** challenge has already ended. Keep an eye out for the next one!",
+            challenge.challenge_name
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+
+    return Ok(());
+  }
+
+  if DatabaseHandler::challenge_participant_exists(&mut transaction, &challenge.id, &user_id)
+    .await?
+  {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "You've already joined the **{}** challenge. Awesome!",
+            challenge.challenge_name
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+
+    return Ok(());
+  }
+
+  let teams = DatabaseHandler::get_challenge_teams(&mut transaction, &challenge.id).await?;
+
+  let team = if teams.is_empty() {
+    None
+  } else {
+    let team = team_name.and_then(|team_name| {
+      teams
+        .into_iter()
+        .find(|team| team.team_name.eq_ignore_ascii_case(team_name.trim()))
+    });
+
+    match team {
+      Some(team) => Some(team),
+      None => {
+        ctx
+          .send(
+            CreateReply::default()
+              .content(format!(
+                ":x: The **{}** challenge is a team challenge. Please choose one of its teams with the `team` option.",
+                challenge.challenge_name
+              ))
+              .ephemeral(true),
+          )
+          .await?;
+
+        return Ok(());
+      }
+    }
+  };
+
+  DatabaseHandler::join_challenge(
+    &mut transaction,
+    &guild_id,
+    &user_id,
+    &challenge.id,
+    team.as_ref().map(|team| team.id.as_str()),
+  )
+  .await?;
+
+  let team_text = match &team {
+    Some(team) => format!(" as part of team **{}**", team.team_name),
+    None => String::new(),
+  };
+
+  commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "Challenge accepted! You're awesome, <@{}>! You've joined the **{}** challenge{}. Reach {} between {} and {}, and `/add` your times to track your progress. Let's do this!",
+      user_id,
+      challenge.challenge_name,
+      team_text,
+      challenge.describe_goal(),
+      challenge.start_date.format("%B %d"),
+      challenge.end_date.format("%B %d"),
+    )),
+    false,
+  )
+  .await?;
+
+  // The member has already joined, so a failure here shouldn't fail the command
+  if let Err(err) = check_badges(ctx, guild_id, user_id, false).await {
+    error!("Error checking badges: {}", err);
+  }
+
+  Ok(())
+}
+
+async fn leave_custom(
+  ctx: Context<'_>,
+  guild_id: serenity::GuildId,
+  challenge_name: String,
+) -> Result<()> {
+  let user_id = ctx.author().id;
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let challenge =
+    match DatabaseHandler::get_challenge(&mut transaction, &guild_id, &challenge_name).await? {
+      Some(challenge) => challenge,
+      None => {
+        ctx
+          .send(
+            CreateReply::default()
+              .content(":x: Challenge does not exist.")
+              .ephemeral(true),
+          )
+          .await?;
+
+        return Ok(());
+      }
+    };
+
+  if !DatabaseHandler::challenge_participant_exists(&mut transaction, &challenge.id, &user_id)
+    .await?
+  {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "You're not currently participating in the **{}** challenge. If you want to join, use `/challenge join`.",
+            challenge.challenge_name
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+
+    return Ok(());
+  }
+
+  DatabaseHandler::leave_challenge(&mut transaction, &user_id, &challenge.id).await?;
+
+  commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "You have successfully opted out of the **{}** challenge, <@{}>.",
+      challenge.challenge_name, user_id,
+    )),
+    false,
+  )
+  .await?;
+
+  Ok(())
+}
+
 /// Leave a meditation challenge
 ///
-/// Leave the monthly or 365-day meditation challenge.
+/// Leave the monthly or 365-day meditation challenge, or a custom challenge by name.
 #[poise::command(slash_command)]
 pub async fn leave(
   ctx: Context<'_>,
   #[description = "Challenge you wish to leave (Defaults to monthly)"] challenge: Option<
     ChallengeChoices,
   >,
+  #[description = "Name of a custom challenge you wish to leave"]
+  #[autocomplete = "autocomplete_open_challenge"]
+  custom: Option<String>,
 ) -> Result<()> {
   let guild_id = ctx.guild_id().unwrap();
+
+  if let Some(custom) = custom {
+    return leave_custom(ctx, guild_id, custom).await;
+  }
+
   let member = guild_id.member(ctx, ctx.author().id).await?;
 
   match challenge {
@@ -229,3 +604,249 @@ pub async fn leave(
     }
   }
 }
+
+/// Show progress in meditation challenges
+///
+/// Shows the standings for a custom challenge. If no challenge is specified, shows your progress in the custom challenges you have joined.
+#[poise::command(slash_command)]
+pub async fn progress(
+  ctx: Context<'_>,
+  #[description = "Challenge to show the standings for (Defaults to your challenges)"]
+  #[autocomplete = "autocomplete_any_challenge"]
+  challenge: Option<String>,
+  #[description = "The page to show"] page: Option<usize>,
+) -> Result<()> {
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+  let user_id = ctx.author().id;
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+
+  let challenge_name = match challenge {
+    Some(challenge_name) => challenge_name,
+    None => {
+      let challenges =
+        DatabaseHandler::get_user_challenges(&mut transaction, &guild_id, &user_id).await?;
+
+      let mut embed = BloomBotEmbed::new()
+        .author(CreateEmbedAuthor::new("Your Challenge Progress").icon_url(ctx.author().face()));
+
+      if challenges.is_empty() {
+        embed = embed.description(
+          "You haven't joined any ongoing custom challenges. Use `/challenge join` to join one!",
+        );
+      }
+
+      for (challenge, progress) in challenges {
+        let value = progress.value(challenge.goal_type);
+        let percentage = (value * 100 / i64::from(challenge.goal_target.max(1))).min(100);
+        let status = match progress.completed_at {
+          Some(_) => ":white_check_mark: Completed".to_string(),
+          None if challenge.is_active() => {
+            format!("Ends {}", challenge.end_date.format("%Y-%m-%d"))
+          }
+          None => format!("Starts {}", challenge.start_date.format("%Y-%m-%d")),
+        };
+
+        embed = embed.field(
+          challenge.challenge_name.clone(),
+          format!(
+            "```{} / {} {} ({}%)```{}",
+            value,
+            challenge.goal_target,
+            challenge.goal_type.as_str(),
+            percentage,
+            status
+          ),
+          false,
+        );
+      }
+
+      ctx
+        .send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+      return Ok(());
+    }
+  };
+
+  let challenge =
+    match DatabaseHandler::get_challenge(&mut transaction, &guild_id, &challenge_name).await? {
+      Some(challenge) => challenge,
+      None => {
+        ctx
+          .send(
+            CreateReply::default()
+              .content(":x: Challenge does not exist.")
+              .ephemeral(true),
+          )
+          .await?;
+
+        return Ok(());
+      }
+    };
+
+  // Define some unique identifiers for the navigation buttons
+  let ctx_id = ctx.id();
+  let prev_button_id = format!("{}prev", ctx_id);
+  let next_button_id = format!("{}next", ctx_id);
+
+  let mut current_page = page.unwrap_or(0).saturating_sub(1);
+
+  let standings: Vec<ChallengeStanding> =
+    DatabaseHandler::get_challenge_standings(&mut transaction, &challenge)
+      .await?
+      .into_iter()
+      .enumerate()
+      .map(|(index, progress)| ChallengeStanding {
+        rank: index + 1,
+        goal_type: challenge.goal_type,
+        goal_target: challenge.goal_target,
+        progress,
+      })
+      .collect();
+  let standings: Vec<PageRowRef> = standings.iter().map(|standing| standing as _).collect();
+
+  // Team challenges show the team totals above the individual standings. Once the results have
+  // been posted, the recorded results are shown instead of recalculating them.
+  let team_standings = match challenge.has_ended() {
+    true => DatabaseHandler::get_team_results(&mut transaction, &challenge.id).await?,
+    false => Vec::new(),
+  };
+  // Results are recorded a day after the challenge ends, and until then the live totals can
+  // still change, so only recorded results are final
+  let is_final = !team_standings.is_empty();
+  let team_standings = match team_standings.is_empty() {
+    true => DatabaseHandler::get_team_standings(&mut transaction, &challenge).await?,
+    false => team_standings,
+  };
+  let team_embed = match team_standings.is_empty() {
+    true => None,
+    false => Some(team_standings_embed(&challenge, &team_standings, is_final)),
+  };
+
+  drop(transaction);
+  let pagination =
+    Pagination::new(format!("{} Standings", challenge.challenge_name), standings).await?;
+
+  if pagination.get_page(current_page).is_none() {
+    current_page = pagination.get_last_page_number();
+  }
+
+  let page_embeds = |page: usize| -> Vec<CreateEmbed> {
+    let mut embeds: Vec<CreateEmbed> = team_embed.iter().cloned().collect();
+    embeds.push(pagination.create_page_embed(page));
+    embeds
+  };
+
+  ctx
+    .send({
+      let mut f = CreateReply::default();
+      if pagination.get_page_count() > 1 {
+        f = f.components(vec![CreateActionRow::Buttons(vec![
+          CreateButton::new(&prev_button_id).label("Previous"),
+          CreateButton::new(&next_button_id).label("Next"),
+        ])])
+      }
+      f.embeds = page_embeds(current_page);
+      f.ephemeral(true)
+    })
+    .await?;
+
+  // Loop through incoming interactions with the navigation buttons
+  while let Some(press) = serenity::ComponentInteractionCollector::new(ctx)
+    // We defined our button IDs to start with `ctx_id`. If they don't, some other command's
+    // button was pressed
+    .filter(move |press| press.data.custom_id.starts_with(&ctx_id.to_string()))
+    // Timeout when no navigation button has been pressed for 24 hours
+    .timeout(std::time::Duration::from_secs(3600 * 24))
+    .await
+  {
+    // Depending on which button was pressed, go to next or previous page
+    if press.data.custom_id == next_button_id {
+      current_page = pagination.update_page_number(current_page, 1);
+    } else if press.data.custom_id == prev_button_id {
+      current_page = pagination.update_page_number(current_page, -1);
+    } else {
+      // This is an unrelated button interaction
+      continue;
+    }
+
+    // Update the message with the new page contents
+    press
+      .create_response(
+        ctx,
+        CreateInteractionResponse::UpdateMessage(
+          CreateInteractionResponseMessage::new().embeds(page_embeds(current_page)),
+        ),
+      )
+      .await?;
+  }
+
+  Ok(())
+}
+
+/// Builds the embed showing the team totals for a team challenge
+///
+/// Used for the periodically updated standings message, the final results posted to the
+/// announcement channel, and `/challenge progress`.
+pub fn team_standings_embed(
+  challenge: &ChallengeData,
+  standings: &[TeamStanding],
+  is_final: bool,
+) -> CreateEmbed {
+  let rows = standings
+    .iter()
+    .enumerate()
+    .map(|(index, standing)| {
+      let place = match index {
+        0 => ":first_place:".to_string(),
+        1 => ":second_place:".to_string(),
+        2 => ":third_place:".to_string(),
+        _ => format!("**{}.**", index + 1),
+      };
+
+      format!(
+        "{} **{}**: {} minutes ({} {})",
+        place,
+        standing.team_name,
+        standing.total_minutes,
+        standing.member_count,
+        if standing.member_count == 1 {
+          "member"
+        } else {
+          "members"
+        }
+      )
+    })
+    .collect::<Vec<String>>()
+    .join("\n");
+
+  if is_final {
+    let winner = match standings.first() {
+      Some(winner) => format!(" Congratulations to team **{}**!", winner.team_name),
+      None => String::new(),
+    };
+
+    BloomBotEmbed::new()
+      .title(format!("{} Final Results", challenge.challenge_name))
+      .description(format!(
+        "The **{}** challenge has ended!{} Thank you to everyone who took part.\n\n{}",
+        challenge.challenge_name, winner, rows
+      ))
+  } else {
+    BloomBotEmbed::new()
+      .title(format!("{} Team Standings", challenge.challenge_name))
+      .description(match rows.is_empty() {
+        true => "No teams have been added yet.".to_string(),
+        false => rows,
+      })
+      .footer(CreateEmbedFooter::new(format!(
+        "Ends {} • Last updated",
+        challenge.end_date.format("%Y-%m-%d")
+      )))
+      .timestamp(serenity::Timestamp::now())
+  }
+}
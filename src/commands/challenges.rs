@@ -0,0 +1,605 @@
+use crate::commands::challenge::team_standings_embed;
+use crate::commands::{commit_and_say, MessageType};
+use crate::database::{ChallengeData, ChallengeGoal, DatabaseHandler};
+use crate::pagination::{PageRowRef, Pagination};
+use crate::Context;
+use anyhow::Result;
+use poise::serenity_prelude::{self as serenity, builder::*};
+use poise::CreateReply;
+use ulid::Ulid;
+
+/// Commands for managing custom challenges
+///
+/// Commands to add, edit, list, or remove custom meditation challenges, and to manage the teams and standings of team challenges.
+///
+/// Requires `Manage Roles` permissions.
+#[poise::command(
+  slash_command,
+  required_permissions = "MANAGE_ROLES",
+  default_member_permissions = "MANAGE_ROLES",
+  category = "Moderator Commands",
+  subcommands("add", "edit", "list", "remove", "teams", "standings"),
+  subcommand_required,
+  //hide_in_help,
+  guild_only
+)]
+pub async fn challenges(_: Context<'_>) -> Result<()> {
+  Ok(())
+}
+
+fn parse_date(date: &str) -> Option<chrono::NaiveDate> {
+  chrono::NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").ok()
+}
+
+/// Returns an error message if the role cannot be used as a reward role.
+fn validate_reward_role(guild_id: serenity::GuildId, role: &serenity::Role) -> Option<&str> {
+  if !role.guild_id.eq(&guild_id) {
+    return Some(":x: The reward role must be in the same guild as the command.");
+  }
+  if role.managed {
+    return Some(":x: The reward role must not be a bot role.");
+  }
+  if role.permissions.administrator() {
+    return Some(":x: The reward role must not be an administrator role.");
+  }
+
+  None
+}
+
+/// Add a custom challenge
+///
+/// Adds a custom challenge that members can join with `/challenge join`. Dates are inclusive and use the format `YYYY-MM-DD`.
+#[poise::command(slash_command)]
+pub async fn add(
+  ctx: Context<'_>,
+  #[description = "Name of the challenge"] challenge_name: String,
+  #[description = "First day of the challenge (YYYY-MM-DD)"] start_date: String,
+  #[description = "Last day of the challenge (YYYY-MM-DD)"] end_date: String,
+  #[description = "What participants are working towards"] goal_type: ChallengeGoal,
+  #[description = "Amount needed to complete the challenge"]
+  #[min = 1]
+  goal_target: i32,
+  #[description = "Role to be given to participants who complete the challenge"]
+  reward_role: Option<serenity::Role>,
+) -> Result<()> {
+  ctx.defer_ephemeral().await?;
+
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+
+  let (start_date, end_date) = match (parse_date(&start_date), parse_date(&end_date)) {
+    (Some(start_date), Some(end_date)) => (start_date, end_date),
+    _ => {
+      ctx
+        .say(":x: Invalid date. Please use the format `YYYY-MM-DD`.")
+        .await?;
+      return Ok(());
+    }
+  };
+
+  if end_date < start_date {
+    ctx
+      .say(":x: The end date must not be before the start date.")
+      .await?;
+    return Ok(());
+  }
+
+  if let Some(role) = &reward_role {
+    if let Some(message) = validate_reward_role(guild_id, role) {
+      ctx.say(message).await?;
+      return Ok(());
+    }
+  }
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+  if DatabaseHandler::get_challenge(&mut transaction, &guild_id, challenge_name.as_str())
+    .await?
+    .is_some()
+  {
+    ctx.say(":x: Challenge already exists.").await?;
+    return Ok(());
+  }
+
+  let challenge = ChallengeData {
+    id: Ulid::new().to_string(),
+    challenge_name,
+    start_date,
+    end_date,
+    goal_type,
+    goal_target,
+    reward_role: reward_role.map(|role| role.id),
+  };
+
+  DatabaseHandler::add_challenge(&mut transaction, &guild_id, &challenge).await?;
+
+  commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(":white_check_mark: Challenge has been added.".to_string()),
+    true,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Update an existing custom challenge
+///
+/// Updates the dates, goal, or reward role of an existing custom challenge.
+#[allow(clippy::too_many_arguments)]
+#[poise::command(slash_command)]
+pub async fn edit(
+  ctx: Context<'_>,
+  #[description = "Name of the challenge"] challenge_name: String,
+  #[description = "Update the first day of the challenge (YYYY-MM-DD)"] start_date: Option<String>,
+  #[description = "Update the last day of the challenge (YYYY-MM-DD)"] end_date: Option<String>,
+  #[description = "Update what participants are working towards"] goal_type: Option<ChallengeGoal>,
+  #[description = "Update the amount needed to complete the challenge"]
+  #[min = 1]
+  goal_target: Option<i32>,
+  #[description = "Update the role given to participants who complete the challenge"]
+  reward_role: Option<serenity::Role>,
+  #[description = "Remove the reward role from the challenge"] remove_reward_role: Option<bool>,
+) -> Result<()> {
+  ctx.defer_ephemeral().await?;
+
+  let remove_reward_role = remove_reward_role.unwrap_or(false);
+
+  if start_date.is_none()
+    && end_date.is_none()
+    && goal_type.is_none()
+    && goal_target.is_none()
+    && reward_role.is_none()
+    && !remove_reward_role
+  {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(":x: No changes were provided.")
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+  let mut challenge =
+    match DatabaseHandler::get_challenge(&mut transaction, &guild_id, challenge_name.as_str())
+      .await?
+    {
+      Some(challenge) => challenge,
+      None => {
+        ctx.say(":x: Challenge does not exist.").await?;
+        return Ok(());
+      }
+    };
+
+  if let Some(start_date) = start_date {
+    challenge.start_date = match parse_date(&start_date) {
+      Some(start_date) => start_date,
+      None => {
+        ctx
+          .say(":x: Invalid start date. Please use the format `YYYY-MM-DD`.")
+          .await?;
+        return Ok(());
+      }
+    };
+  }
+
+  if let Some(end_date) = end_date {
+    challenge.end_date = match parse_date(&end_date) {
+      Some(end_date) => end_date,
+      None => {
+        ctx
+          .say(":x: Invalid end date. Please use the format `YYYY-MM-DD`.")
+          .await?;
+        return Ok(());
+      }
+    };
+  }
+
+  if challenge.end_date < challenge.start_date {
+    ctx
+      .say(":x: The end date must not be before the start date.")
+      .await?;
+    return Ok(());
+  }
+
+  if let Some(goal_type) = goal_type {
+    challenge.goal_type = goal_type;
+  }
+
+  if let Some(goal_target) = goal_target {
+    challenge.goal_target = goal_target;
+  }
+
+  if let Some(role) = reward_role {
+    if let Some(message) = validate_reward_role(guild_id, &role) {
+      ctx.say(message).await?;
+      return Ok(());
+    }
+    challenge.reward_role = Some(role.id);
+  } else if remove_reward_role {
+    challenge.reward_role = None;
+  }
+
+  DatabaseHandler::edit_challenge(&mut transaction, &challenge).await?;
+
+  commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(":white_check_mark: Challenge has been updated.".to_string()),
+    true,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// List all custom challenges
+///
+/// Lists all custom challenges in the database.
+#[poise::command(slash_command)]
+pub async fn list(
+  ctx: Context<'_>,
+  #[description = "The page to show"] page: Option<usize>,
+) -> Result<()> {
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+
+  // Define some unique identifiers for the navigation buttons
+  let ctx_id = ctx.id();
+  let prev_button_id = format!("{}prev", ctx_id);
+  let next_button_id = format!("{}next", ctx_id);
+
+  let mut current_page = page.unwrap_or(0).saturating_sub(1);
+
+  let challenges = DatabaseHandler::get_all_challenges(&mut transaction, &guild_id).await?;
+  let challenges: Vec<PageRowRef> = challenges.iter().map(|challenge| challenge as _).collect();
+  drop(transaction);
+  let pagination = Pagination::new("Challenges", challenges).await?;
+
+  if pagination.get_page(current_page).is_none() {
+    current_page = pagination.get_last_page_number();
+  }
+
+  let first_page = pagination.create_page_embed(current_page);
+
+  ctx
+    .send({
+      let mut f = CreateReply::default();
+      if pagination.get_page_count() > 1 {
+        f = f.components(vec![CreateActionRow::Buttons(vec![
+          CreateButton::new(&prev_button_id).label("Previous"),
+          CreateButton::new(&next_button_id).label("Next"),
+        ])])
+      }
+      f.embeds = vec![first_page];
+      f.ephemeral(true)
+    })
+    .await?;
+
+  // Loop through incoming interactions with the navigation buttons
+  while let Some(press) = serenity::ComponentInteractionCollector::new(ctx)
+    // We defined our button IDs to start with `ctx_id`. If they don't, some other command's
+    // button was pressed
+    .filter(move |press| press.data.custom_id.starts_with(&ctx_id.to_string()))
+    // Timeout when no navigation button has been pressed for 24 hours
+    .timeout(std::time::Duration::from_secs(3600 * 24))
+    .await
+  {
+    // Depending on which button was pressed, go to next or previous page
+    if press.data.custom_id == next_button_id {
+      current_page = pagination.update_page_number(current_page, 1);
+    } else if press.data.custom_id == prev_button_id {
+      current_page = pagination.update_page_number(current_page, -1);
+    } else {
+      // This is an unrelated button interaction
+      continue;
+    }
+
+    // Update the message with the new page contents
+    press
+      .create_response(
+        ctx,
+        CreateInteractionResponse::UpdateMessage(
+          CreateInteractionResponseMessage::new().embed(pagination.create_page_embed(current_page)),
+        ),
+      )
+      .await?;
+  }
+
+  Ok(())
+}
+
+/// Remove a custom challenge from the database
+///
+/// Removes a custom challenge and all of its participants from the database.
+#[poise::command(slash_command)]
+pub async fn remove(
+  ctx: Context<'_>,
+  #[description = "Name of the challenge"] challenge_name: String,
+) -> Result<()> {
+  ctx.defer_ephemeral().await?;
+
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+  let challenge =
+    match DatabaseHandler::get_challenge(&mut transaction, &guild_id, challenge_name.as_str())
+      .await?
+    {
+      Some(challenge) => challenge,
+      None => {
+        ctx.say(":x: Challenge does not exist.").await?;
+        return Ok(());
+      }
+    };
+
+  DatabaseHandler::remove_challenge(&mut transaction, &challenge.id).await?;
+
+  commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(":white_check_mark: Challenge has been removed.".to_string()),
+    true,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Commands for managing challenge teams
+///
+/// Commands to add, remove, or list the teams of a team challenge. A challenge becomes a team challenge once it has at least one team.
+#[poise::command(slash_command, subcommands("add_team", "remove_team", "list_teams"))]
+pub async fn teams(_: Context<'_>) -> Result<()> {
+  Ok(())
+}
+
+/// Add a team to a challenge
+///
+/// Adds a team to a custom challenge. Members choose their team when joining the challenge.
+#[poise::command(slash_command, rename = "add")]
+pub async fn add_team(
+  ctx: Context<'_>,
+  #[description = "Name of the challenge"] challenge_name: String,
+  #[description = "Name of the team"] team_name: String,
+) -> Result<()> {
+  ctx.defer_ephemeral().await?;
+
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+  let challenge =
+    match DatabaseHandler::get_challenge(&mut transaction, &guild_id, challenge_name.as_str())
+      .await?
+    {
+      Some(challenge) => challenge,
+      None => {
+        ctx.say(":x: Challenge does not exist.").await?;
+        return Ok(());
+      }
+    };
+
+  if challenge.has_ended() {
+    ctx
+      .say(":x: Teams cannot be added to a challenge that has ended.")
+      .await?;
+    return Ok(());
+  }
+
+  let team_name = team_name.trim();
+
+  if DatabaseHandler::get_challenge_team(&mut transaction, &challenge.id, team_name)
+    .await?
+    .is_some()
+  {
+    ctx.say(":x: Team already exists.").await?;
+    return Ok(());
+  }
+
+  DatabaseHandler::add_challenge_team(&mut transaction, &challenge.id, team_name).await?;
+
+  commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      ":white_check_mark: Team **{}** has been added to the **{}** challenge.",
+      team_name, challenge.challenge_name
+    )),
+    true,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Remove a team from a challenge
+///
+/// Removes a team from a custom challenge. Members of the team stay in the challenge, but no longer count towards a team.
+#[poise::command(slash_command, rename = "remove")]
+pub async fn remove_team(
+  ctx: Context<'_>,
+  #[description = "Name of the challenge"] challenge_name: String,
+  #[description = "Name of the team"] team_name: String,
+) -> Result<()> {
+  ctx.defer_ephemeral().await?;
+
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+  let challenge =
+    match DatabaseHandler::get_challenge(&mut transaction, &guild_id, challenge_name.as_str())
+      .await?
+    {
+      Some(challenge) => challenge,
+      None => {
+        ctx.say(":x: Challenge does not exist.").await?;
+        return Ok(());
+      }
+    };
+
+  let team =
+    match DatabaseHandler::get_challenge_team(&mut transaction, &challenge.id, team_name.as_str())
+      .await?
+    {
+      Some(team) => team,
+      None => {
+        ctx.say(":x: Team does not exist.").await?;
+        return Ok(());
+      }
+    };
+
+  DatabaseHandler::remove_challenge_team(&mut transaction, &team.id).await?;
+
+  commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(":white_check_mark: Team has been removed.".to_string()),
+    true,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// List the teams of a challenge
+///
+/// Lists the teams of a custom challenge along with their current totals.
+#[poise::command(slash_command, rename = "list")]
+pub async fn list_teams(
+  ctx: Context<'_>,
+  #[description = "Name of the challenge"] challenge_name: String,
+) -> Result<()> {
+  ctx.defer_ephemeral().await?;
+
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+  let challenge =
+    match DatabaseHandler::get_challenge(&mut transaction, &guild_id, challenge_name.as_str())
+      .await?
+    {
+      Some(challenge) => challenge,
+      None => {
+        ctx.say(":x: Challenge does not exist.").await?;
+        return Ok(());
+      }
+    };
+
+  let standings = DatabaseHandler::get_team_standings(&mut transaction, &challenge).await?;
+
+  ctx
+    .send(
+      CreateReply::default()
+        .embed(team_standings_embed(&challenge, &standings, false))
+        .ephemeral(true),
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// Post live team standings for a challenge
+///
+/// Posts the team standings for a team challenge in a channel. The message is updated periodically until the challenge ends, at which point the final results are posted in the announcement channel.
+#[poise::command(slash_command)]
+pub async fn standings(
+  ctx: Context<'_>,
+  #[description = "Name of the challenge"] challenge_name: String,
+  #[description = "Channel to post the standings in (Defaults to this channel)"]
+  #[channel_types("Text")]
+  channel: Option<serenity::GuildChannel>,
+) -> Result<()> {
+  ctx.defer_ephemeral().await?;
+
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+  let challenge =
+    match DatabaseHandler::get_challenge(&mut transaction, &guild_id, challenge_name.as_str())
+      .await?
+    {
+      Some(challenge) => challenge,
+      None => {
+        ctx.say(":x: Challenge does not exist.").await?;
+        return Ok(());
+      }
+    };
+
+  if challenge.has_ended() {
+    ctx
+      .say(":x: That challenge has ended. Use `/challenge progress` to see the results.")
+      .await?;
+    return Ok(());
+  }
+
+  let standings = DatabaseHandler::get_team_standings(&mut transaction, &challenge).await?;
+
+  if standings.is_empty() {
+    ctx
+      .say(":x: That challenge has no teams. Add some with `/challenges teams add` first.")
+      .await?;
+    return Ok(());
+  }
+
+  let channel_id = match channel {
+    Some(channel) => channel.id,
+    None => ctx.channel_id(),
+  };
+
+  let message = channel_id
+    .send_message(
+      ctx,
+      CreateMessage::new().embed(team_standings_embed(&challenge, &standings, false)),
+    )
+    .await?;
+
+  DatabaseHandler::set_challenge_standings_message(
+    &mut transaction,
+    &challenge.id,
+    &channel_id,
+    &message.id,
+  )
+  .await?;
+
+  commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      ":white_check_mark: Standings have been posted in <#{}> and will be updated periodically.",
+      channel_id
+    )),
+    true,
+  )
+  .await?;
+
+  Ok(())
+}
@@ -1,8 +1,50 @@
+use crate::commands::rank_suggestions;
 use crate::config::{BloomBotEmbed, CHANNELS};
 use crate::database::DatabaseHandler;
 use crate::Context;
 use anyhow::Result;
 use poise::serenity_prelude as serenity;
+use std::collections::hash_map::{Entry, HashMap};
+
+/// Suggests the courses the member is enrolled in, meaning they have the course's participant role
+/// in the course's server.
+async fn autocomplete_enrolled_course(ctx: Context<'_>, partial: &str) -> Vec<String> {
+  let mut transaction = match ctx.data().db.start_transaction_with_retry(5).await {
+    Ok(transaction) => transaction,
+    Err(_) => return Vec::new(),
+  };
+
+  let courses = DatabaseHandler::get_courses_in_dm(&mut transaction)
+    .await
+    .unwrap_or_default();
+  drop(transaction);
+
+  // Look up the member once per server rather than once per course
+  let mut member_roles: HashMap<serenity::GuildId, Vec<serenity::RoleId>> = HashMap::new();
+  let mut enrolled = Vec::new();
+
+  for course in courses {
+    let roles = match member_roles.entry(course.guild_id) {
+      Entry::Occupied(entry) => entry.into_mut(),
+      Entry::Vacant(entry) => {
+        let roles = match course.guild_id.member(ctx, ctx.author().id).await {
+          Ok(member) => member.roles,
+          Err(_) => Vec::new(),
+        };
+        entry.insert(roles)
+      }
+    };
+
+    if roles.contains(&course.participant_role) {
+      enrolled.push(course);
+    }
+  }
+
+  rank_suggestions(partial, enrolled, |course| vec![course.course_name.clone()])
+    .into_iter()
+    .map(|course| course.course_name)
+    .collect()
+}
 
 /// Indicate that you have completed a course
 ///
@@ -18,7 +60,9 @@ use poise::serenity_prelude as serenity;
 )]
 pub async fn complete(
   ctx: Context<'_>,
-  #[description = "The course you have completed"] course_name: String,
+  #[description = "The course you have completed"]
+  #[autocomplete = "autocomplete_enrolled_course"]
+  course_name: String,
 ) -> Result<()> {
   let data = ctx.data();
 
@@ -1,4 +1,4 @@
-use crate::commands::{commit_and_say, course_not_found, MessageType};
+use crate::commands::{commit_and_say, course_not_found, rank_suggestions, MessageType};
 use crate::database::DatabaseHandler;
 use crate::pagination::{PageRowRef, Pagination};
 use crate::Context;
@@ -6,6 +6,28 @@ use anyhow::Result;
 use poise::serenity_prelude::{self as serenity, builder::*};
 use poise::CreateReply;
 
+/// Suggests the guild's courses whose name matches what has been typed so far.
+pub async fn autocomplete_course(ctx: Context<'_>, partial: &str) -> Vec<String> {
+  let guild_id = match ctx.guild_id() {
+    Some(guild_id) => guild_id,
+    None => return Vec::new(),
+  };
+
+  let mut transaction = match ctx.data().db.start_transaction_with_retry(5).await {
+    Ok(transaction) => transaction,
+    Err(_) => return Vec::new(),
+  };
+
+  let courses = DatabaseHandler::get_all_courses(&mut transaction, &guild_id)
+    .await
+    .unwrap_or_default();
+
+  rank_suggestions(partial, courses, |course| vec![course.course_name.clone()])
+    .into_iter()
+    .map(|course| course.course_name)
+    .collect()
+}
+
 /// Commands for managing courses
 ///
 /// Commands to add, edit, list, or remove courses.
@@ -124,7 +146,9 @@ pub async fn add(
 #[poise::command(slash_command)]
 pub async fn edit(
   ctx: Context<'_>,
-  #[description = "Name of the course"] course_name: String,
+  #[description = "Name of the course"]
+  #[autocomplete = "autocomplete_course"]
+  course_name: String,
   #[description = "Update the role that participants of the course are assumed to have"]
   participant_role: Option<serenity::Role>,
   #[description = "Update the role that graduates of the course are given"] graduate_role: Option<
@@ -328,7 +352,9 @@ pub async fn list(
 #[poise::command(slash_command)]
 pub async fn remove(
   ctx: Context<'_>,
-  #[description = "Name of the course"] course_name: String,
+  #[description = "Name of the course"]
+  #[autocomplete = "autocomplete_course"]
+  course_name: String,
 ) -> Result<()> {
   ctx.defer_ephemeral().await?;
 
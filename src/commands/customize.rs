@@ -115,10 +115,10 @@ pub enum OnOff {
 ///
 /// Customize your meditation tracking experience.
 ///
-/// Set a UTC offset, make your stats or streak private, turn streak reporting off, or enable anonymous tracking.
+/// Set a UTC offset, make your stats or streak private, turn streak reporting off, enable anonymous tracking, or get a weekly recap by DM.
 #[poise::command(
   slash_command,
-  subcommands("show", "offset", "tracking", "streak", "stats"),
+  subcommands("show", "offset", "tracking", "streak", "stats", "recap"),
   category = "Meditation Tracking",
   //hide_in_help,
   guild_only
@@ -199,7 +199,7 @@ pub async fn show(ctx: Context<'_>) -> Result<()> {
         //.title("Meditation Tracking Customization Settings")
         .description(format!(
           //"**UTC Offset**: {}\n**Anonymous Tracking**: {}\n**Streak Reporting**: {}\n**Streak Visibility**: {}\n**Stats Visibility**: {}",
-          "```UTC Offset:           {}\nAnonymous Tracking:   {}\nStreak Reporting:     {}\nStreak Visibility:    {}\nStats Visibility:     {}```",
+          "```UTC Offset:           {}\nAnonymous Tracking:   {}\nStreak Reporting:     {}\nStreak Visibility:    {}\nStats Visibility:     {}\nWeekly Recap:         {}```",
           //Only show the offset (no time zone abbreviations)
           utc_offset.split_whitespace().next().unwrap().to_string(),
           match tracking_profile.anonymous_tracking {
@@ -218,6 +218,10 @@ pub async fn show(ctx: Context<'_>) -> Result<()> {
             true => "Private",
             false => "Public"
           },
+          match tracking_profile.weekly_recap {
+            true => "On",
+            false => "Off"
+          },
         ))
     )
     .ephemeral(true))
@@ -762,3 +766,78 @@ pub async fn stats(
 
   Ok(())
 }
+
+/// Turn the weekly recap on or off
+///
+/// Turn the weekly recap on or off.
+///
+/// When the weekly recap is turned on, you will be sent a DM every Sunday evening (in your local time, as set with `/customize offset`) summarizing your meditation for the week.
+#[poise::command(slash_command)]
+pub async fn recap(
+  ctx: Context<'_>,
+  #[description = "Turn the weekly recap on or off (Default is off)"] weekly_recap: OnOff,
+) -> Result<()> {
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+  let user_id = ctx.author().id;
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+
+  let enabled = match weekly_recap {
+    OnOff::On => true,
+    OnOff::Off => false,
+  };
+
+  match DatabaseHandler::get_tracking_profile(&mut transaction, &guild_id, &user_id).await? {
+    Some(tracking_profile) => {
+      if enabled == tracking_profile.weekly_recap {
+        ctx
+          .send(
+            CreateReply::default()
+              .content(format!(
+                "Weekly recap already turned **{}**. No changes made.",
+                weekly_recap.name()
+              ))
+              .ephemeral(true),
+          )
+          .await?;
+
+        return Ok(());
+      }
+    }
+    None => {
+      let default = TrackingProfile {
+        ..Default::default()
+      };
+
+      DatabaseHandler::create_tracking_profile(
+        &mut transaction,
+        &guild_id,
+        &user_id,
+        default.utc_offset,
+        default.anonymous_tracking,
+        default.streaks_active,
+        default.streaks_private,
+        default.stats_private,
+      )
+      .await?;
+    }
+  }
+
+  DatabaseHandler::update_weekly_recap(&mut transaction, &guild_id, &user_id, enabled).await?;
+
+  commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      ":white_check_mark: Weekly recap successfully turned **{}**.",
+      weekly_recap.name()
+    )),
+    true,
+  )
+  .await?;
+
+  Ok(())
+}
@@ -1,9 +1,12 @@
+use crate::appeals::appeal_button;
+use crate::commands::reasons::{autocomplete_reason, resolve_reason, send_reason_pages};
 use crate::commands::{commit_and_say, MessageType};
 use crate::config::{self, BloomBotEmbed, CHANNELS};
-use crate::database::DatabaseHandler;
+use crate::database::{DatabaseHandler, ModAction, NotificationMethod};
 use crate::pagination::{PageRowRef, Pagination};
 use crate::Context;
 use anyhow::Result;
+use log::error;
 use poise::serenity_prelude::{self as serenity, builder::*, ChannelId};
 use poise::CreateReply;
 
@@ -33,14 +36,22 @@ pub async fn message(
   ctx: Context<'_>,
   #[description = "The message to delete"] message: serenity::Message,
   #[max_length = 512] // Max length for audit log reason
-  #[description = "The reason for deleting the message"]
+  #[description = "The reason for deleting the message, or the key of a reason template"]
+  #[autocomplete = "autocomplete_reason"]
   reason: Option<String>,
 ) -> Result<()> {
   ctx.defer_ephemeral().await?;
 
+  let data = ctx.data();
+  let guild_id = ctx.guild_id().unwrap();
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+
   let channel_id = message.channel_id;
   let message_id = message.id;
-  let reason = reason.unwrap_or("No reason provided.".to_string());
+  let (reason, reason_key) = match reason {
+    Some(reason) => resolve_reason(&mut transaction, &guild_id, &reason).await?,
+    None => ("No reason provided.".to_string(), None),
+  };
   let audit_log_reason: Option<&str> = Some(reason.as_str());
 
   ctx
@@ -50,11 +61,8 @@ pub async fn message(
 
   let occurred_at = chrono::Utc::now();
 
-  let data = ctx.data();
-  let guild_id = ctx.guild_id().unwrap();
   let user_id = message.author.id;
 
-  let mut transaction = data.db.start_transaction_with_retry(5).await?;
   let erase_count = DatabaseHandler::get_erases(&mut transaction, &guild_id, &user_id)
     .await?
     .len()
@@ -72,9 +80,7 @@ pub async fn message(
     "**Channel**: <#{}>\n**Author**: {} ({})\n**Reason**: {}",
     message.channel_id, message.author, erase_count_message, reason,
   ));
-  dm_embed = dm_embed
-    .title("A message you sent has been deleted.")
-    .description(format!("**Reason**: {}", reason));
+  dm_embed = notification_embed(dm_embed, "A message you sent has been deleted.", &reason);
 
   if let Some(attachment) = message.attachments.first() {
     log_embed = log_embed.field("Attachment", attachment.url.clone(), false);
@@ -103,9 +109,6 @@ pub async fn message(
     ))
     .icon_url(ctx.author().avatar_url().unwrap_or_default()),
   );
-  dm_embed = dm_embed.footer(CreateEmbedFooter::new(
-    "If you have any questions or concerns regarding this action, please contact a moderator. Replies sent to Bloom are not viewable by staff."
-  ));
 
   let log_channel = serenity::ChannelId::new(CHANNELS.logs);
 
@@ -115,110 +118,151 @@ pub async fn message(
 
   let message_link = log_message.link();
 
-  DatabaseHandler::add_erase(
+  let erase = DatabaseHandler::add_erase(
     &mut transaction,
     &guild_id,
     &user_id,
+    Some(&ctx.author().id),
+    Some(&reason),
+    reason_key.as_deref(),
     &message_link,
     occurred_at,
   )
   .await?;
 
-  commit_and_say(
-    ctx,
-    transaction,
-    MessageType::TextOnly(format!(
-      ":white_check_mark: Message deleted. User will be notified via DM or private thread."
-    )),
-    true,
-  )
-  .await?;
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  // Only notify once the case is saved, so that the appeal button refers to a case that exists
+  let notification_method = notify_user(ctx, &message.author, dm_embed, &erase.id).await;
+  save_notification_method(&data.db, &erase.id, notification_method).await;
+
+  let notification_message = match notification_method {
+    NotificationMethod::DirectMessage => "User was notified via DM.",
+    NotificationMethod::PrivateThread => {
+      "Could not DM the user, so they were notified in a private thread."
+    }
+    NotificationMethod::Failed => "Could not notify the user via DM or private thread.",
+  };
+
+  ctx
+    .send(
+      CreateReply::default()
+        .content(format!(
+          ":white_check_mark: Message deleted (case #{}). {}",
+          erase.case_number, notification_message
+        ))
+        .ephemeral(true),
+    )
+    .await?;
 
-  match message
-    .author
-    .direct_message(ctx, CreateMessage::new().embed(dm_embed.to_owned()))
+  Ok(())
+}
+
+/// Sends the notification to the user via DM, with a button to appeal the case. If their DMs are
+/// closed, a private thread is created in the private notification channel instead, with the user
+/// and the moderator added to it.
+async fn notify_user(
+  ctx: Context<'_>,
+  user: &serenity::User,
+  dm_embed: CreateEmbed,
+  case_id: &str,
+) -> NotificationMethod {
+  if user
+    .direct_message(
+      ctx,
+      CreateMessage::new()
+        .embed(dm_embed.clone())
+        .components(appeal_button(case_id)),
+    )
     .await
+    .is_ok()
   {
-    Ok(_) => {
-      //  commit_and_say(
-      //    ctx,
-      //    transaction,
-      //    MessageType::TextOnly(format!(":white_check_mark: Message deleted. Sent the reason in DMs.")),
-      //    true,
-      //  )
-      //  .await?;
+    return NotificationMethod::DirectMessage;
+  }
+
+  match notify_in_thread(ctx, user, dm_embed, case_id).await {
+    Ok(()) => NotificationMethod::PrivateThread,
+    Err(e) => {
+      error!("Failed to create private notification thread: {:?}", e);
+      NotificationMethod::Failed
     }
-    Err(_) => {
-      let thread_channel: ChannelId = match message
-        .channel_id
-        .to_channel(&ctx)
-        .await
-        .unwrap()
-        .guild()
-        .unwrap()
-        .kind
-      {
-        serenity::ChannelType::Text => channel_id,
-        // If not a text channel, then create private thread in lounge to avoid failure
-        _ => ChannelId::from(501464482996944909),
-      };
-
-      let mut notification_thread = thread_channel
-        .create_thread(
-          ctx,
-          CreateThread::new(format!("Private Notification: Message Deleted")),
-        )
-        .await?;
+  }
+}
 
-      notification_thread
-        .edit_thread(ctx, EditThread::new().invitable(false).locked(true))
-        .await?;
+async fn notify_in_thread(
+  ctx: Context<'_>,
+  user: &serenity::User,
+  dm_embed: CreateEmbed,
+  case_id: &str,
+) -> Result<()> {
+  let notification_thread = ChannelId::new(CHANNELS.privatenotifications)
+    .create_thread(
+      ctx,
+      CreateThread::new("Private Notification: Message Deleted")
+        .kind(serenity::ChannelType::PrivateThread)
+        .invitable(false)
+        .auto_archive_duration(serenity::AutoArchiveDuration::OneDay),
+    )
+    .await?;
 
-      dm_embed = dm_embed.footer(CreateEmbedFooter::new(
-        "If you have any questions or concerns regarding this action, please contact staff via ModMail."
-      ));
+  notification_thread
+    .id
+    .add_thread_member(ctx, user.id)
+    .await?;
+  notification_thread
+    .id
+    .add_thread_member(ctx, ctx.author().id)
+    .await?;
 
-      let thread_initial_message = format!("Private notification for <@{}>:", message.author.id);
+  let dm_embed = dm_embed.footer(CreateEmbedFooter::new(
+    "If you have any questions or concerns regarding this action, please contact staff via ModMail. If you believe this action was a mistake, you can appeal it with the button below."
+  ));
 
-      notification_thread
-        .send_message(
-          ctx,
-          CreateMessage::new()
-            .content(thread_initial_message)
-            .embed(dm_embed.to_owned())
-            .allowed_mentions(CreateAllowedMentions::new().users([message.author.id])),
-        )
-        .await?;
+  notification_thread
+    .send_message(
+      ctx,
+      CreateMessage::new()
+        .content(format!("Private notification for <@{}>:", user.id))
+        .embed(dm_embed)
+        .components(appeal_button(case_id))
+        .allowed_mentions(CreateAllowedMentions::new().users([user.id])),
+    )
+    .await?;
 
-      //commit_and_say(
-      //  ctx,
-      //  transaction,
-      //  MessageType::TextOnly(format!(
-      //    ":white_check_mark: Message deleted. Could not send the reason in DMs. Private thread created: <#{}>",
-      //    notification_thread.id
-      //  )),
-      //  true,
-      //)
-      //.await?;
-    }
-  };
+  notification_thread
+    .id
+    .edit_thread(ctx, EditThread::new().locked(true))
+    .await?;
 
   Ok(())
 }
 
 /// List erases for a user
 ///
-/// List erases for a specified user, with dates and links to notification messages, when available.
+/// List erases for a specified user, with dates and links to notification messages, when available. Without a user, shows which reason templates are used most for erases.
 #[poise::command(slash_command)]
 pub async fn list(
   ctx: Context<'_>,
-  #[description = "The user to show erase data for"] user: serenity::User,
+  #[description = "The user to show erase data for"] user: Option<serenity::User>,
   #[description = "The page to show"] page: Option<usize>,
 ) -> Result<()> {
   let data = ctx.data();
 
   // We unwrap here, because we know that the command is guild-only.
   let guild_id = ctx.guild_id().unwrap();
+
+  let user = match user {
+    Some(user) => user,
+    None => {
+      let mut transaction = data.db.start_transaction_with_retry(5).await?;
+      let reasons =
+        DatabaseHandler::get_mod_reasons(&mut transaction, &guild_id, Some(ModAction::Erase))
+          .await?;
+      drop(transaction);
+
+      return send_reason_pages(ctx, "Most Used Erase Reasons", &reasons, page).await;
+    }
+  };
   let user_nick_or_name = match user.nick_in(&ctx, guild_id).await {
     Some(nick) => nick,
     None => user.name.clone(),
@@ -381,6 +425,9 @@ pub async fn populate(
     &mut transaction,
     &guild_id,
     &user.id,
+    None,
+    None,
+    None,
     &message_link,
     datetime,
   )
@@ -396,3 +443,40 @@ pub async fn populate(
 
   Ok(())
 }
+
+/// Adds the title, reason and footer used for private notifications of moderation actions.
+pub fn notification_embed(embed: CreateEmbed, title: &str, reason: &str) -> CreateEmbed {
+  embed
+    .title(title)
+    .description(format!("**Reason**: {}", reason))
+    .footer(CreateEmbedFooter::new(
+      "If you believe this action was a mistake, you can appeal it with the button below. Replies sent to Bloom are not viewable by staff."
+    ))
+}
+
+/// Saves how a member was notified of a case, once the case itself has been saved. Failures are
+/// logged, since the notification has already been sent.
+pub async fn save_notification_method(
+  db: &DatabaseHandler,
+  case_id: &str,
+  notification_method: NotificationMethod,
+) {
+  let result = async {
+    let mut transaction = db.start_transaction_with_retry(5).await?;
+    DatabaseHandler::set_mod_case_notification_method(
+      &mut transaction,
+      case_id,
+      notification_method,
+    )
+    .await?;
+    DatabaseHandler::commit_transaction(transaction).await
+  }
+  .await;
+
+  if let Err(err) = result {
+    error!(
+      "Error saving notification method of case {}: {}",
+      case_id, err
+    );
+  }
+}
@@ -1,11 +1,13 @@
-use crate::commands::BloomBotEmbed;
-use crate::config::CHANNELS;
-use crate::database::DatabaseHandler;
-// use crate::pagination::{PageRowRef, Pagination};
+use crate::commands::terms::autocomplete_term;
+use crate::commands::{rank_suggestions, BloomBotEmbed};
+use crate::config::{CHANNELS, GLOSSARY_RELATED_TERMS, GLOSSARY_SEARCH_RESULTS};
+use crate::database::{DatabaseHandler, RelatedTerm, Term, TermSuggestion};
+use crate::embeddings::embed_search;
+use crate::pagination::{PageRowRef, Pagination};
+use crate::term_suggestions;
 use crate::Context;
 use anyhow::Result;
-use log::info;
-use pgvector;
+use log::{error, info};
 use poise::serenity_prelude::{self as serenity, builder::*};
 use poise::CreateReply;
 
@@ -13,11 +15,11 @@ use poise::CreateReply;
 ///
 /// Commands for interacting with the glossary.
 ///
-/// Get `info` on a glossary entry, see a `list` of entries, `search` for a relevant entry, or `suggest` a term for addition.
+/// Get `info` on a glossary entry, see a `list` of entries, `browse` entries by category, `search` for a relevant entry, or `suggest` a term for addition.
 #[poise::command(
   slash_command,
   category = "Informational",
-  subcommands("list", "info", "search", "suggest"),
+  subcommands("list", "browse", "info", "search", "suggest"),
   subcommand_required,
   guild_only
 )]
@@ -25,20 +27,56 @@ pub async fn glossary(_: Context<'_>) -> Result<()> {
   Ok(())
 }
 
+/// Suggests the glossary categories that match what has been typed so far.
+pub async fn autocomplete_category(ctx: Context<'_>, partial: &str) -> Vec<String> {
+  let guild_id = match ctx.guild_id() {
+    Some(guild_id) => guild_id,
+    None => return Vec::new(),
+  };
+
+  let mut transaction = match ctx.data().db.start_transaction_with_retry(5).await {
+    Ok(transaction) => transaction,
+    Err(_) => return Vec::new(),
+  };
+
+  let categories = DatabaseHandler::get_term_categories(&mut transaction, &guild_id)
+    .await
+    .unwrap_or_default();
+
+  rank_suggestions(partial, categories, |category| vec![category.clone()])
+}
+
 /// See a list of all glossary entries
 ///
-/// Shows a list of all glossary entries.
+/// Shows a list of all glossary entries, optionally only those in a category.
 #[poise::command(slash_command)]
-pub async fn list(ctx: Context<'_>) -> Result<()> {
+pub async fn list(
+  ctx: Context<'_>,
+  #[description = "Only list terms in this category"]
+  #[autocomplete = "autocomplete_category"]
+  category: Option<String>,
+) -> Result<()> {
   let data = ctx.data();
 
   // We unwrap here, because we know that the command is guild-only.
   let guild_id = ctx.guild_id().unwrap();
 
   let mut transaction = data.db.start_transaction_with_retry(5).await?;
-  let term_names = DatabaseHandler::get_term_list(&mut transaction, &guild_id).await?;
+  let term_names =
+    DatabaseHandler::get_term_list(&mut transaction, &guild_id, category.as_deref()).await?;
   let term_count = term_names.len();
 
+  if term_count == 0 && category.is_some() {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(":x: There are no terms in that category.")
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
   let mut term_list = String::new();
   for (i, term) in term_names.iter().enumerate() {
     term_list.push_str(&term.term_name);
@@ -59,10 +97,15 @@ pub async fn list(ctx: Context<'_>) -> Result<()> {
     }
   }
 
+  let title = match &category {
+    Some(category) => format!("List of Glossary Terms in {}", category),
+    None => "List of Glossary Terms".to_string(),
+  };
+
   ctx
     .send(CreateReply::default()
       .embed(BloomBotEmbed::new()
-          .title("List of Glossary Terms")
+          .title(title)
           .description(format!(
             "Use `/glossary info` with any of the following terms to read the full entry. Terms in parentheses are aliases for the preceding term.\n```{}```",
             term_list
@@ -76,13 +119,75 @@ pub async fn list(ctx: Context<'_>) -> Result<()> {
   Ok(())
 }
 
-/*
-/// Browse a list of all glossary entries
+/// Renders a page of `/glossary browse`, showing the last page if `page` is past the end. Returns
+/// the page shown and the number of pages along with the embed.
+async fn browse_page(entries: &[Term], page: usize) -> Result<(usize, usize, CreateEmbed)> {
+  let rows: Vec<PageRowRef> = entries.iter().map(|entry| entry as _).collect();
+  let glossary = Pagination::new("Glossary", rows).await?;
+
+  let page = match glossary.get_page(page) {
+    Some(_) => page,
+    None => glossary.get_last_page_number(),
+  };
+
+  Ok((
+    page,
+    glossary.get_page_count(),
+    glossary.create_page_embed(page),
+  ))
+}
+
+/// The category menu and navigation buttons of `/glossary browse`. The menu values are indexes
+/// into `categories`, or "all".
+fn browse_components(
+  ctx_id: u64,
+  categories: &[String],
+  selected: Option<usize>,
+  page_count: usize,
+) -> Vec<CreateActionRow> {
+  let mut rows = Vec::new();
+
+  if !categories.is_empty() {
+    let mut options = vec![
+      CreateSelectMenuOption::new("All categories", "all").default_selection(selected.is_none())
+    ];
+    // Select menus can't have more than 25 options
+    options.extend(categories.iter().enumerate().take(24).map(|(i, category)| {
+      CreateSelectMenuOption::new(
+        category.chars().take(100).collect::<String>(),
+        i.to_string(),
+      )
+      .default_selection(selected == Some(i))
+    }));
+
+    rows.push(CreateActionRow::SelectMenu(
+      CreateSelectMenu::new(
+        format!("{}category", ctx_id),
+        CreateSelectMenuKind::String { options },
+      )
+      .placeholder("Filter by category"),
+    ));
+  }
+
+  if page_count > 1 {
+    rows.push(CreateActionRow::Buttons(vec![
+      CreateButton::new(format!("{}prev", ctx_id)).label("Previous"),
+      CreateButton::new(format!("{}next", ctx_id)).label("Next"),
+    ]));
+  }
+
+  rows
+}
+
+/// Browse glossary entries
 ///
-/// Browse a list of all glossary entries.
+/// Browse glossary entries one at a time, optionally only those in a category.
 #[poise::command(slash_command)]
 pub async fn browse(
   ctx: Context<'_>,
+  #[description = "Only show terms in this category"]
+  #[autocomplete = "autocomplete_category"]
+  category: Option<String>,
   #[description = "The page to show"] page: Option<usize>,
 ) -> Result<()> {
   let data = ctx.data();
@@ -91,87 +196,273 @@ pub async fn browse(
   let guild_id = ctx.guild_id().unwrap();
 
   let mut transaction = data.db.start_transaction_with_retry(5).await?;
-
-  // Define some unique identifiers for the navigation buttons
-  let ctx_id = ctx.id();
-  let prev_button_id = format!("{}prev", ctx_id);
-  let next_button_id = format!("{}next", ctx_id);
-
-  let mut current_page = page.unwrap_or(0);
-
-  if current_page > 0 {
-    current_page = current_page - 1
+  let categories = DatabaseHandler::get_term_categories(&mut transaction, &guild_id).await?;
+
+  let mut selected = None;
+  if let Some(category) = category {
+    selected = categories
+      .iter()
+      .position(|existing| existing.eq_ignore_ascii_case(&category));
+
+    if selected.is_none() {
+      ctx
+        .send(
+          CreateReply::default()
+            .content(":x: There are no terms in that category.")
+            .ephemeral(true),
+        )
+        .await?;
+      return Ok(());
+    }
   }
 
-  let entries = DatabaseHandler::get_all_glossary_terms(&mut transaction, &guild_id).await?;
-  let entries: Vec<PageRowRef> = entries.iter().map(|entry| entry as _).collect();
+  let mut entries = DatabaseHandler::get_all_glossary_terms(
+    &mut transaction,
+    &guild_id,
+    selected.map(|i| categories[i].as_str()),
+  )
+  .await?;
   drop(transaction);
-  let glossary = Pagination::new("Glossary", entries).await?;
 
-  if glossary.get_page(current_page).is_none() {
-    current_page = glossary.get_last_page_number();
-  }
+  // Define some unique identifiers for the navigation buttons and category menu
+  let ctx_id = ctx.id();
+  let prev_button_id = format!("{}prev", ctx_id);
+  let next_button_id = format!("{}next", ctx_id);
+  let category_menu_id = format!("{}category", ctx_id);
 
-  let first_page = glossary.create_page_embed(current_page);
+  let (mut current_page, mut page_count, first_page) =
+    browse_page(&entries, page.unwrap_or(0).saturating_sub(1)).await?;
 
   ctx
-    .send(|f| {
-      f.components(|b| {
-        if glossary.get_page_count() > 1 {
-          b.create_action_row(|b| {
-            b.create_button(|b| b.custom_id(&prev_button_id).label("Previous"))
-              .create_button(|b| b.custom_id(&next_button_id).label("Next"))
-          });
-        }
-
-        b
-      });
-
+    .send({
+      let mut f = CreateReply::default().components(browse_components(
+        ctx_id,
+        &categories,
+        selected,
+        page_count,
+      ));
       f.embeds = vec![first_page];
-
       f
     })
     .await?;
 
-  // Loop through incoming interactions with the navigation buttons
-  while let Some(press) = serenity::CollectComponentInteraction::new(ctx)
-    // We defined our button IDs to start with `ctx_id`. If they don't, some other command's
-    // button was pressed
+  // Loop through incoming interactions with the navigation buttons and category menu
+  while let Some(press) = serenity::ComponentInteractionCollector::new(ctx)
+    // We defined our component IDs to start with `ctx_id`. If they don't, some other command's
+    // component was used
     .filter(move |press| press.data.custom_id.starts_with(&ctx_id.to_string()))
-    // Timeout when no navigation button has been pressed for 24 hours
+    // Timeout when nothing has been pressed for 24 hours
     .timeout(std::time::Duration::from_secs(3600 * 24))
     .await
   {
-    // Depending on which button was pressed, go to next or previous page
+    // Depending on which button was pressed, go to next or previous page, or show another category
     if press.data.custom_id == next_button_id {
-      current_page = glossary.update_page_number(current_page, 1);
+      current_page = (current_page + 1) % page_count;
     } else if press.data.custom_id == prev_button_id {
-      current_page = glossary.update_page_number(current_page, -1);
+      current_page = (current_page + page_count - 1) % page_count;
+    } else if press.data.custom_id == category_menu_id {
+      selected = match &press.data.kind {
+        serenity::ComponentInteractionDataKind::StringSelect { values } => values
+          .first()
+          .and_then(|value| value.parse::<usize>().ok())
+          .filter(|i| *i < categories.len()),
+        _ => continue,
+      };
+
+      let mut transaction = data.db.start_transaction_with_retry(5).await?;
+      entries = DatabaseHandler::get_all_glossary_terms(
+        &mut transaction,
+        &guild_id,
+        selected.map(|i| categories[i].as_str()),
+      )
+      .await?;
+      current_page = 0;
     } else {
-      // This is an unrelated button interaction
+      // This is an unrelated interaction
       continue;
     }
 
+    let (page, count, embed) = browse_page(&entries, current_page).await?;
+    current_page = page;
+    page_count = count;
+
     // Update the message with the new page contents
     press
-      .create_interaction_response(ctx, |b| {
-        b.kind(serenity::InteractionResponseType::UpdateMessage)
-          .interaction_response_data(|f| f.set_embed(glossary.create_page_embed(current_page)))
-      })
+      .create_response(
+        ctx,
+        CreateInteractionResponse::UpdateMessage(
+          CreateInteractionResponseMessage::new()
+            .embed(embed)
+            .components(browse_components(ctx_id, &categories, selected, page_count)),
+        ),
+      )
       .await?;
   }
 
   Ok(())
 }
-*/
+
+/// Builds the embed showing a glossary entry.
+pub fn term_embed(term: &Term) -> CreateEmbed {
+  let mut embed = BloomBotEmbed::new()
+    .title(&term.term_name)
+    .description(&term.meaning);
+  let usage = term.usage.clone().unwrap_or_default();
+  if !usage.is_empty() {
+    embed = embed.field("Example of Usage:", usage, false);
+  }
+  let links = term.links.clone().unwrap_or_default();
+  if !links.is_empty() {
+    embed = embed.field(
+      "Related Resources:",
+      {
+        let mut field = String::new();
+        let mut count = 1;
+
+        for link in links {
+          field.push_str(&format!("{}. {}\n", count, link));
+          count += 1;
+        }
+
+        field
+      },
+      false,
+    );
+  }
+  let aliases = term.aliases.clone().unwrap_or_default();
+  if !aliases.is_empty() {
+    embed = embed.field(
+      "Aliases:",
+      {
+        let mut field = String::new();
+        let alias_count = aliases.len();
+
+        for (i, alias) in aliases.iter().enumerate() {
+          field.push_str(&alias);
+          if i < (alias_count - 1) {
+            field.push_str(", ");
+          }
+        }
+
+        field
+      },
+      false,
+    );
+  }
+  let category = term.category.clone().unwrap_or_default();
+  if !category.is_empty() {
+    embed = embed.footer(CreateEmbedFooter::new(format!("Categories: {}", category)));
+  }
+
+  embed
+}
+
+/// Finds the names a meaning refers to with "see also", e.g. "See also: Jhana, Samadhi". A
+/// reference runs until the end of the sentence and may list several names separated by commas,
+/// "and" or "or".
+fn see_also_references(meaning: &str) -> Vec<String> {
+  // ASCII lowercasing keeps byte offsets the same, so matches can index the original
+  let lowercase = meaning.to_ascii_lowercase();
+  let mut references = Vec::new();
+
+  for (start, marker) in lowercase.match_indices("see also") {
+    let rest =
+      meaning[start + marker.len()..].trim_start_matches(|c: char| c == ':' || c.is_whitespace());
+    let end = rest
+      .find(['.', ';', '(', ')', '[', ']', '\n'])
+      .unwrap_or(rest.len());
+
+    for reference in rest[..end]
+      .split(',')
+      .flat_map(|part| part.split(" and "))
+      .flat_map(|part| part.split(" or "))
+    {
+      let reference = reference
+        .trim()
+        .trim_matches(|c: char| matches!(c, '`' | '*' | '_' | '"' | '\'' | '“' | '”'))
+        .trim();
+      if !reference.is_empty() {
+        references.push(reference.to_string());
+      }
+    }
+  }
+
+  references
+}
+
+/// Finds the terms to link from a glossary entry: the terms it refers to with "see also" first,
+/// then the terms with the closest embeddings.
+async fn related_terms(
+  transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+  guild_id: &serenity::GuildId,
+  term: &Term,
+) -> Result<Vec<RelatedTerm>> {
+  let mut related: Vec<RelatedTerm> = Vec::new();
+
+  for reference in see_also_references(&term.meaning) {
+    // A reference that can't be resolved is left out rather than failing the whole entry
+    let referenced =
+      match DatabaseHandler::get_referenced_term(transaction, guild_id, &reference).await {
+        Ok(Some(referenced)) => referenced,
+        Ok(None) => continue,
+        Err(err) => {
+          error!(
+            "Error resolving reference {:?} in {}: {}",
+            reference, term.term_name, err
+          );
+          continue;
+        }
+      };
+    if referenced.id != term.id && !related.iter().any(|other| other.id == referenced.id) {
+      related.push(referenced);
+    }
+  }
+
+  let closest = DatabaseHandler::get_related_terms(
+    transaction,
+    guild_id,
+    &term.id,
+    GLOSSARY_RELATED_TERMS as i64,
+  )
+  .await?;
+  for closest in closest {
+    if !related.iter().any(|other| other.id == closest.id) {
+      related.push(closest);
+    }
+  }
+
+  related.truncate(GLOSSARY_RELATED_TERMS);
+
+  Ok(related)
+}
+
+/// Buttons that show a related term in place of the current one. Their IDs are `prefix` followed
+/// by the term's ID.
+fn related_buttons(prefix: &str, related: &[RelatedTerm]) -> Vec<CreateActionRow> {
+  match related.is_empty() {
+    true => vec![],
+    false => vec![CreateActionRow::Buttons(
+      related
+        .iter()
+        .map(|term| {
+          CreateButton::new(format!("{}{}", prefix, term.id))
+            .label(term.term_name.chars().take(80).collect::<String>())
+            .style(serenity::ButtonStyle::Secondary)
+        })
+        .collect(),
+    )],
+  }
+}
 
 /// See information about a glossary entry
 ///
-/// Shows information about a glossary entry.
+/// Shows information about a glossary entry, with buttons for related terms.
 #[poise::command(slash_command)]
 pub async fn info(
   ctx: Context<'_>,
-  #[description = "The term to show information about"] term: String,
+  #[description = "The term to show information about"]
+  #[autocomplete = "autocomplete_term"]
+  term: String,
 ) -> Result<()> {
   let guild_id = ctx.guild_id().unwrap();
 
@@ -179,58 +470,12 @@ pub async fn info(
 
   let term_info = DatabaseHandler::get_term(&mut transaction, &guild_id, &term.as_str()).await?;
   let mut embed = BloomBotEmbed::new();
+  let mut related = Vec::new();
 
   match term_info {
     Some(term_info) => {
-      embed = embed
-        .title(term_info.term_name)
-        .description(term_info.meaning);
-      let usage = term_info.usage.unwrap_or(String::new());
-      if !usage.is_empty() {
-        embed = embed.field("Example of Usage:", usage, false);
-      }
-      let links = term_info.links.unwrap_or(Vec::new());
-      if !links.is_empty() {
-        embed = embed.field(
-          "Related Resources:",
-          {
-            let mut field = String::new();
-            let mut count = 1;
-
-            for link in links {
-              field.push_str(&format!("{}. {}\n", count, link));
-              count += 1;
-            }
-
-            field
-          },
-          false,
-        );
-      }
-      let aliases = term_info.aliases.clone().unwrap_or(Vec::new());
-      if !aliases.is_empty() {
-        embed = embed.field(
-          "Aliases:",
-          {
-            let mut field = String::new();
-            let alias_count = aliases.len();
-
-            for (i, alias) in aliases.iter().enumerate() {
-              field.push_str(&alias);
-              if i < (alias_count - 1) {
-                field.push_str(", ");
-              }
-            }
-
-            field
-          },
-          false,
-        );
-      }
-      let category = term_info.category.unwrap_or(String::new());
-      if !category.is_empty() {
-        embed = embed.footer(CreateEmbedFooter::new(format!("Categories: {}", category)));
-      }
+      embed = term_embed(&term_info);
+      related = related_terms(&mut transaction, &guild_id, &term_info).await?;
     }
     None => {
       let possible_terms =
@@ -240,51 +485,7 @@ pub async fn info(
       if possible_terms.len() == 1 {
         let possible_term = possible_terms.first().unwrap();
 
-        embed = embed
-          .title(&possible_term.term_name)
-          .description(&possible_term.meaning);
-        let usage = possible_term.usage.clone().unwrap_or(String::new());
-        if !usage.is_empty() {
-          embed = embed.field("Example of Usage:", usage, false);
-        }
-        let links = possible_term.links.clone().unwrap_or(Vec::new());
-        if !links.is_empty() {
-          embed = embed.field(
-            "Related Resources:",
-            {
-              let mut field = String::new();
-              let mut count = 1;
-
-              for link in links {
-                field.push_str(&format!("{}. {}\n", count, link));
-                count += 1;
-              }
-
-              field
-            },
-            false,
-          );
-        }
-        let aliases = possible_term.aliases.clone().unwrap_or(Vec::new());
-        if !aliases.is_empty() {
-          embed = embed.field(
-            "Aliases:",
-            {
-              let mut field = String::new();
-              let alias_count = aliases.len();
-
-              for (i, alias) in aliases.iter().enumerate() {
-                field.push_str(&alias);
-                if i < (alias_count - 1) {
-                  field.push_str(", ");
-                }
-              }
-
-              field
-            },
-            false,
-          );
-        }
+        embed = term_embed(possible_term);
         let category = possible_term.category.clone().unwrap_or(String::new());
         if !category.is_empty() {
           embed = embed.footer(CreateEmbedFooter::new(format!(
@@ -297,6 +498,7 @@ pub async fn info(
             term, possible_term.term_name
           )));
         }
+        related = related_terms(&mut transaction, &guild_id, possible_term).await?;
       } else if possible_terms.is_empty() {
         embed = embed.title("Term not found").description(format!(
           "The term `{}` was not found in the glossary.",
@@ -326,44 +528,116 @@ pub async fn info(
       }
     }
   }
+  drop(transaction);
+
+  let ctx_id = ctx.id();
+  let term_button_prefix = format!("{}term:", ctx_id);
 
   ctx
     .send({
-      let mut f = CreateReply::default();
+      let mut f = CreateReply::default().components(related_buttons(&term_button_prefix, &related));
       f.embeds = vec![embed];
 
       f
     })
     .await?;
 
+  if related.is_empty() {
+    return Ok(());
+  }
+
+  // Loop through presses of the related term buttons, showing the pressed term in place
+  while let Some(press) = serenity::ComponentInteractionCollector::new(ctx)
+    // We defined our button IDs to start with `ctx_id`. If they don't, some other command's
+    // button was pressed
+    .filter(move |press| press.data.custom_id.starts_with(&ctx_id.to_string()))
+    // Timeout when no button has been pressed for 24 hours
+    .timeout(std::time::Duration::from_secs(3600 * 24))
+    .await
+  {
+    let term_id = match press.data.custom_id.strip_prefix(&term_button_prefix) {
+      Some(term_id) => term_id,
+      // This is an unrelated button interaction
+      None => continue,
+    };
+
+    let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+    let term_info =
+      match DatabaseHandler::get_term_by_id(&mut transaction, &guild_id, term_id).await? {
+        Some(term_info) => term_info,
+        None => {
+          press
+            .create_response(
+              ctx,
+              CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                  .content(":x: That term has been removed from the glossary.")
+                  .ephemeral(true),
+              ),
+            )
+            .await?;
+          continue;
+        }
+      };
+    let related = related_terms(&mut transaction, &guild_id, &term_info).await?;
+    drop(transaction);
+
+    press
+      .create_response(
+        ctx,
+        CreateInteractionResponse::UpdateMessage(
+          CreateInteractionResponseMessage::new()
+            .embed(term_embed(&term_info))
+            .components(related_buttons(&term_button_prefix, &related)),
+        ),
+      )
+      .await?;
+  }
+
   Ok(())
 }
 
 /// Search glossary entries using keywords or phrases
 ///
-/// Searches glossary entries using keywords or phrases, leveraging AI to find the closest matches.
+/// Searches glossary entries using keywords or phrases, matching term names and aliases and leveraging AI to find the closest matches.
 #[poise::command(slash_command)]
 pub async fn search(
   ctx: Context<'_>,
   #[description = "The term to search for"] search: String,
+  #[description = "Only search terms in this category"]
+  #[autocomplete = "autocomplete_category"]
+  category: Option<String>,
+  #[description = "The number of results to show (defaults to 3)"]
+  #[min = 1]
+  #[max = 5]
+  results: Option<usize>,
 ) -> Result<()> {
-  ctx.defer().await?;
-
   let data = ctx.data();
 
+  ctx.defer().await?;
+
   // We unwrap here, because we know that the command is guild-only.
   let guild_id = ctx.guild_id().unwrap();
 
   let start_time = std::time::Instant::now();
   let mut transaction = data.db.start_transaction_with_retry(5).await?;
-  let vector = pgvector::Vector::from(
-    data
-      .embeddings
-      .create_embedding(search.clone(), ctx.author().id)
-      .await?,
-  );
-  let possible_terms =
-    DatabaseHandler::search_terms_by_vector(&mut transaction, &guild_id, vector, 3).await?;
+  let search_vector = embed_search(
+    &data.db,
+    &data.query_embeddings,
+    data.embeddings.as_ref(),
+    &search,
+    ctx.author().id,
+  )
+  .await;
+  let possible_terms = DatabaseHandler::search_terms(
+    &mut transaction,
+    &guild_id,
+    &search,
+    search_vector,
+    category.as_deref(),
+    results.unwrap_or(GLOSSARY_SEARCH_RESULTS),
+  )
+  .await?;
   let end_time = std::time::Instant::now();
 
   let mut embed = BloomBotEmbed::new();
@@ -375,34 +649,14 @@ pub async fn search(
       embed.description("No terms were found. Try browsing the glossary with `/glossary list`.");
   } else {
     for (index, possible_term) in possible_terms.iter().enumerate() {
-      // Set threshold for terms to include
-      if possible_term.distance_score.unwrap_or(1.0) > 0.3 {
-        continue;
-      }
-      let relevance_description = match possible_term.distance_score {
-        Some(score) => {
-          let similarity_score = ((1.0 - score) * 100.0) as i32;
-          info!(
-            "Term {} has a similarity score of {}",
-            index + 1,
-            similarity_score
-          );
-          match similarity_score {
-            100..=i32::MAX => "Exact match",
-            // Adjust for cosine similarity
-            90..=99 => "High",
-            80..=89 => "Medium",
-            70..=79 => "Low",
-            // 80..=99 => "Very similar",
-            // 60..=79 => "Similar",
-            // 40..=59 => "Somewhat similar",
-            // 20..=39 => "Not very similar",
-            // 0..=19 => "Not similar",
-            _ => "Unknown",
-          }
-        }
-        None => "Unknown",
-      };
+      // Terms below the similarity thresholds were already left out by the search
+      info!(
+        "Term {} has a fused score of {:.4} ({:?})",
+        index + 1,
+        possible_term.score.fused,
+        possible_term.score
+      );
+      let relevance_description = possible_term.score.relevance();
 
       let meaning = possible_term.meaning.clone();
 
@@ -444,38 +698,121 @@ pub async fn search(
 
 /// Suggest a term for the glossary
 ///
-/// Suggest a term for addition to the glossary.
+/// Suggest a term for addition to the glossary. Staff will review your suggestion, and you will be notified of the outcome.
 #[poise::command(slash_command)]
 pub async fn suggest(
   ctx: Context<'_>,
   #[description = "Term you wish to suggest"] suggestion: String,
+  #[description = "What the term means (optional)"]
+  #[max_length = 1000]
+  meaning: Option<String>,
 ) -> Result<()> {
-  let log_embed = BloomBotEmbed::new()
-    .title("Term Suggestion")
-    .description(format!("**Suggestion**: {}", suggestion))
-    .footer(
-      CreateEmbedFooter::new(format!(
-        "Suggested by {} ({})",
-        ctx.author().name,
-        ctx.author().id
-      ))
-      .icon_url(ctx.author().avatar_url().unwrap_or_default()),
-    )
-    .to_owned();
+  let data = ctx.data();
+  let guild_id = ctx.guild_id().unwrap();
 
-  let log_channel = serenity::ChannelId::new(CHANNELS.bloomlogs);
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
 
-  log_channel
-    .send_message(ctx, CreateMessage::new().embed(log_embed))
-    .await?;
+  if let Some(term) =
+    DatabaseHandler::get_term(&mut transaction, &guild_id, suggestion.as_str()).await?
+  {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            ":x: `{}` is already in the glossary. Use `/glossary info` to view it.",
+            term.term_name
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let possible_terms =
+    DatabaseHandler::get_possible_terms(&mut transaction, &guild_id, suggestion.as_str(), 0.7)
+      .await?;
+
+  if !possible_terms.is_empty() {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            ":x: The glossary already has similar terms. If your suggestion is different, please try rewording it.\n{}",
+            possible_terms
+              .iter()
+              .map(|term| format!("`{}`", term.term_name))
+              .collect::<Vec<String>>()
+              .join("\n")
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let suggestion = match DatabaseHandler::add_term_suggestion(
+    &mut transaction,
+    &guild_id,
+    suggestion.as_str(),
+    meaning.as_deref(),
+    &ctx.author().id,
+  )
+  .await?
+  {
+    Some(suggestion) => suggestion,
+    None => {
+      ctx
+        .send(
+          CreateReply::default()
+            .content(":x: This term has already been suggested and is awaiting review.")
+            .ephemeral(true),
+        )
+        .await?;
+      return Ok(());
+    }
+  };
+
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  // The suggestion is saved before staff can act on it. If it can't be posted, it is logged
+  // rather than failing the command.
+  if let Err(err) = post_suggestion(ctx, &suggestion).await {
+    error!(
+      "Error posting term suggestion {}: {}",
+      suggestion.term_name, err
+    );
+  }
 
   ctx
     .send(
       CreateReply::default()
-        .content("Your suggestion has been submitted. Thank you!")
+        .content(
+          "Your suggestion has been submitted. You will be notified once it has been reviewed. Thank you!",
+        )
         .ephemeral(true),
     )
     .await?;
 
   Ok(())
 }
+
+/// Posts a suggestion for staff to review, and saves the message so it can be updated later.
+async fn post_suggestion(ctx: Context<'_>, suggestion: &TermSuggestion) -> Result<()> {
+  let log_channel = serenity::ChannelId::new(CHANNELS.bloomlogs);
+
+  let message = log_channel
+    .send_message(
+      ctx,
+      CreateMessage::new()
+        .embed(term_suggestions::suggestion_embed(ctx.serenity_context(), suggestion).await?)
+        .components(term_suggestions::suggestion_buttons(suggestion)),
+    )
+    .await?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  DatabaseHandler::set_term_suggestion_message(&mut transaction, &suggestion.id, &message.id)
+    .await?;
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  Ok(())
+}
@@ -4,10 +4,15 @@ use crate::Context;
 use anyhow::Result;
 use log::info;
 use poise::{serenity_prelude as serenity, CreateReply};
+use std::collections::HashSet;
 use std::sync::atomic::Ordering;
 
 pub mod add;
+pub mod badges;
+pub mod boards;
+pub mod buddy;
 pub mod challenge;
+pub mod challenges;
 pub mod coffee;
 pub mod complete;
 pub mod courses;
@@ -18,16 +23,21 @@ pub mod hello;
 pub mod help;
 pub mod keys;
 pub mod manage;
+pub mod moderation;
 pub mod pick_winner;
 pub mod ping;
 pub mod quote;
 pub mod quotes;
+pub mod reasons;
 pub mod recent;
 pub mod remove_entry;
 pub mod report_message;
+pub mod reports;
+pub mod starboard;
 pub mod stats;
 pub mod streak;
 pub mod suggest;
+pub mod term_of_the_day;
 pub mod terms;
 pub mod whatis;
 
@@ -120,6 +130,77 @@ async fn commit_and_say(
   Ok(())
 }
 
+/// The trigrams of a string, built the way `pg_trgm` builds them: each word is lowercased and
+/// padded with two spaces in front and one behind.
+fn trigrams(text: &str) -> HashSet<[char; 3]> {
+  text
+    .to_lowercase()
+    .split(|c: char| !c.is_alphanumeric())
+    .filter(|word| !word.is_empty())
+    .flat_map(|word| {
+      let padded: Vec<char> = format!("  {} ", word).chars().collect();
+      padded
+        .windows(3)
+        .map(|trigram| [trigram[0], trigram[1], trigram[2]])
+        .collect::<Vec<[char; 3]>>()
+    })
+    .collect()
+}
+
+/// Trigram similarity of two strings from 0 to 1, matching `SIMILARITY` in `pg_trgm`.
+fn trigram_similarity(a: &str, b: &str) -> f64 {
+  let a = trigrams(a);
+  let b = trigrams(b);
+
+  match a.union(&b).count() {
+    0 => 0.0,
+    union => a.intersection(&b).count() as f64 / union as f64,
+  }
+}
+
+/// Ranks autocomplete suggestions against what has been typed so far, using the best of each
+/// item's names. Names that start with the input come first, then names that contain it, then
+/// names that are only similar, which catches typos. Returns at most 25 items, the most Discord
+/// shows, in their original order when nothing has been typed.
+pub fn rank_suggestions<T>(
+  partial: &str,
+  items: Vec<T>,
+  names: impl Fn(&T) -> Vec<String>,
+) -> Vec<T> {
+  let partial = partial.trim().to_lowercase();
+
+  if partial.is_empty() {
+    return items.into_iter().take(25).collect();
+  }
+
+  let mut ranked: Vec<(f64, T)> = items
+    .into_iter()
+    .filter_map(|item| {
+      let score = names(&item)
+        .iter()
+        .map(|name| {
+          let name = name.to_lowercase();
+          let similarity = trigram_similarity(&name, &partial);
+          match (name.starts_with(&partial), name.contains(&partial)) {
+            (true, _) => 2.0 + similarity,
+            (false, true) => 1.0 + similarity,
+            (false, false) => similarity,
+          }
+        })
+        .fold(0.0, f64::max);
+
+      // Below this, names share little more than a letter or two with the input
+      match score >= 0.2 {
+        true => Some((score, item)),
+        false => None,
+      }
+    })
+    .collect();
+
+  ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+  ranked.into_iter().take(25).map(|(_, item)| item).collect()
+}
+
 pub async fn course_not_found(
   ctx: Context<'_>,
   transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
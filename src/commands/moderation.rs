@@ -0,0 +1,363 @@
+use crate::appeals::appeal_button;
+use crate::commands::erase::{notification_embed, save_notification_method};
+use crate::commands::reasons::{autocomplete_reason, resolve_reason};
+use crate::commands::{commit_and_say, MessageType};
+use crate::config::{BloomBotEmbed, CHANNELS};
+use crate::database::{DatabaseHandler, ModAction, ModCase, NotificationMethod};
+use crate::pagination::{PageRowRef, Pagination};
+use crate::Context;
+use anyhow::Result;
+use log::error;
+use poise::serenity_prelude::{self as serenity, builder::*, Mentionable};
+use poise::{ChoiceParameter, CreateReply};
+
+/// Commands for the moderation log
+///
+/// Commands to warn members, add notes, time out members, and review a member's moderation history.
+///
+/// Requires `Moderate Members` permissions.
+#[poise::command(
+  slash_command,
+  rename = "mod",
+  required_permissions = "MODERATE_MEMBERS",
+  default_member_permissions = "MODERATE_MEMBERS",
+  category = "Moderator Commands",
+  subcommands("warn", "note", "timeout", "history"),
+  subcommand_required,
+  //hide_in_help,
+  guild_only
+)]
+pub async fn moderation(_: Context<'_>) -> Result<()> {
+  Ok(())
+}
+
+/// Warn a member
+///
+/// Records a warning in the moderation log and notifies the member via DM.
+#[poise::command(slash_command)]
+pub async fn warn(
+  ctx: Context<'_>,
+  #[description = "The member to warn"] user: serenity::User,
+  #[max_length = 512]
+  #[description = "The reason for the warning, or the key of a reason template"]
+  #[autocomplete = "autocomplete_reason"]
+  reason: String,
+  #[description = "A link to evidence for the warning"] evidence: Option<String>,
+) -> Result<()> {
+  ctx.defer_ephemeral().await?;
+
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+  let (reason, reason_key) = resolve_reason(&mut transaction, &guild_id, &reason).await?;
+
+  let case = DatabaseHandler::add_mod_case(
+    &mut transaction,
+    &guild_id,
+    &user.id,
+    Some(&ctx.author().id),
+    ModAction::Warning,
+    Some(&reason),
+    reason_key.as_deref(),
+    evidence.as_deref(),
+    None,
+    chrono::Utc::now(),
+  )
+  .await?;
+
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  // The case is saved, so the member is only given an appeal button for a case that exists, and
+  // failures past this point are logged rather than failing the command
+  let dm_embed = notification_embed(
+    BloomBotEmbed::new(),
+    "You have received a warning from the moderation team.",
+    &reason,
+  );
+  let notified = user
+    .direct_message(
+      ctx,
+      CreateMessage::new()
+        .embed(dm_embed)
+        .components(appeal_button(&case.id)),
+    )
+    .await
+    .is_ok();
+  let notification_method = match notified {
+    true => NotificationMethod::DirectMessage,
+    false => NotificationMethod::Failed,
+  };
+  save_notification_method(&data.db, &case.id, notification_method).await;
+
+  if let Err(err) = log_case(ctx, &case).await {
+    error!("Error logging case #{}: {}", case.case_number, err);
+  }
+
+  ctx
+    .send(
+      CreateReply::default()
+        .content(match notified {
+          true => format!(
+            ":white_check_mark: Warning recorded as case #{}. User was notified via DM.",
+            case.case_number
+          ),
+          false => format!(
+            ":white_check_mark: Warning recorded as case #{}. Could not notify the user via DM.",
+            case.case_number
+          ),
+        })
+        .ephemeral(true),
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// Add a note about a member
+///
+/// Records a note in the moderation log. The member is not notified.
+#[poise::command(slash_command)]
+pub async fn note(
+  ctx: Context<'_>,
+  #[description = "The member the note is about"] user: serenity::User,
+  #[max_length = 1000]
+  #[description = "The note"]
+  note: String,
+  #[description = "A link to evidence for the note"] evidence: Option<String>,
+) -> Result<()> {
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+  let case = DatabaseHandler::add_mod_case(
+    &mut transaction,
+    &guild_id,
+    &user.id,
+    Some(&ctx.author().id),
+    ModAction::Note,
+    Some(&note),
+    None,
+    evidence.as_deref(),
+    None,
+    chrono::Utc::now(),
+  )
+  .await?;
+
+  commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      ":white_check_mark: Note recorded as case #{}.",
+      case.case_number
+    )),
+    true,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Time out a member
+///
+/// Times out a member and records the timeout in the moderation log.
+#[poise::command(slash_command)]
+pub async fn timeout(
+  ctx: Context<'_>,
+  #[description = "The member to time out"] user: serenity::User,
+  #[description = "How long to time out the member for, in minutes"]
+  #[min = 1]
+  #[max = 40320] // Discord allows timeouts of up to 28 days
+  minutes: u32,
+  #[max_length = 512] // Max length for audit log reason
+  #[description = "The reason for the timeout, or the key of a reason template"]
+  #[autocomplete = "autocomplete_reason"]
+  reason: String,
+  #[description = "A link to evidence for the timeout"] evidence: Option<String>,
+) -> Result<()> {
+  ctx.defer_ephemeral().await?;
+
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+  let (reason, reason_key) = resolve_reason(&mut transaction, &guild_id, &reason).await?;
+
+  let until = chrono::Utc::now() + chrono::Duration::minutes(minutes.into());
+
+  guild_id
+    .edit_member(
+      ctx,
+      user.id,
+      EditMember::new()
+        .disable_communication_until_datetime(serenity::Timestamp::from(until))
+        .audit_log_reason(&reason),
+    )
+    .await?;
+
+  let case = DatabaseHandler::add_mod_case(
+    &mut transaction,
+    &guild_id,
+    &user.id,
+    Some(&ctx.author().id),
+    ModAction::Timeout,
+    Some(&format!("{} (until <t:{}:f>)", reason, until.timestamp())),
+    reason_key.as_deref(),
+    evidence.as_deref(),
+    None,
+    chrono::Utc::now(),
+  )
+  .await?;
+
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  if let Err(err) = log_case(ctx, &case).await {
+    error!("Error logging case #{}: {}", case.case_number, err);
+  }
+
+  ctx
+    .send(
+      CreateReply::default()
+        .content(format!(
+          ":white_check_mark: Member timed out until <t:{}:f>. Recorded as case #{}.",
+          until.timestamp(),
+          case.case_number
+        ))
+        .ephemeral(true),
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// Show a member's moderation history
+///
+/// Shows the warnings, notes, timeouts, and erases recorded for a member, newest first.
+#[poise::command(slash_command)]
+pub async fn history(
+  ctx: Context<'_>,
+  #[description = "The member to show history for"] user: serenity::User,
+  #[description = "Only show cases of this type"] action: Option<ModAction>,
+  #[description = "The page to show"] page: Option<usize>,
+) -> Result<()> {
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+  let user_nick_or_name = match user.nick_in(&ctx, guild_id).await {
+    Some(nick) => nick,
+    None => user.name.clone(),
+  };
+
+  let privacy = ctx.channel_id() != CHANNELS.logs;
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+
+  // Define some unique identifiers for the navigation buttons
+  let ctx_id = ctx.id();
+  let prev_button_id = format!("{}prev", ctx_id);
+  let next_button_id = format!("{}next", ctx_id);
+
+  let mut current_page = page.unwrap_or(0).saturating_sub(1);
+
+  let cases = DatabaseHandler::get_mod_cases(&mut transaction, &guild_id, &user.id, action).await?;
+  let cases: Vec<PageRowRef> = cases.iter().map(|case| case as _).collect();
+  drop(transaction);
+
+  let title = match action {
+    Some(action) => format!("{} History for {}", action.name(), user_nick_or_name),
+    None => format!("Moderation History for {}", user_nick_or_name),
+  };
+  let pagination = Pagination::new(title, cases).await?;
+
+  if pagination.get_page(current_page).is_none() {
+    current_page = pagination.get_last_page_number();
+  }
+
+  let first_page = pagination.create_page_embed(current_page);
+
+  ctx
+    .send({
+      let mut f = CreateReply::default();
+      if pagination.get_page_count() > 1 {
+        f = f.components(vec![CreateActionRow::Buttons(vec![
+          CreateButton::new(&prev_button_id).label("Previous"),
+          CreateButton::new(&next_button_id).label("Next"),
+        ])])
+      }
+      f.embeds = vec![first_page];
+      f.ephemeral(privacy)
+    })
+    .await?;
+
+  // Loop through incoming interactions with the navigation buttons
+  while let Some(press) = serenity::ComponentInteractionCollector::new(ctx)
+    // We defined our button IDs to start with `ctx_id`. If they don't, some other command's
+    // button was pressed
+    .filter(move |press| press.data.custom_id.starts_with(&ctx_id.to_string()))
+    // Timeout when no navigation button has been pressed for 24 hours
+    .timeout(std::time::Duration::from_secs(3600 * 24))
+    .await
+  {
+    // Depending on which button was pressed, go to next or previous page
+    if press.data.custom_id == next_button_id {
+      current_page = pagination.update_page_number(current_page, 1);
+    } else if press.data.custom_id == prev_button_id {
+      current_page = pagination.update_page_number(current_page, -1);
+    } else {
+      // This is an unrelated button interaction
+      continue;
+    }
+
+    // Update the message with the new page contents
+    press
+      .create_response(
+        ctx,
+        CreateInteractionResponse::UpdateMessage(
+          CreateInteractionResponseMessage::new().embed(pagination.create_page_embed(current_page)),
+        ),
+      )
+      .await?;
+  }
+
+  Ok(())
+}
+
+/// Posts a case to the log channel.
+async fn log_case(ctx: Context<'_>, case: &ModCase) -> Result<()> {
+  let mut log_embed = BloomBotEmbed::new()
+    .title(format!(
+      "Case #{} — {}",
+      case.case_number,
+      case.action.name()
+    ))
+    .description(format!(
+      "**Member**: {}\n**Reason**: {}",
+      case.user_id.mention(),
+      case.reason.as_deref().unwrap_or("No reason provided."),
+    ))
+    .footer(
+      CreateEmbedFooter::new(format!(
+        "Moderator: {} ({})",
+        ctx.author().name,
+        ctx.author().id
+      ))
+      .icon_url(ctx.author().avatar_url().unwrap_or_default()),
+    );
+
+  if let Some(evidence_link) = &case.evidence_link {
+    log_embed = log_embed.field("Evidence", evidence_link, false);
+  }
+
+  serenity::ChannelId::new(CHANNELS.logs)
+    .send_message(ctx, CreateMessage::new().embed(log_embed))
+    .await?;
+
+  Ok(())
+}
@@ -1,4 +1,4 @@
-use crate::commands::{commit_and_say, MessageType};
+use crate::commands::{commit_and_say, rank_suggestions, MessageType};
 use crate::database::DatabaseHandler;
 use crate::pagination::{PageRowRef, Pagination};
 use crate::{Context, Data as AppData, Error as AppError};
@@ -30,6 +30,48 @@ struct EditQuoteModal {
   author: Option<String>,
 }
 
+/// Suggests quotes whose text, author, or ID matches what has been typed so far. Each suggestion
+/// shows the quote and submits its ID.
+pub async fn autocomplete_quote(
+  ctx: Context<'_>,
+  partial: &str,
+) -> Vec<serenity::AutocompleteChoice> {
+  let guild_id = match ctx.guild_id() {
+    Some(guild_id) => guild_id,
+    None => return Vec::new(),
+  };
+
+  let mut transaction = match ctx.data().db.start_transaction_with_retry(5).await {
+    Ok(transaction) => transaction,
+    Err(_) => return Vec::new(),
+  };
+
+  let quotes = DatabaseHandler::get_all_quotes(&mut transaction, &guild_id)
+    .await
+    .unwrap_or_default();
+
+  rank_suggestions(partial, quotes, |quote| {
+    let mut names = vec![quote.quote.clone(), quote.id.clone()];
+    names.extend(quote.author.clone());
+    names
+  })
+  .into_iter()
+  .map(|quote| {
+    let name = format!(
+      "{} — {}",
+      quote.quote,
+      quote.author.as_deref().unwrap_or("Anonymous")
+    );
+    // Choice names are limited to 100 characters
+    let name = match name.chars().count() > 100 {
+      true => format!("{}...", name.chars().take(97).collect::<String>()),
+      false => name,
+    };
+    serenity::AutocompleteChoice::new(name, quote.id)
+  })
+  .collect()
+}
+
 /// Commands for managing quotes
 ///
 /// Commands to list, add, edit, or remove quotes.
@@ -104,7 +146,9 @@ pub async fn add(ctx: poise::ApplicationContext<'_, AppData, AppError>) -> Resul
 #[poise::command(slash_command)]
 pub async fn edit(
   ctx: poise::ApplicationContext<'_, AppData, AppError>,
-  #[description = "ID of the quote to edit"] quote_id: String,
+  #[description = "ID of the quote to edit"]
+  #[autocomplete = "autocomplete_quote"]
+  quote_id: String,
 ) -> Result<()> {
   let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
 
@@ -175,7 +219,9 @@ pub async fn edit(
 #[poise::command(slash_command)]
 pub async fn remove(
   ctx: Context<'_>,
-  #[description = "The quote ID to remove"] id: String,
+  #[description = "The quote ID to remove"]
+  #[autocomplete = "autocomplete_quote"]
+  id: String,
 ) -> Result<()> {
   let data = ctx.data();
 
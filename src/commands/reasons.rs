@@ -0,0 +1,329 @@
+use crate::commands::{commit_and_say, MessageType};
+use crate::database::{DatabaseHandler, ModReason};
+use crate::pagination::{PageRowRef, Pagination};
+use crate::Context;
+use anyhow::Result;
+use poise::serenity_prelude::{self as serenity, builder::*};
+use poise::CreateReply;
+
+/// Suggests reason templates whose key or text matches what has been typed so far. The value
+/// of each suggestion is the template key, which [`resolve_reason`] expands to the full text.
+pub async fn autocomplete_reason(
+  ctx: Context<'_>,
+  partial: &str,
+) -> Vec<serenity::AutocompleteChoice> {
+  let guild_id = match ctx.guild_id() {
+    Some(guild_id) => guild_id,
+    None => return Vec::new(),
+  };
+
+  let mut transaction = match ctx.data().db.start_transaction_with_retry(5).await {
+    Ok(transaction) => transaction,
+    Err(_) => return Vec::new(),
+  };
+
+  let reasons = DatabaseHandler::get_mod_reasons(&mut transaction, &guild_id, None)
+    .await
+    .unwrap_or_default();
+
+  let partial = partial.to_lowercase();
+
+  reasons
+    .into_iter()
+    .filter(|reason| {
+      reason.key.to_lowercase().starts_with(&partial)
+        || reason.text.to_lowercase().contains(&partial)
+    })
+    .take(25)
+    .map(|reason| {
+      // Choice names are limited to 100 characters
+      let name = format!("{} — {}", reason.key, reason.text);
+      let name = match name.chars().count() > 100 {
+        true => format!("{}...", name.chars().take(97).collect::<String>()),
+        false => name,
+      };
+      serenity::AutocompleteChoice::new(name, reason.key)
+    })
+    .collect()
+}
+
+/// Expands a reason that matches a template key into the template's full text. Returns the
+/// reason to record and the key of the template that was used, if any.
+pub async fn resolve_reason(
+  transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+  guild_id: &serenity::GuildId,
+  reason: &str,
+) -> Result<(String, Option<String>)> {
+  match DatabaseHandler::get_mod_reason(transaction, guild_id, reason.trim()).await? {
+    Some(template) => Ok((template.full_text(), Some(template.key))),
+    None => Ok((reason.to_string(), None)),
+  }
+}
+
+/// Commands for moderation reason templates
+///
+/// Commands to manage the reason templates suggested for erases, warnings, and timeouts.
+///
+/// Requires `Manage Messages` permissions.
+#[poise::command(
+  slash_command,
+  required_permissions = "MANAGE_MESSAGES",
+  default_member_permissions = "MANAGE_MESSAGES",
+  category = "Moderator Commands",
+  subcommands("add", "edit", "remove", "list"),
+  subcommand_required,
+  //hide_in_help,
+  guild_only
+)]
+pub async fn reasons(_: Context<'_>) -> Result<()> {
+  Ok(())
+}
+
+/// Add a reason template
+///
+/// Adds a reason template with a short key, the full reason, and an optional rule link.
+#[poise::command(slash_command)]
+pub async fn add(
+  ctx: Context<'_>,
+  #[max_length = 32]
+  #[description = "A short key for the reason, e.g. rule3"]
+  key: String,
+  #[max_length = 400]
+  #[description = "The full reason"]
+  text: String,
+  #[max_length = 100]
+  #[description = "A link to the rule the reason cites"]
+  rule_link: Option<String>,
+) -> Result<()> {
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+
+  if !DatabaseHandler::add_mod_reason(
+    &mut transaction,
+    &guild_id,
+    key.trim(),
+    &text,
+    rule_link.as_deref(),
+  )
+  .await?
+  {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            ":x: A reason with the key `{}` already exists.",
+            key
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(":white_check_mark: Reason `{}` added.", key.trim())),
+    true,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Edit a reason template
+///
+/// Updates the text and/or rule link of a reason template.
+#[poise::command(slash_command)]
+pub async fn edit(
+  ctx: Context<'_>,
+  #[description = "The key of the reason to edit"]
+  #[autocomplete = "autocomplete_reason"]
+  key: String,
+  #[max_length = 400]
+  #[description = "The new full reason"]
+  text: Option<String>,
+  #[max_length = 100]
+  #[description = "The new rule link"]
+  rule_link: Option<String>,
+) -> Result<()> {
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+
+  if text.is_none() && rule_link.is_none() {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(":x: Provide a new text or rule link.")
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+
+  if !DatabaseHandler::update_mod_reason(
+    &mut transaction,
+    &guild_id,
+    &key,
+    text.as_deref(),
+    rule_link.as_deref(),
+  )
+  .await?
+  {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(":x: No reason with the key `{}` exists.", key))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(":white_check_mark: Reason `{}` updated.", key)),
+    true,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Remove a reason template
+///
+/// Removes a reason template. Cases that used it keep their reason.
+#[poise::command(slash_command)]
+pub async fn remove(
+  ctx: Context<'_>,
+  #[description = "The key of the reason to remove"]
+  #[autocomplete = "autocomplete_reason"]
+  key: String,
+) -> Result<()> {
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+
+  if !DatabaseHandler::remove_mod_reason(&mut transaction, &guild_id, &key).await? {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(":x: No reason with the key `{}` exists.", key))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(":white_check_mark: Reason `{}` removed.", key)),
+    true,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// List reason templates
+///
+/// Lists the reason templates, most used first.
+#[poise::command(slash_command)]
+pub async fn list(
+  ctx: Context<'_>,
+  #[description = "The page to show"] page: Option<usize>,
+) -> Result<()> {
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+  let reasons = DatabaseHandler::get_mod_reasons(&mut transaction, &guild_id, None).await?;
+  drop(transaction);
+
+  send_reason_pages(ctx, "Reason Templates", &reasons, page).await
+}
+
+/// Sends paginated reason templates with their use counts.
+pub async fn send_reason_pages(
+  ctx: Context<'_>,
+  title: &str,
+  reasons: &[ModReason],
+  page: Option<usize>,
+) -> Result<()> {
+  // Define some unique identifiers for the navigation buttons
+  let ctx_id = ctx.id();
+  let prev_button_id = format!("{}prev", ctx_id);
+  let next_button_id = format!("{}next", ctx_id);
+
+  let mut current_page = page.unwrap_or(0).saturating_sub(1);
+
+  let reasons: Vec<PageRowRef> = reasons.iter().map(|reason| reason as _).collect();
+  let pagination = Pagination::new(title, reasons).await?;
+
+  if pagination.get_page(current_page).is_none() {
+    current_page = pagination.get_last_page_number();
+  }
+
+  let first_page = pagination.create_page_embed(current_page);
+
+  ctx
+    .send({
+      let mut f = CreateReply::default();
+      if pagination.get_page_count() > 1 {
+        f = f.components(vec![CreateActionRow::Buttons(vec![
+          CreateButton::new(&prev_button_id).label("Previous"),
+          CreateButton::new(&next_button_id).label("Next"),
+        ])])
+      }
+      f.embeds = vec![first_page];
+      f.ephemeral(true)
+    })
+    .await?;
+
+  // Loop through incoming interactions with the navigation buttons
+  while let Some(press) = serenity::ComponentInteractionCollector::new(ctx)
+    // We defined our button IDs to start with `ctx_id`. If they don't, some other command's
+    // button was pressed
+    .filter(move |press| press.data.custom_id.starts_with(&ctx_id.to_string()))
+    // Timeout when no navigation button has been pressed for 24 hours
+    .timeout(std::time::Duration::from_secs(3600 * 24))
+    .await
+  {
+    // Depending on which button was pressed, go to next or previous page
+    if press.data.custom_id == next_button_id {
+      current_page = pagination.update_page_number(current_page, 1);
+    } else if press.data.custom_id == prev_button_id {
+      current_page = pagination.update_page_number(current_page, -1);
+    } else {
+      // This is an unrelated button interaction
+      continue;
+    }
+
+    // Update the message with the new page contents
+    press
+      .create_response(
+        ctx,
+        CreateInteractionResponse::UpdateMessage(
+          CreateInteractionResponseMessage::new().embed(pagination.create_page_embed(current_page)),
+        ),
+      )
+      .await?;
+  }
+
+  Ok(())
+}
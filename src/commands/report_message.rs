@@ -1,7 +1,7 @@
-use crate::config::{BloomBotEmbed, CHANNELS, ROLES};
+use crate::reports;
 use crate::Context;
 use anyhow::Result;
-use poise::serenity_prelude::{self as serenity, builder::*};
+use poise::serenity_prelude as serenity;
 
 /// Reports a message to server staff
 ///
@@ -18,46 +18,23 @@ pub async fn report_message(
   ctx: Context<'_>,
   #[description = "Message to report"] message: serenity::Message,
 ) -> Result<()> {
-  let reporting_user = ctx.author();
-  let report_channel_id = serenity::ChannelId::new(CHANNELS.reportchannel);
-  let message_link = message.link().clone();
-  let message_user = message.author;
-  let message_channel_name = message.channel_id.name(ctx).await?;
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
 
-  let message_content = match message.content.is_empty() {
-    true => match message.attachments.first() {
-      Some(attachment) => format!("**Attachment**\n{}", attachment.url.clone()),
-      None => message.content.clone(),
-    },
-    false => message.content.clone(),
-  };
-
-  report_channel_id
-    .send_message(
-      &ctx,
-      CreateMessage::new()
-        .content(format!("<@&{}> Message Reported", ROLES.staff))
-        .embed(
-          BloomBotEmbed::new()
-            .author(
-              CreateEmbedAuthor::new(format!("{}", &message_user.name))
-                .icon_url(message_user.face()),
-            )
-            .description(message_content)
-            .field("Link", format!("[Go to message]({})", message_link), false)
-            .footer(CreateEmbedFooter::new(format!(
-              "Author ID: {}\nReported via context menu in #{} by {} ({})",
-              &message_user.id, message_channel_name, reporting_user.name, reporting_user.id
-            )))
-            .timestamp(message.timestamp),
-        ),
-    )
-    .await?;
+  let outcome = reports::file_report(
+    ctx.serenity_context(),
+    &ctx.data().db,
+    &guild_id,
+    ctx.author(),
+    &message,
+    "context menu",
+  )
+  .await?;
 
   ctx
     .send(
       poise::CreateReply::default()
-        .content("Your report has been sent to the moderation team.")
+        .content(outcome.reply())
         .ephemeral(true),
     )
     .await?;
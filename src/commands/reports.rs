@@ -0,0 +1,111 @@
+use crate::database::{DatabaseHandler, ReportStatus};
+use crate::pagination::{PageRowRef, Pagination};
+use crate::Context;
+use anyhow::Result;
+use poise::serenity_prelude::{self as serenity, builder::*};
+use poise::{ChoiceParameter, CreateReply};
+
+/// Commands for the report queue
+///
+/// Commands to review reported messages. Reports are claimed, resolved, or dismissed with the buttons on the report in the report channel.
+///
+/// Requires `Manage Messages` permissions.
+#[poise::command(
+  slash_command,
+  required_permissions = "MANAGE_MESSAGES",
+  default_member_permissions = "MANAGE_MESSAGES",
+  category = "Moderator Commands",
+  subcommands("list"),
+  subcommand_required,
+  //hide_in_help,
+  guild_only
+)]
+pub async fn reports(_: Context<'_>) -> Result<()> {
+  Ok(())
+}
+
+/// List reports
+///
+/// Lists reports, newest first, optionally filtered by status.
+#[poise::command(slash_command)]
+pub async fn list(
+  ctx: Context<'_>,
+  #[description = "Only show reports with this status"] status: Option<ReportStatus>,
+  #[description = "The page to show"] page: Option<usize>,
+) -> Result<()> {
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+
+  // Define some unique identifiers for the navigation buttons
+  let ctx_id = ctx.id();
+  let prev_button_id = format!("{}prev", ctx_id);
+  let next_button_id = format!("{}next", ctx_id);
+
+  let mut current_page = page.unwrap_or(0).saturating_sub(1);
+
+  let reports = DatabaseHandler::get_reports(&mut transaction, &guild_id, status).await?;
+  let reports: Vec<PageRowRef> = reports.iter().map(|report| report as _).collect();
+  drop(transaction);
+
+  let title = match status {
+    Some(status) => format!("{} Reports", status.name()),
+    None => "Reports".to_string(),
+  };
+  let pagination = Pagination::new(title, reports).await?;
+
+  if pagination.get_page(current_page).is_none() {
+    current_page = pagination.get_last_page_number();
+  }
+
+  let first_page = pagination.create_page_embed(current_page);
+
+  ctx
+    .send({
+      let mut f = CreateReply::default();
+      if pagination.get_page_count() > 1 {
+        f = f.components(vec![CreateActionRow::Buttons(vec![
+          CreateButton::new(&prev_button_id).label("Previous"),
+          CreateButton::new(&next_button_id).label("Next"),
+        ])])
+      }
+      f.embeds = vec![first_page];
+      f.ephemeral(true)
+    })
+    .await?;
+
+  // Loop through incoming interactions with the navigation buttons
+  while let Some(press) = serenity::ComponentInteractionCollector::new(ctx)
+    // We defined our button IDs to start with `ctx_id`. If they don't, some other command's
+    // button was pressed
+    .filter(move |press| press.data.custom_id.starts_with(&ctx_id.to_string()))
+    // Timeout when no navigation button has been pressed for 24 hours
+    .timeout(std::time::Duration::from_secs(3600 * 24))
+    .await
+  {
+    // Depending on which button was pressed, go to next or previous page
+    if press.data.custom_id == next_button_id {
+      current_page = pagination.update_page_number(current_page, 1);
+    } else if press.data.custom_id == prev_button_id {
+      current_page = pagination.update_page_number(current_page, -1);
+    } else {
+      // This is an unrelated button interaction
+      continue;
+    }
+
+    // Update the message with the new page contents
+    press
+      .create_response(
+        ctx,
+        CreateInteractionResponse::UpdateMessage(
+          CreateInteractionResponseMessage::new().embed(pagination.create_page_embed(current_page)),
+        ),
+      )
+      .await?;
+  }
+
+  Ok(())
+}
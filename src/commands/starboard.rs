@@ -0,0 +1,424 @@
+use crate::commands::{commit_and_say, MessageType};
+use crate::config::{BloomBotEmbed, MIN_STARS};
+use crate::database::{DatabaseHandler, ReactionBoard};
+use crate::pagination::{PageRowRef, Pagination};
+use crate::reaction_boards;
+use crate::Context;
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use poise::serenity_prelude::{self as serenity, builder::*, Mentionable};
+use poise::{ChoiceParameter, CreateReply};
+
+#[derive(poise::ChoiceParameter)]
+pub enum StarPeriod {
+  #[name = "Past week"]
+  Week,
+  #[name = "Past month"]
+  Month,
+  #[name = "Past year"]
+  Year,
+  #[name = "All time"]
+  AllTime,
+}
+
+impl StarPeriod {
+  fn since(&self) -> Option<chrono::DateTime<Utc>> {
+    match self {
+      StarPeriod::Week => Some(Utc::now() - Duration::days(7)),
+      StarPeriod::Month => Some(Utc::now() - Duration::days(30)),
+      StarPeriod::Year => Some(Utc::now() - Duration::days(365)),
+      StarPeriod::AllTime => None,
+    }
+  }
+}
+
+/// Suggests the names of the guild's boards, including the starboard.
+pub async fn autocomplete_board(ctx: Context<'_>, partial: &str) -> Vec<String> {
+  let guild_id = match ctx.guild_id() {
+    Some(guild_id) => guild_id,
+    None => return Vec::new(),
+  };
+
+  let mut transaction = match ctx.data().db.start_transaction_with_retry(5).await {
+    Ok(transaction) => transaction,
+    Err(_) => return Vec::new(),
+  };
+
+  let boards = reaction_boards::get_boards(&mut transaction, &guild_id)
+    .await
+    .unwrap_or_default();
+
+  boards
+    .into_iter()
+    .map(|board| board.name)
+    .filter(|name| name.to_lowercase().starts_with(&partial.to_lowercase()))
+    .collect()
+}
+
+/// Starboard hall of fame and settings
+///
+/// Commands to see the most-starred messages and the members who received the most stars, on the starboard or any other reaction board.
+///
+/// Staff can also set per-channel star thresholds and exclude channels from the starboard, which requires `Manage Roles` permissions. NSFW channels are always excluded.
+#[poise::command(
+  slash_command,
+  category = "Informational",
+  subcommands("top", "users", "threshold", "exclude", "include", "settings"),
+  subcommand_required,
+  guild_only
+)]
+pub async fn starboard(_: Context<'_>) -> Result<()> {
+  Ok(())
+}
+
+async fn send_pages(
+  ctx: Context<'_>,
+  pagination: Pagination<'_>,
+  page: Option<usize>,
+) -> Result<()> {
+  // Define some unique identifiers for the navigation buttons
+  let ctx_id = ctx.id();
+  let prev_button_id = format!("{}prev", ctx_id);
+  let next_button_id = format!("{}next", ctx_id);
+
+  let mut current_page = page.unwrap_or(0).saturating_sub(1);
+
+  if pagination.get_page(current_page).is_none() {
+    current_page = pagination.get_last_page_number();
+  }
+
+  let first_page = pagination.create_page_embed(current_page);
+
+  ctx
+    .send({
+      let mut f = CreateReply::default();
+      if pagination.get_page_count() > 1 {
+        f = f.components(vec![CreateActionRow::Buttons(vec![
+          CreateButton::new(&prev_button_id).label("Previous"),
+          CreateButton::new(&next_button_id).label("Next"),
+        ])])
+      }
+      f.embeds = vec![first_page];
+      f.ephemeral(true)
+    })
+    .await?;
+
+  // Loop through incoming interactions with the navigation buttons
+  while let Some(press) = serenity::ComponentInteractionCollector::new(ctx)
+    // We defined our button IDs to start with `ctx_id`. If they don't, some other command's
+    // button was pressed
+    .filter(move |press| press.data.custom_id.starts_with(&ctx_id.to_string()))
+    // Timeout when no navigation button has been pressed for 24 hours
+    .timeout(std::time::Duration::from_secs(3600 * 24))
+    .await
+  {
+    // Depending on which button was pressed, go to next or previous page
+    if press.data.custom_id == next_button_id {
+      current_page = pagination.update_page_number(current_page, 1);
+    } else if press.data.custom_id == prev_button_id {
+      current_page = pagination.update_page_number(current_page, -1);
+    } else {
+      // This is an unrelated button interaction
+      continue;
+    }
+
+    // Update the message with the new page contents
+    press
+      .create_response(
+        ctx,
+        CreateInteractionResponse::UpdateMessage(
+          CreateInteractionResponseMessage::new().embed(pagination.create_page_embed(current_page)),
+        ),
+      )
+      .await?;
+  }
+
+  Ok(())
+}
+
+/// See the most-starred messages
+///
+/// Shows the messages that received the most stars, optionally limited to a period. Other reaction boards can be shown instead of the starboard.
+#[poise::command(slash_command)]
+pub async fn top(
+  ctx: Context<'_>,
+  #[description = "The period to show (Defaults to all time)"] period: Option<StarPeriod>,
+  #[description = "The board to show (Defaults to the starboard)"]
+  #[autocomplete = "autocomplete_board"]
+  board: Option<String>,
+  #[description = "The page to show"] page: Option<usize>,
+) -> Result<()> {
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+
+  let period = period.unwrap_or(StarPeriod::AllTime);
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+  let board = match board {
+    Some(board_name) => {
+      match reaction_boards::find_board(&mut transaction, &guild_id, &board_name).await? {
+        Some(board) => board,
+        None => {
+          ctx
+            .send(
+              CreateReply::default()
+                .content(":x: Board does not exist.")
+                .ephemeral(true),
+            )
+            .await?;
+          return Ok(());
+        }
+      }
+    }
+    None => ReactionBoard::starboard(),
+  };
+  let star_messages =
+    DatabaseHandler::get_top_star_messages(&mut transaction, &guild_id, &board.id, period.since())
+      .await?;
+  let star_messages: Vec<PageRowRef> = star_messages
+    .iter()
+    .map(|star_message| star_message as _)
+    .collect();
+  drop(transaction);
+
+  let pagination = Pagination::new(
+    match board.is_starboard() {
+      true => format!("Top Starred Messages ({})", period.name()),
+      false => format!("Top Messages on {} ({})", board.name, period.name()),
+    },
+    star_messages,
+  )
+  .await?;
+
+  send_pages(ctx, pagination, page).await
+}
+
+/// See the members who received the most stars
+///
+/// Shows the members whose messages received the most stars on the starboard, optionally limited to a period. Other reaction boards can be shown instead of the starboard.
+#[poise::command(slash_command)]
+pub async fn users(
+  ctx: Context<'_>,
+  #[description = "The period to show (Defaults to all time)"] period: Option<StarPeriod>,
+  #[description = "The board to show (Defaults to the starboard)"]
+  #[autocomplete = "autocomplete_board"]
+  board: Option<String>,
+  #[description = "The page to show"] page: Option<usize>,
+) -> Result<()> {
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+
+  let period = period.unwrap_or(StarPeriod::AllTime);
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+  let board = match board {
+    Some(board_name) => {
+      match reaction_boards::find_board(&mut transaction, &guild_id, &board_name).await? {
+        Some(board) => board,
+        None => {
+          ctx
+            .send(
+              CreateReply::default()
+                .content(":x: Board does not exist.")
+                .ephemeral(true),
+            )
+            .await?;
+          return Ok(());
+        }
+      }
+    }
+    None => ReactionBoard::starboard(),
+  };
+  let recipients =
+    DatabaseHandler::get_star_recipients(&mut transaction, &guild_id, &board.id, period.since())
+      .await?;
+  let recipients: Vec<PageRowRef> = recipients.iter().map(|recipient| recipient as _).collect();
+  drop(transaction);
+
+  let pagination = Pagination::new(
+    match board.is_starboard() {
+      true => format!("Most Starred Members ({})", period.name()),
+      false => format!("Top Members on {} ({})", board.name, period.name()),
+    },
+    recipients,
+  )
+  .await?;
+
+  send_pages(ctx, pagination, page).await
+}
+
+/// Set the star threshold for a channel
+///
+/// Sets the number of stars a message in the channel needs to reach the starboard. Leave out the number of stars to use the default threshold again.
+///
+/// Requires `Manage Roles` permissions.
+#[poise::command(slash_command, required_permissions = "MANAGE_ROLES")]
+pub async fn threshold(
+  ctx: Context<'_>,
+  #[description = "The channel to set the threshold for"]
+  #[channel_types("Text", "Forum")]
+  channel: serenity::GuildChannel,
+  #[description = "Stars needed to reach the starboard (Defaults to the global threshold)"]
+  #[min = 1]
+  stars: Option<i32>,
+) -> Result<()> {
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+  DatabaseHandler::set_starboard_threshold(&mut transaction, &guild_id, &channel.id, stars).await?;
+
+  let message = match stars {
+    Some(stars) => format!(
+      ":white_check_mark: Messages in {} now need {} stars to reach the starboard.",
+      channel.mention(),
+      stars
+    ),
+    None => format!(
+      ":white_check_mark: Messages in {} now use the default threshold of {} stars.",
+      channel.mention(),
+      MIN_STARS
+    ),
+  };
+
+  commit_and_say(ctx, transaction, MessageType::TextOnly(message), true).await?;
+
+  Ok(())
+}
+
+/// Exclude a channel from the starboard
+///
+/// Excludes a channel, and any threads in it, from the starboard and every other reaction board.
+///
+/// Requires `Manage Roles` permissions.
+#[poise::command(slash_command, required_permissions = "MANAGE_ROLES")]
+pub async fn exclude(
+  ctx: Context<'_>,
+  #[description = "The channel to exclude"]
+  #[channel_types("Text", "Forum")]
+  channel: serenity::GuildChannel,
+) -> Result<()> {
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+  DatabaseHandler::set_starboard_excluded(&mut transaction, &guild_id, &channel.id, true).await?;
+
+  commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      ":white_check_mark: Messages in {} will no longer be posted to the starboard.",
+      channel.mention()
+    )),
+    true,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Include a previously excluded channel in the starboard
+///
+/// Allows messages in a previously excluded channel to reach the starboard again.
+///
+/// Requires `Manage Roles` permissions.
+#[poise::command(slash_command, required_permissions = "MANAGE_ROLES")]
+pub async fn include(
+  ctx: Context<'_>,
+  #[description = "The channel to include"]
+  #[channel_types("Text", "Forum")]
+  channel: serenity::GuildChannel,
+) -> Result<()> {
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+  DatabaseHandler::set_starboard_excluded(&mut transaction, &guild_id, &channel.id, false).await?;
+
+  let message = match channel.nsfw {
+    true => format!(
+      ":white_check_mark: {} is no longer excluded, but NSFW channels are never posted to the starboard.",
+      channel.mention()
+    ),
+    false => format!(
+      ":white_check_mark: Messages in {} can be posted to the starboard again.",
+      channel.mention()
+    ),
+  };
+
+  commit_and_say(ctx, transaction, MessageType::TextOnly(message), true).await?;
+
+  Ok(())
+}
+
+/// Show the starboard settings
+///
+/// Shows the excluded channels and the channels with their own star threshold.
+///
+/// Requires `Manage Roles` permissions.
+#[poise::command(slash_command, required_permissions = "MANAGE_ROLES")]
+pub async fn settings(ctx: Context<'_>) -> Result<()> {
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+  let channels = DatabaseHandler::get_starboard_channels(&mut transaction, &guild_id).await?;
+
+  let excluded = channels
+    .iter()
+    .filter(|channel| channel.excluded)
+    .map(|channel| channel.channel_id.mention().to_string())
+    .collect::<Vec<String>>();
+
+  let thresholds = channels
+    .iter()
+    .filter(|channel| !channel.excluded)
+    .filter_map(|channel| {
+      channel
+        .min_stars
+        .map(|min_stars| format!("{}: {} stars", channel.channel_id.mention(), min_stars))
+    })
+    .collect::<Vec<String>>();
+
+  let embed = BloomBotEmbed::new()
+    .title("Starboard Settings")
+    .description(format!(
+      "Messages need {} stars unless their channel has its own threshold. The author's own star and stars from bots are not counted. Excluded channels and NSFW channels are left out of every reaction board.",
+      MIN_STARS
+    ))
+    .field(
+      "Excluded Channels",
+      match excluded.is_empty() {
+        true => "None".to_string(),
+        false => excluded.join("\n"),
+      },
+      false,
+    )
+    .field(
+      "Channel Thresholds",
+      match thresholds.is_empty() {
+        true => "None".to_string(),
+        false => thresholds.join("\n"),
+      },
+      false,
+    );
+
+  ctx
+    .send(CreateReply::default().embed(embed).ephemeral(true))
+    .await?;
+
+  Ok(())
+}
@@ -1,3 +1,4 @@
+use crate::badges::Badge;
 use crate::config::{BloomBotEmbed, ROLES};
 use crate::database::Timeframe;
 use crate::database::{DatabaseHandler, TrackingProfile};
@@ -164,6 +165,17 @@ pub async fn user(
     }
   }
 
+  let badges: Vec<&str> = DatabaseHandler::get_user_badges(&mut transaction, &guild_id, &user.id)
+    .await?
+    .iter()
+    .filter_map(|unlocked| Badge::from_key(&unlocked.badge_key))
+    .map(|badge| badge.emoji())
+    .collect();
+
+  if !badges.is_empty() {
+    embed = embed.field("Badges", badges.join(" "), false);
+  }
+
   // Role-based bar color for donators; default otherwise
   let bar_color = if user.has_role(&ctx, guild_id, config::ROLES.patreon).await?
     || user.has_role(&ctx, guild_id, config::ROLES.kofi).await?
@@ -0,0 +1,280 @@
+use crate::commands::terms::{autocomplete_term, term_not_found};
+use crate::commands::{commit_and_say, MessageType};
+use crate::config::{BloomBotEmbed, TERM_OF_THE_DAY_HOUR};
+use crate::database::DatabaseHandler;
+use crate::Context;
+use anyhow::Result;
+use poise::serenity_prelude::{self as serenity, Mentionable};
+use poise::CreateReply;
+
+/// Commands for managing the term of the day
+///
+/// Commands to choose the channel the daily glossary term is posted to, and to pin or skip upcoming terms. Terms are chosen at random, without repeats until every term has been posted.
+///
+/// Requires `Manage Roles` permissions.
+#[poise::command(
+  slash_command,
+  required_permissions = "MANAGE_ROLES",
+  default_member_permissions = "MANAGE_ROLES",
+  category = "Moderator Commands",
+  subcommands("channel", "disable", "pin", "skip", "upcoming"),
+  subcommand_required,
+  //hide_in_help,
+  guild_only
+)]
+pub async fn term_of_the_day(_: Context<'_>) -> Result<()> {
+  Ok(())
+}
+
+/// Set the term of the day channel
+///
+/// Sets the channel the term of the day is posted to, turning the daily posts on if they were off.
+#[poise::command(slash_command)]
+pub async fn channel(
+  ctx: Context<'_>,
+  #[description = "The channel to post the term of the day to"]
+  #[channel_types("Text")]
+  channel: serenity::GuildChannel,
+) -> Result<()> {
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+  DatabaseHandler::set_term_of_the_day_channel(&mut transaction, &guild_id, &channel.id).await?;
+
+  commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      ":white_check_mark: The term of the day will be posted to {} daily from {}:00 UTC.",
+      channel.mention(),
+      TERM_OF_THE_DAY_HOUR
+    )),
+    true,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Stop posting the term of the day
+///
+/// Stops posting the term of the day. Pinned terms and the history of posted terms are kept.
+#[poise::command(slash_command)]
+pub async fn disable(ctx: Context<'_>) -> Result<()> {
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+  if !DatabaseHandler::remove_term_of_the_day_channel(&mut transaction, &guild_id).await? {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(":x: The term of the day is not being posted.")
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(
+      ":white_check_mark: The term of the day will no longer be posted.".to_string(),
+    ),
+    true,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Pin a term as an upcoming term of the day
+///
+/// Queues a term to be posted as the term of the day before any randomly chosen term. Pinned terms are posted in the order they were pinned.
+#[poise::command(slash_command)]
+pub async fn pin(
+  ctx: Context<'_>,
+  #[description = "The term to pin"]
+  #[autocomplete = "autocomplete_term"]
+  term: String,
+) -> Result<()> {
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+
+  let term = match DatabaseHandler::get_term(&mut transaction, &guild_id, &term).await? {
+    Some(term) => term,
+    None => {
+      term_not_found(ctx, &mut transaction, guild_id, term).await?;
+      return Ok(());
+    }
+  };
+
+  if !DatabaseHandler::add_term_of_the_day_pin(
+    &mut transaction,
+    &guild_id,
+    &term.term_name,
+    &ctx.author().id,
+  )
+  .await?
+  {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(":x: `{}` is already pinned.", term.term_name))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      ":white_check_mark: `{}` has been pinned as an upcoming term of the day.",
+      term.term_name
+    )),
+    true,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Skip a term of the day
+///
+/// Skips a term so that it isn't posted until every other term has been, and unpins it if it was pinned.
+#[poise::command(slash_command)]
+pub async fn skip(
+  ctx: Context<'_>,
+  #[description = "The term to skip"]
+  #[autocomplete = "autocomplete_term"]
+  term: String,
+) -> Result<()> {
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+
+  let term = match DatabaseHandler::get_term(&mut transaction, &guild_id, &term).await? {
+    Some(term) => term,
+    None => {
+      term_not_found(ctx, &mut transaction, guild_id, term).await?;
+      return Ok(());
+    }
+  };
+
+  let unpinned =
+    DatabaseHandler::remove_term_of_the_day_pin(&mut transaction, &guild_id, &term.term_name)
+      .await?;
+
+  let cycle = DatabaseHandler::get_term_of_the_day_cycle(&mut transaction, &guild_id).await?;
+  let done =
+    DatabaseHandler::term_of_the_day_done(&mut transaction, &guild_id, &term.term_name, cycle)
+      .await?;
+
+  if done && !unpinned {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            ":x: `{}` has already been posted or skipped in this cycle.",
+            term.term_name
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  if !done {
+    DatabaseHandler::add_term_of_the_day(&mut transaction, &guild_id, &term.term_name, cycle, true)
+      .await?;
+  }
+
+  commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      ":white_check_mark: `{}` will be skipped until every other term has been posted.",
+      term.term_name
+    )),
+    true,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// See upcoming terms of the day
+///
+/// Shows the term of the day channel, the pinned terms that will be posted next, and how far through the glossary the current cycle is.
+#[poise::command(slash_command)]
+pub async fn upcoming(ctx: Context<'_>) -> Result<()> {
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+  let channel = DatabaseHandler::get_term_of_the_day_channel(&mut transaction, &guild_id).await?;
+  let pins = DatabaseHandler::get_term_of_the_day_pins(&mut transaction, &guild_id).await?;
+  let cycle = DatabaseHandler::get_term_of_the_day_cycle(&mut transaction, &guild_id).await?;
+  let (done, total) =
+    DatabaseHandler::get_term_of_the_day_progress(&mut transaction, &guild_id, cycle).await?;
+  drop(transaction);
+
+  let channel = match channel {
+    Some(channel) => format!(
+      "{} daily from {}:00 UTC",
+      channel.mention(),
+      TERM_OF_THE_DAY_HOUR
+    ),
+    None => "Not posted. Use `/term_of_the_day channel` to choose a channel.".to_string(),
+  };
+
+  let pinned = match pins.is_empty() {
+    true => format!(
+      "None. The next term will be chosen at random from the {} terms left in this cycle.",
+      total - done
+    ),
+    false => pins
+      .iter()
+      .enumerate()
+      .map(|(i, term)| format!("{}. `{}`", i + 1, term.term_name))
+      .collect::<Vec<String>>()
+      .join("\n"),
+  };
+
+  ctx
+    .send(
+      CreateReply::default()
+        .embed(
+          BloomBotEmbed::new()
+            .title("Upcoming Terms of the Day")
+            .field("Channel", channel, false)
+            .field("Pinned", pinned, false)
+            .field(
+              format!("Cycle {}", cycle),
+              format!("{} of {} terms posted or skipped", done, total),
+              false,
+            ),
+        )
+        .ephemeral(true),
+    )
+    .await?;
+
+  Ok(())
+}
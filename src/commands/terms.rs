@@ -1,14 +1,20 @@
-use crate::commands::{commit_and_say, MessageType};
-use crate::database::DatabaseHandler;
+use crate::commands::{commit_and_say, rank_suggestions, MessageType};
+use crate::config::BloomBotEmbed;
+use crate::database::{DatabaseHandler, TermRevision, TermRevisionAction};
+use crate::embeddings::{embed_term, EmbeddingProvider, TermText};
+use crate::jobs::term_reindex::reindex_terms;
+use crate::pagination::{PageRow, PageRowRef, Pagination};
+use crate::term_transfer::{self, ImportPlan, TermRecord};
 use crate::{Context, Data as AppData, Error as AppError};
 use anyhow::Result;
-use pgvector;
-use poise::serenity_prelude as serenity;
+use poise::serenity_prelude::{self as serenity, builder::*, Mentionable};
 use poise::Modal;
+use std::collections::HashMap;
+use std::sync::Arc;
 
 #[derive(Debug, Modal)]
 #[name = "Add a new term"]
-struct AddTermModal {
+pub struct AddTermModal {
   // #[name = "The term to add"]
   // #[placeholder = "For acronyms, use the full name here"]
   // term: String,
@@ -16,15 +22,15 @@ struct AddTermModal {
   #[placeholder = "Include the acronym at the beginning of your definition"]
   #[paragraph]
   #[max_length = 1000]
-  definition: String,
+  pub definition: String,
   #[name = "An example sentence showing the term in use"]
-  example: Option<String>,
+  pub example: Option<String>,
   #[name = "The category of the term"]
-  category: Option<String>,
+  pub category: Option<String>,
   #[name = "Links to further reading, comma separated"]
-  links: Option<String>,
+  pub links: Option<String>,
   #[name = "Term aliases, comma separated"]
-  aliases: Option<String>,
+  pub aliases: Option<String>,
 }
 
 #[derive(Debug, Modal)]
@@ -44,6 +50,32 @@ struct UpdateTermModal {
   aliases: Option<String>,
 }
 
+/// Suggests glossary terms whose name or aliases match what has been typed so far.
+pub async fn autocomplete_term(ctx: Context<'_>, partial: &str) -> Vec<String> {
+  let guild_id = match ctx.guild_id() {
+    Some(guild_id) => guild_id,
+    None => return Vec::new(),
+  };
+
+  let mut transaction = match ctx.data().db.start_transaction_with_retry(5).await {
+    Ok(transaction) => transaction,
+    Err(_) => return Vec::new(),
+  };
+
+  let terms = DatabaseHandler::get_term_list(&mut transaction, &guild_id, None)
+    .await
+    .unwrap_or_default();
+
+  rank_suggestions(partial, terms, |term| {
+    let mut names = vec![term.term_name.clone()];
+    names.extend(term.aliases.clone().unwrap_or_default());
+    names
+  })
+  .into_iter()
+  .map(|term| term.term_name)
+  .collect()
+}
+
 pub async fn term_not_found(
   ctx: Context<'_>,
   transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
@@ -104,7 +136,9 @@ pub async fn term_not_found(
   required_permissions = "MANAGE_ROLES",
   default_member_permissions = "MANAGE_ROLES",
   category = "Moderator Commands",
-  subcommands("add", "remove", "edit"),
+  subcommands(
+    "add", "remove", "edit", "history", "revert", "reindex", "export", "import"
+  ),
   subcommand_required,
   //hide_in_help,
   guild_only
@@ -113,6 +147,80 @@ pub async fn terms(_: poise::Context<'_, AppData, AppError>) -> Result<()> {
   Ok(())
 }
 
+/// Splits a comma-separated field of the add term form into its items.
+fn split_list(list: Option<&str>) -> Vec<String> {
+  match list {
+    Some(list) => list.split(",").map(|s| s.trim().to_string()).collect(),
+    None => Vec::new(),
+  }
+}
+
+/// Embeds a term from the add term form. Without an embeddings provider, the term is added
+/// without an embedding and can't be found with glossary search until it is reindexed.
+///
+/// This can take a while, so it should be called before starting the transaction that adds the
+/// term.
+pub async fn embed_term_data(
+  embeddings: Option<&Arc<dyn EmbeddingProvider>>,
+  term_name: &str,
+  term_data: &AddTermModal,
+  author_id: &serenity::UserId,
+) -> Result<Option<crate::database::TermEmbedding>> {
+  match embeddings {
+    Some(embeddings) => {
+      let aliases = split_list(term_data.aliases.as_deref());
+      let term_text = TermText {
+        term_name,
+        meaning: &term_data.definition,
+        usage: term_data.example.as_deref(),
+        category: term_data.category.as_deref(),
+        aliases: &aliases,
+      };
+      Ok(Some(
+        embed_term(embeddings.as_ref(), &term_text, *author_id).await?,
+      ))
+    }
+    None => Ok(None),
+  }
+}
+
+/// Adds a term from the add term form and records its first revision.
+pub async fn insert_term(
+  transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+  guild_id: &serenity::GuildId,
+  term_name: &str,
+  term_data: AddTermModal,
+  embedding: Option<crate::database::TermEmbedding>,
+  author_id: &serenity::UserId,
+) -> Result<()> {
+  let links = split_list(term_data.links.as_deref());
+  let aliases = split_list(term_data.aliases.as_deref());
+
+  DatabaseHandler::add_term(
+    transaction,
+    term_name,
+    term_data.definition.as_str(),
+    term_data.example.as_deref(),
+    links.as_slice(),
+    term_data.category.as_deref(),
+    aliases.as_slice(),
+    guild_id,
+    embedding,
+  )
+  .await?;
+  DatabaseHandler::add_term_revision(
+    transaction,
+    guild_id,
+    term_name,
+    TermRevisionAction::Add,
+    author_id,
+    None,
+  )
+  .await?;
+
+  Ok(())
+}
+
 /// Add a new term to the glossary
 ///
 /// Adds a new term to the glossary.
@@ -127,39 +235,26 @@ pub async fn add(
 
   match term_data {
     Some(term_data) => {
+      let embedding = embed_term_data(
+        ctx.data().embeddings.as_ref(),
+        &term_name,
+        &term_data,
+        &ctx.author().id,
+      )
+      .await?;
+
       let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
 
       // We unwrap here, because we know that the command is guild-only.
       let guild_id = ctx.guild_id().unwrap();
 
-      let links = match term_data.links {
-        Some(links) => links.split(",").map(|s| s.trim().to_string()).collect(),
-        None => Vec::new(),
-      };
-
-      let aliases = match term_data.aliases {
-        Some(aliases) => aliases.split(",").map(|s| s.trim().to_string()).collect(),
-        None => Vec::new(),
-      };
-
-      let vector = pgvector::Vector::from(
-        ctx
-          .data()
-          .embeddings
-          .create_embedding(term_name.clone(), ctx.author().id)
-          .await?,
-      );
-
-      DatabaseHandler::add_term(
+      insert_term(
         &mut transaction,
-        term_name.as_str(),
-        term_data.definition.as_str(),
-        term_data.example.as_deref(),
-        links.as_slice(),
-        term_data.category.as_deref(),
-        aliases.as_slice(),
         &guild_id,
-        vector,
+        &term_name,
+        term_data,
+        embedding,
+        &ctx.author().id,
       )
       .await?;
 
@@ -192,7 +287,9 @@ pub async fn add(
 #[poise::command(slash_command)]
 pub async fn edit(
   ctx: poise::ApplicationContext<'_, AppData, AppError>,
-  #[description = "The term to edit"] term_name: String,
+  #[description = "The term to edit"]
+  #[autocomplete = "autocomplete_term"]
+  term_name: String,
 ) -> Result<()> {
   let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
 
@@ -223,8 +320,6 @@ pub async fn edit(
     None => None,
   };
 
-  let existing_definition = existing_term.meaning.clone();
-
   let defaults = UpdateTermModal {
     definition: existing_term.meaning,
     example: existing_term.usage,
@@ -244,18 +339,6 @@ pub async fn edit(
         None => Vec::new(),
       };
 
-      let vector = if term_data.definition != existing_definition {
-        Some(pgvector::Vector::from(
-          ctx
-            .data()
-            .embeddings
-            .create_embedding(existing_term.term_name, ctx.author().id)
-            .await?,
-        ))
-      } else {
-        None
-      };
-
       let aliases = match term_data.aliases {
         Some(aliases) => aliases.split(",").map(|s| s.trim().to_string()).collect(),
         None => Vec::new(),
@@ -269,7 +352,30 @@ pub async fn edit(
         links.as_slice(),
         term_data.category.as_deref(),
         aliases.as_slice(),
-        vector,
+      )
+      .await?;
+
+      // Every field goes into the embedding, so any edit can change it. Without an embeddings
+      // provider, the old embedding is kept until the term is reindexed.
+      if let Some(embeddings) = &ctx.data().embeddings {
+        let term_text = TermText {
+          term_name: &existing_term.term_name,
+          meaning: &term_data.definition,
+          usage: term_data.example.as_deref(),
+          category: term_data.category.as_deref(),
+          aliases: &aliases,
+        };
+        let embedding = embed_term(embeddings.as_ref(), &term_text, ctx.author().id).await?;
+        DatabaseHandler::set_term_embedding(&mut transaction, &existing_term.id, embedding).await?;
+      }
+
+      DatabaseHandler::add_term_revision(
+        &mut transaction,
+        &guild_id,
+        &existing_term.term_name,
+        TermRevisionAction::Edit,
+        &ctx.author().id,
+        None,
       )
       .await?;
 
@@ -302,7 +408,9 @@ pub async fn edit(
 #[poise::command(slash_command)]
 pub async fn remove(
   ctx: Context<'_>,
-  #[description = "The term to remove"] term: String,
+  #[description = "The term to remove"]
+  #[autocomplete = "autocomplete_term"]
+  term: String,
 ) -> Result<()> {
   let data = ctx.data();
 
@@ -321,6 +429,16 @@ pub async fn remove(
     return Ok(());
   }
 
+  // The revision keeps the term as it was, so it can be restored with `/terms revert`
+  DatabaseHandler::add_term_revision(
+    &mut transaction,
+    &guild_id,
+    &term,
+    TermRevisionAction::Remove,
+    &ctx.author().id,
+    None,
+  )
+  .await?;
   DatabaseHandler::remove_term(&mut transaction, term.as_str(), &guild_id).await?;
 
   commit_and_say(
@@ -333,3 +451,699 @@ pub async fn remove(
 
   Ok(())
 }
+
+/// A revision shown in `/terms history`, with the changes from the revision before it.
+struct RevisionRow {
+  title: String,
+  body: String,
+}
+
+impl PageRow for RevisionRow {
+  fn title(&self) -> String {
+    self.title.clone()
+  }
+
+  fn body(&self) -> String {
+    self.body.clone()
+  }
+}
+
+/// Marks the words removed from `old` with strikethrough and the words added in `new` in bold,
+/// using the longest common subsequence of words.
+fn diff_words(old: &str, new: &str) -> String {
+  let old: Vec<&str> = old.split_whitespace().collect();
+  let new: Vec<&str> = new.split_whitespace().collect();
+
+  // lengths[i][j] is the length of the longest common subsequence of old[i..] and new[j..]
+  let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+  for i in (0..old.len()).rev() {
+    for j in (0..new.len()).rev() {
+      lengths[i][j] = match old[i] == new[j] {
+        true => lengths[i + 1][j + 1] + 1,
+        false => lengths[i + 1][j].max(lengths[i][j + 1]),
+      };
+    }
+  }
+
+  let mut words = Vec::new();
+  let (mut i, mut j) = (0, 0);
+  while i < old.len() || j < new.len() {
+    if i < old.len() && j < new.len() && old[i] == new[j] {
+      words.push(old[i].to_string());
+      i += 1;
+      j += 1;
+    } else if i < old.len() && (j == new.len() || lengths[i + 1][j] >= lengths[i][j + 1]) {
+      words.push(format!("~~{}~~", old[i]));
+      i += 1;
+    } else {
+      words.push(format!("**{}**", new[j]));
+      j += 1;
+    }
+  }
+
+  words.join(" ")
+}
+
+/// Describes what a revision changed compared with the revision before it.
+fn describe_changes(previous: Option<&TermRevision>, revision: &TermRevision) -> String {
+  let previous = match (previous, revision.action) {
+    (_, TermRevisionAction::Remove) => return "Term removed.".to_string(),
+    (Some(previous), _) if previous.action != TermRevisionAction::Remove => previous,
+    // A new or restored term has nothing to compare with
+    _ => return format!("**Meaning**: {}", revision.meaning),
+  };
+
+  let fields = [
+    (
+      "Meaning",
+      previous.meaning.clone(),
+      revision.meaning.clone(),
+    ),
+    (
+      "Example",
+      previous.usage.clone().unwrap_or_default(),
+      revision.usage.clone().unwrap_or_default(),
+    ),
+    (
+      "Category",
+      previous.category.clone().unwrap_or_default(),
+      revision.category.clone().unwrap_or_default(),
+    ),
+    (
+      "Links",
+      previous.links.join(", "),
+      revision.links.join(", "),
+    ),
+    (
+      "Aliases",
+      previous.aliases.join(", "),
+      revision.aliases.join(", "),
+    ),
+  ];
+
+  let changes: Vec<String> = fields
+    .iter()
+    .filter(|(_, old, new)| old != new)
+    .map(|(field, old, new)| format!("**{}**: {}", field, diff_words(old, new)))
+    .collect();
+
+  match changes.is_empty() {
+    true => "No changes.".to_string(),
+    false => changes.join("\n"),
+  }
+}
+
+/// Show the revision history of a term
+///
+/// Shows every change made to a term, newest first, with the words removed and added by each edit.
+#[poise::command(slash_command)]
+pub async fn history(
+  ctx: Context<'_>,
+  #[description = "The term to show history for"]
+  #[autocomplete = "autocomplete_term"]
+  term: String,
+  #[description = "The page to show"] page: Option<usize>,
+) -> Result<()> {
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+
+  // Look up aliases, but still allow the name of a removed term
+  let term_name = match DatabaseHandler::get_term(&mut transaction, &guild_id, &term).await? {
+    Some(existing_term) => existing_term.term_name,
+    None => term,
+  };
+  let revisions =
+    DatabaseHandler::get_term_revisions(&mut transaction, &guild_id, &term_name).await?;
+  drop(transaction);
+
+  if revisions.is_empty() {
+    ctx
+      .send(
+        poise::CreateReply::default()
+          .content(":x: No history found for that term.")
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let mut rows: Vec<RevisionRow> = revisions
+    .iter()
+    .enumerate()
+    .map(|(index, revision)| {
+      let previous = match index {
+        0 => None,
+        _ => revisions.get(index - 1),
+      };
+      let action = match (revision.action, revision.reverted_to) {
+        (TermRevisionAction::Add, _) => "Added".to_string(),
+        (TermRevisionAction::Edit, _) => "Edited".to_string(),
+        (TermRevisionAction::Remove, _) => "Removed".to_string(),
+        (TermRevisionAction::Revert, Some(reverted_to)) => format!("Reverted to #{}", reverted_to),
+        (TermRevisionAction::Revert, None) => "Reverted".to_string(),
+      };
+      let editor = match revision.editor_id {
+        Some(editor_id) => format!(" by {}", editor_id.mention()),
+        None => String::new(),
+      };
+      let changes = describe_changes(previous, revision);
+      // Field values are limited to 1024 characters
+      let changes = match changes.chars().count() > 900 {
+        true => format!("{}...", changes.chars().take(897).collect::<String>()),
+        false => changes,
+      };
+
+      RevisionRow {
+        title: format!("Revision #{} — {}", revision.revision_number, action),
+        body: format!(
+          "<t:{}:f>{}\n{}",
+          revision.created_at.timestamp(),
+          editor,
+          changes
+        ),
+      }
+    })
+    .collect();
+  rows.reverse();
+
+  // Define some unique identifiers for the navigation buttons
+  let ctx_id = ctx.id();
+  let prev_button_id = format!("{}prev", ctx_id);
+  let next_button_id = format!("{}next", ctx_id);
+
+  let mut current_page = page.unwrap_or(0).saturating_sub(1);
+
+  let rows: Vec<PageRowRef> = rows.iter().map(|row| row as _).collect();
+  let pagination = Pagination::new(format!("History for {}", term_name), rows).await?;
+
+  if pagination.get_page(current_page).is_none() {
+    current_page = pagination.get_last_page_number();
+  }
+
+  let first_page = pagination.create_page_embed(current_page);
+
+  ctx
+    .send({
+      let mut f = poise::CreateReply::default();
+      if pagination.get_page_count() > 1 {
+        f = f.components(vec![CreateActionRow::Buttons(vec![
+          CreateButton::new(&prev_button_id).label("Previous"),
+          CreateButton::new(&next_button_id).label("Next"),
+        ])])
+      }
+      f.embeds = vec![first_page];
+      f.ephemeral(true)
+    })
+    .await?;
+
+  // Loop through incoming interactions with the navigation buttons
+  while let Some(press) = serenity::ComponentInteractionCollector::new(ctx)
+    // We defined our button IDs to start with `ctx_id`. If they don't, some other command's
+    // button was pressed
+    .filter(move |press| press.data.custom_id.starts_with(&ctx_id.to_string()))
+    // Timeout when no navigation button has been pressed for 24 hours
+    .timeout(std::time::Duration::from_secs(3600 * 24))
+    .await
+  {
+    // Depending on which button was pressed, go to next or previous page
+    if press.data.custom_id == next_button_id {
+      current_page = pagination.update_page_number(current_page, 1);
+    } else if press.data.custom_id == prev_button_id {
+      current_page = pagination.update_page_number(current_page, -1);
+    } else {
+      // This is an unrelated button interaction
+      continue;
+    }
+
+    // Update the message with the new page contents
+    press
+      .create_response(
+        ctx,
+        CreateInteractionResponse::UpdateMessage(
+          CreateInteractionResponseMessage::new().embed(pagination.create_page_embed(current_page)),
+        ),
+      )
+      .await?;
+  }
+
+  Ok(())
+}
+
+/// Revert a term to an earlier revision
+///
+/// Restores a term to how it was at an earlier revision and recomputes its embedding. Reverting a removed term adds it back.
+#[poise::command(slash_command)]
+pub async fn revert(
+  ctx: Context<'_>,
+  #[description = "The term to revert"]
+  #[autocomplete = "autocomplete_term"]
+  term: String,
+  #[description = "The revision to revert to, from /terms history"]
+  #[min = 1]
+  revision: i32,
+) -> Result<()> {
+  ctx.defer_ephemeral().await?;
+
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+
+  let existing_term = DatabaseHandler::get_term(&mut transaction, &guild_id, &term).await?;
+  let term_name = match &existing_term {
+    Some(existing_term) => existing_term.term_name.clone(),
+    None => term,
+  };
+
+  let revisions =
+    DatabaseHandler::get_term_revisions(&mut transaction, &guild_id, &term_name).await?;
+  let target = match revisions
+    .into_iter()
+    .find(|target| target.revision_number == revision)
+  {
+    Some(target) => target,
+    None => {
+      ctx
+        .send(
+          poise::CreateReply::default()
+            .content(format!(
+              ":x: Revision #{} does not exist for that term.",
+              revision
+            ))
+            .ephemeral(true),
+        )
+        .await?;
+      return Ok(());
+    }
+  };
+
+  let embedding = match &data.embeddings {
+    Some(embeddings) => {
+      let term_text = TermText {
+        term_name: &target.term_name,
+        meaning: &target.meaning,
+        usage: target.usage.as_deref(),
+        category: target.category.as_deref(),
+        aliases: &target.aliases,
+      };
+      Some(embed_term(embeddings.as_ref(), &term_text, ctx.author().id).await?)
+    }
+    None => None,
+  };
+
+  match &existing_term {
+    Some(existing_term) => {
+      DatabaseHandler::edit_term(
+        &mut transaction,
+        &existing_term.id,
+        &target.meaning,
+        target.usage.as_deref(),
+        &target.links,
+        target.category.as_deref(),
+        &target.aliases,
+      )
+      .await?;
+      // Without an embeddings provider, the stale embedding is replaced when the term is
+      // reindexed
+      if let Some(embedding) = embedding {
+        DatabaseHandler::set_term_embedding(&mut transaction, &existing_term.id, embedding).await?;
+      }
+    }
+    None => {
+      DatabaseHandler::add_term(
+        &mut transaction,
+        &target.term_name,
+        &target.meaning,
+        target.usage.as_deref(),
+        &target.links,
+        target.category.as_deref(),
+        &target.aliases,
+        &guild_id,
+        embedding,
+      )
+      .await?;
+    }
+  }
+
+  DatabaseHandler::add_term_revision(
+    &mut transaction,
+    &guild_id,
+    &target.term_name,
+    TermRevisionAction::Revert,
+    &ctx.author().id,
+    Some(target.revision_number),
+  )
+  .await?;
+
+  commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      ":white_check_mark: `{}` has been reverted to revision #{}.",
+      target.term_name, target.revision_number
+    )),
+    true,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Recompute glossary embeddings
+///
+/// Recomputes the embeddings used by glossary search for terms that are missing one or were embedded with a different model or template.
+#[poise::command(slash_command)]
+pub async fn reindex(
+  ctx: Context<'_>,
+  #[description = "Re-embed every term, even if its embedding is current (defaults to false)"]
+  force: Option<bool>,
+) -> Result<()> {
+  let data = ctx.data();
+
+  let embeddings = match &data.embeddings {
+    Some(embeddings) => embeddings,
+    None => {
+      ctx
+        .send(
+          poise::CreateReply::default()
+            .content(":x: No embeddings provider is configured.")
+            .ephemeral(true),
+        )
+        .await?;
+      return Ok(());
+    }
+  };
+
+  ctx.defer_ephemeral().await?;
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+
+  let summary = reindex_terms(
+    &data.db,
+    embeddings.as_ref(),
+    Some(&guild_id),
+    force.unwrap_or(false),
+    ctx.author().id,
+  )
+  .await?;
+
+  ctx
+    .send(
+      poise::CreateReply::default()
+        .content(format!(
+          ":white_check_mark: Checked {} terms and re-embedded {} with `{}`. {} failed.",
+          summary.checked,
+          summary.updated,
+          embeddings.model(),
+          summary.failed
+        ))
+        .ephemeral(true),
+    )
+    .await?;
+
+  Ok(())
+}
+
+#[derive(poise::ChoiceParameter)]
+pub enum ExportFormat {
+  #[name = "JSON"]
+  Json,
+  #[name = "Markdown"]
+  Markdown,
+}
+
+/// Export the glossary
+///
+/// Exports every term in the glossary as a JSON file, which can be edited and imported with `/terms import`, or as a Markdown document for reviewing.
+#[poise::command(slash_command)]
+pub async fn export(
+  ctx: Context<'_>,
+  #[description = "The format of the export (defaults to JSON)"] format: Option<ExportFormat>,
+) -> Result<()> {
+  let data = ctx.data();
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+  let terms = DatabaseHandler::get_terms_for_export(&mut transaction, &guild_id).await?;
+  drop(transaction);
+
+  let attachment = match format.unwrap_or(ExportFormat::Json) {
+    ExportFormat::Json => CreateAttachment::bytes(
+      term_transfer::to_json(&terms)?.into_bytes(),
+      "glossary.json",
+    ),
+    ExportFormat::Markdown => {
+      let guild_name = guild_id.name(ctx).unwrap_or_else(|| "Server".to_string());
+      CreateAttachment::bytes(
+        term_transfer::to_markdown(&guild_name, &terms).into_bytes(),
+        "glossary.md",
+      )
+    }
+  };
+
+  ctx
+    .send(
+      poise::CreateReply::default()
+        .content(format!("Exported {} terms.", terms.len()))
+        .attachment(attachment)
+        .ephemeral(true),
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// Lists terms for an import summary, leaving out the rest if they don't fit in a field.
+fn list_terms(names: impl ExactSizeIterator<Item = String>) -> String {
+  let total = names.len();
+  let mut list = String::new();
+
+  for (count, name) in names.enumerate() {
+    let line = format!("{}\n", name);
+    // Leave room for the note about the names that were left out
+    if list.len() + line.len() > 1000 {
+      list.push_str(&format!("...and {} more", total - count));
+      break;
+    }
+    list.push_str(&line);
+  }
+
+  match list.is_empty() {
+    true => "None".to_string(),
+    false => list,
+  }
+}
+
+fn import_summary(plan: &ImportPlan, dry_run: bool) -> CreateEmbed {
+  let title = match dry_run {
+    true => "Import Preview (dry run, nothing was changed)",
+    false => "Import Complete",
+  };
+
+  BloomBotEmbed::new()
+    .title(title)
+    .field(
+      format!("New ({})", plan.new.len()),
+      list_terms(
+        plan
+          .new
+          .iter()
+          .map(|record| format!("`{}`", record.term_name)),
+      ),
+      false,
+    )
+    .field(
+      format!("Changed ({})", plan.changed.len()),
+      list_terms(plan.changed.iter().map(|changed| {
+        format!(
+          "`{}` ({})",
+          changed.record.term_name,
+          changed.fields.join(", ")
+        )
+      })),
+      false,
+    )
+    .field(
+      format!("Unchanged ({})", plan.unchanged.len()),
+      list_terms(plan.unchanged.iter().map(|name| format!("`{}`", name))),
+      false,
+    )
+}
+
+async fn embed_record(
+  embeddings: Option<&Arc<dyn EmbeddingProvider>>,
+  record: &TermRecord,
+  author_id: serenity::UserId,
+) -> Result<Option<crate::database::TermEmbedding>> {
+  match embeddings {
+    Some(embeddings) => {
+      let term_text = TermText {
+        term_name: &record.term_name,
+        meaning: &record.meaning,
+        usage: record.usage.as_deref(),
+        category: record.category.as_deref(),
+        aliases: &record.aliases,
+      };
+      Ok(Some(
+        embed_term(embeddings.as_ref(), &term_text, author_id).await?,
+      ))
+    }
+    None => Ok(None),
+  }
+}
+
+/// Import terms into the glossary
+///
+/// Adds and updates terms from a JSON file exported with `/terms export`. Terms are matched by name, and terms missing from the file are kept. Run with `dry_run` first to see what would change.
+#[poise::command(slash_command)]
+pub async fn import(
+  ctx: Context<'_>,
+  #[description = "A JSON file in the format of /terms export"] file: serenity::Attachment,
+  #[description = "Only show what would change, without changing anything (defaults to true)"]
+  dry_run: Option<bool>,
+) -> Result<()> {
+  let data = ctx.data();
+  let dry_run = dry_run.unwrap_or(true);
+
+  // We unwrap here, because we know that the command is guild-only.
+  let guild_id = ctx.guild_id().unwrap();
+
+  if file.size > 1_000_000 {
+    ctx
+      .send(
+        poise::CreateReply::default()
+          .content(":x: The file is too large. Imports are limited to 1 MB.")
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  ctx.defer_ephemeral().await?;
+
+  let contents = file.download().await?;
+  let records = match term_transfer::parse_json(&contents) {
+    Ok(records) => records,
+    Err(err) => {
+      ctx
+        .send(
+          poise::CreateReply::default()
+            .content(format!(":x: Could not read the file: {}", err))
+            .ephemeral(true),
+        )
+        .await?;
+      return Ok(());
+    }
+  };
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+  let existing = DatabaseHandler::get_terms_for_export(&mut transaction, &guild_id).await?;
+  drop(transaction);
+  let plan = term_transfer::plan_import(&existing, records.clone());
+
+  if dry_run {
+    ctx
+      .send(
+        poise::CreateReply::default()
+          .embed(import_summary(&plan, true))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let author_id = ctx.author().id;
+
+  // Creating embeddings can take a while, so create them all before starting the transaction
+  let mut embeddings = HashMap::new();
+  for record in plan
+    .new
+    .iter()
+    .chain(plan.changed.iter().map(|changed| &changed.record))
+  {
+    let embedding = embed_record(data.embeddings.as_ref(), record, author_id).await?;
+    embeddings.insert(record.term_name.to_lowercase(), embedding);
+  }
+
+  // Plan again in case the glossary changed while the embeddings were created. Terms without an
+  // embedding get one when the terms are reindexed.
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+  let existing = DatabaseHandler::get_terms_for_export(&mut transaction, &guild_id).await?;
+  let plan = term_transfer::plan_import(&existing, records);
+
+  for record in &plan.new {
+    let embedding = embeddings
+      .remove(&record.term_name.to_lowercase())
+      .flatten();
+    DatabaseHandler::add_term(
+      &mut transaction,
+      &record.term_name,
+      &record.meaning,
+      record.usage.as_deref(),
+      &record.links,
+      record.category.as_deref(),
+      &record.aliases,
+      &guild_id,
+      embedding,
+    )
+    .await?;
+    DatabaseHandler::add_term_revision(
+      &mut transaction,
+      &guild_id,
+      &record.term_name,
+      TermRevisionAction::Add,
+      &author_id,
+      None,
+    )
+    .await?;
+  }
+
+  for changed in &plan.changed {
+    let record = &changed.record;
+    DatabaseHandler::edit_term(
+      &mut transaction,
+      &changed.id,
+      &record.meaning,
+      record.usage.as_deref(),
+      &record.links,
+      record.category.as_deref(),
+      &record.aliases,
+    )
+    .await?;
+    // Without a new embedding, the stale one is replaced when the term is reindexed
+    if let Some(embedding) = embeddings
+      .remove(&record.term_name.to_lowercase())
+      .flatten()
+    {
+      DatabaseHandler::set_term_embedding(&mut transaction, &changed.id, embedding).await?;
+    }
+    DatabaseHandler::add_term_revision(
+      &mut transaction,
+      &guild_id,
+      &record.term_name,
+      TermRevisionAction::Edit,
+      &author_id,
+      None,
+    )
+    .await?;
+  }
+
+  commit_and_say(
+    ctx,
+    transaction,
+    MessageType::EmbedOnly(import_summary(&plan, false)),
+    true,
+  )
+  .await?;
+
+  Ok(())
+}
@@ -1,5 +1,8 @@
+use crate::commands::terms::autocomplete_term;
 use crate::commands::BloomBotEmbed;
 use crate::database::DatabaseHandler;
+use crate::embeddings::embed_search;
+use crate::term_search::MIN_ANSWER_SIMILARITY;
 use crate::Context;
 use anyhow::Result;
 use poise::serenity_prelude::CreateEmbedFooter;
@@ -10,7 +13,9 @@ use poise::serenity_prelude::CreateEmbedFooter;
 #[poise::command(slash_command, category = "Informational", guild_only)]
 pub async fn whatis(
   ctx: Context<'_>,
-  #[description = "The term to show information about"] term: String,
+  #[description = "The term to show information about"]
+  #[autocomplete = "autocomplete_term"]
+  term: String,
 ) -> Result<()> {
   let guild_id = ctx.guild_id().unwrap();
 
@@ -35,11 +40,23 @@ pub async fn whatis(
       };
     }
     None => {
+      let data = ctx.data();
+      let search_vector = embed_search(
+        &data.db,
+        &data.query_embeddings,
+        data.embeddings.as_ref(),
+        &term,
+        ctx.author().id,
+      )
+      .await;
       let possible_terms =
-        DatabaseHandler::get_possible_terms(&mut transaction, &guild_id, &term.as_str(), 0.7)
+        DatabaseHandler::search_terms(&mut transaction, &guild_id, &term, search_vector, None, 3)
           .await?;
 
-      if possible_terms.len() == 1 {
+      // Only answer with a close match, so that a weak one is offered as a suggestion instead
+      if possible_terms.len() == 1
+        && possible_terms[0].score.best_trigram_similarity() >= MIN_ANSWER_SIMILARITY
+      {
         let possible_term = possible_terms.first().unwrap();
 
         embed = embed.title(&possible_term.term_name);
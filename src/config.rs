@@ -3,6 +3,38 @@ use poise::serenity_prelude::{self as serenity, Guild, Member, RoleId, Embed};
 pub const EMBED_COLOR: u32 = 0xFDAC2E;
 pub const TERMS_PER_PAGE: usize = 10;
 pub const MIN_STARS: u64 = 5;
+/// How often, in seconds, live team challenge standings are refreshed.
+pub const TEAM_STANDINGS_INTERVAL: u64 = 600;
+/// How often, in seconds, due buddy digests are checked for and sent.
+pub const BUDDY_DIGEST_INTERVAL: u64 = 3600;
+/// How often, in seconds, due weekly recaps are checked for and sent.
+pub const WEEKLY_RECAP_INTERVAL: u64 = 900;
+/// How often, in seconds, glossary terms with outdated embeddings are re-embedded.
+pub const TERM_REINDEX_INTERVAL: u64 = 86400;
+/// How often, in seconds, guilds are checked for a term of the day that is due.
+pub const TERM_OF_THE_DAY_INTERVAL: u64 = 900;
+/// The hour of the day, in UTC, from which the term of the day is posted.
+pub const TERM_OF_THE_DAY_HOUR: u32 = 14;
+/// The text embedded for each glossary term. `{term}`, `{meaning}`, `{aliases}`, `{usage}` and
+/// `{category}` are replaced with the term's fields, and lines whose placeholders are all empty
+/// are left out. Changing the template causes every term to be re-embedded.
+pub const TERM_EMBEDDING_TEMPLATE: &str =
+  "{term}\n{meaning}\nAlso known as: {aliases}\nExample: {usage}";
+/// Number of results `/glossary search` shows when no count is given.
+pub const GLOSSARY_SEARCH_RESULTS: usize = 3;
+/// Number of candidates taken from each ranking before hybrid glossary search fuses them.
+pub const GLOSSARY_SEARCH_CANDIDATES: i64 = 20;
+/// Number of related terms `/glossary info` links to. Buttons for more than five would need a
+/// second row.
+pub const GLOSSARY_RELATED_TERMS: usize = 5;
+/// Number of search query embeddings kept in memory in front of the database cache.
+pub const QUERY_EMBEDDING_CACHE_SIZE: usize = 500;
+/// Number of reporters at which an existing case pings staff again.
+pub const REPORT_PING_THRESHOLD: i64 = 3;
+/// Maximum number of reports a member can make within [`REPORT_RATE_LIMIT_WINDOW`].
+pub const REPORT_RATE_LIMIT: i64 = 5;
+/// Length, in minutes, of the window used for report rate limiting.
+pub const REPORT_RATE_LIMIT_WINDOW: i64 = 60;
 
 /// Sensible defaults for use within our application.
 pub struct BloomBotEmbed {}
@@ -44,6 +76,8 @@ pub struct Channels {
   pub reportchannel: u64,
   pub donators: u64,
   pub suggestion: u64,
+  pub privatenotifications: u64,
+  pub appealchannel: u64,
 }
 
 pub const CHANNELS: Channels = Channels {
@@ -55,6 +89,8 @@ pub const CHANNELS: Channels = Channels {
   reportchannel: 855894610001395743,
   donators: 551895169532952578,
   suggestion: 553676378621476887,
+  privatenotifications: 501464482996944909,
+  appealchannel: 855894610001395743,
 };
 
 pub struct Emotes<'a> {
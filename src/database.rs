@@ -1,9 +1,12 @@
 use crate::pagination::PageRow;
+use crate::term_search::{self, Candidate, HybridScore, Signal};
 use anyhow::{Context, Result};
 use chrono::Utc;
 use futures::{stream::Stream, StreamExt, TryStreamExt};
 use log::{info, warn};
 use poise::serenity_prelude::{self as serenity, Mentionable};
+use poise::ChoiceParameter;
+use std::collections::HashMap;
 use ulid::Ulid;
 
 #[derive(Debug)]
@@ -13,11 +16,39 @@ struct Res {
   meditation_count: Option<i64>,
 }
 
+#[derive(Debug)]
+struct ChallengeRow {
+  record_id: String,
+  challenge_name: String,
+  start_date: chrono::NaiveDate,
+  end_date: chrono::NaiveDate,
+  goal_type: String,
+  goal_target: i32,
+  reward_role: Option<String>,
+}
+
+impl From<ChallengeRow> for ChallengeData {
+  fn from(row: ChallengeRow) -> Self {
+    ChallengeData {
+      id: row.record_id,
+      challenge_name: row.challenge_name,
+      start_date: row.start_date,
+      end_date: row.end_date,
+      goal_type: ChallengeGoal::from_str(&row.goal_type).unwrap_or(ChallengeGoal::Minutes),
+      goal_target: row.goal_target,
+      reward_role: row
+        .reward_role
+        .map(|role| serenity::RoleId::new(role.parse::<u64>().unwrap())),
+    }
+  }
+}
+
 #[derive(Debug)]
 struct MeditationCountByDay {
   days_ago: Option<f64>,
 }
 
+#[derive(Clone)]
 pub struct DatabaseHandler {
   pool: sqlx::PgPool,
 }
@@ -31,6 +62,7 @@ pub struct TrackingProfile {
   pub streaks_active: bool,
   pub streaks_private: bool,
   pub stats_private: bool,
+  pub weekly_recap: bool,
 }
 
 //Default values for tracking customization
@@ -44,6 +76,7 @@ impl Default for TrackingProfile {
           streaks_active: true,
           streaks_private: false,
           stats_private: false,
+          weekly_recap: false,
       }
   }
 }
@@ -69,6 +102,225 @@ pub enum Timeframe {
   Daily,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum ChallengeGoal {
+  #[name = "Minutes"]
+  Minutes,
+  #[name = "Sessions"]
+  Sessions,
+  #[name = "Days practiced"]
+  Days,
+}
+
+impl ChallengeGoal {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      ChallengeGoal::Minutes => "minutes",
+      ChallengeGoal::Sessions => "sessions",
+      ChallengeGoal::Days => "days",
+    }
+  }
+
+  pub fn from_str(goal_type: &str) -> Option<ChallengeGoal> {
+    match goal_type {
+      "minutes" => Some(ChallengeGoal::Minutes),
+      "sessions" => Some(ChallengeGoal::Sessions),
+      "days" => Some(ChallengeGoal::Days),
+      _ => None,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum BuddyNotifications {
+  #[name = "Off"]
+  Off,
+  #[name = "Nudge me when they meditate"]
+  Nudge,
+  #[name = "Daily digest"]
+  Digest,
+}
+
+impl BuddyNotifications {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      BuddyNotifications::Off => "off",
+      BuddyNotifications::Nudge => "nudge",
+      BuddyNotifications::Digest => "digest",
+    }
+  }
+
+  pub fn from_str(notifications: &str) -> Option<BuddyNotifications> {
+    match notifications {
+      "off" => Some(BuddyNotifications::Off),
+      "nudge" => Some(BuddyNotifications::Nudge),
+      "digest" => Some(BuddyNotifications::Digest),
+      _ => None,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum ReportStatus {
+  #[name = "Open"]
+  Open,
+  #[name = "Claimed"]
+  Claimed,
+  #[name = "Resolved"]
+  Resolved,
+  #[name = "Dismissed"]
+  Dismissed,
+}
+
+impl ReportStatus {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      ReportStatus::Open => "open",
+      ReportStatus::Claimed => "claimed",
+      ReportStatus::Resolved => "resolved",
+      ReportStatus::Dismissed => "dismissed",
+    }
+  }
+
+  pub fn from_str(status: &str) -> Option<ReportStatus> {
+    match status {
+      "open" => Some(ReportStatus::Open),
+      "claimed" => Some(ReportStatus::Claimed),
+      "resolved" => Some(ReportStatus::Resolved),
+      "dismissed" => Some(ReportStatus::Dismissed),
+      _ => None,
+    }
+  }
+
+  /// Whether the report still needs attention from staff.
+  pub fn is_active(&self) -> bool {
+    matches!(self, ReportStatus::Open | ReportStatus::Claimed)
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum ModAction {
+  #[name = "Warning"]
+  Warning,
+  #[name = "Note"]
+  Note,
+  #[name = "Timeout"]
+  Timeout,
+  #[name = "Erase"]
+  Erase,
+}
+
+impl ModAction {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      ModAction::Warning => "warning",
+      ModAction::Note => "note",
+      ModAction::Timeout => "timeout",
+      ModAction::Erase => "erase",
+    }
+  }
+
+  pub fn from_str(action: &str) -> Option<ModAction> {
+    match action {
+      "warning" => Some(ModAction::Warning),
+      "note" => Some(ModAction::Note),
+      "timeout" => Some(ModAction::Timeout),
+      "erase" => Some(ModAction::Erase),
+      _ => None,
+    }
+  }
+}
+
+/// How a member was told about a moderation action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationMethod {
+  DirectMessage,
+  PrivateThread,
+  Failed,
+}
+
+impl NotificationMethod {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      NotificationMethod::DirectMessage => "dm",
+      NotificationMethod::PrivateThread => "thread",
+      NotificationMethod::Failed => "failed",
+    }
+  }
+
+  pub fn from_str(method: &str) -> Option<NotificationMethod> {
+    match method {
+      "dm" => Some(NotificationMethod::DirectMessage),
+      "thread" => Some(NotificationMethod::PrivateThread),
+      "failed" => Some(NotificationMethod::Failed),
+      _ => None,
+    }
+  }
+
+  pub fn description(&self) -> &'static str {
+    match self {
+      NotificationMethod::DirectMessage => "Notified via DM",
+      NotificationMethod::PrivateThread => "Notified via private thread",
+      NotificationMethod::Failed => "Could not be notified",
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum AppealStatus {
+  #[name = "Pending"]
+  Pending,
+  #[name = "Accepted"]
+  Accepted,
+  #[name = "Rejected"]
+  Rejected,
+}
+
+impl AppealStatus {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      AppealStatus::Pending => "pending",
+      AppealStatus::Accepted => "accepted",
+      AppealStatus::Rejected => "rejected",
+    }
+  }
+
+  pub fn from_str(status: &str) -> Option<AppealStatus> {
+    match status {
+      "pending" => Some(AppealStatus::Pending),
+      "accepted" => Some(AppealStatus::Accepted),
+      "rejected" => Some(AppealStatus::Rejected),
+      _ => None,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuggestionStatus {
+  Pending,
+  Approved,
+  Rejected,
+}
+
+impl SuggestionStatus {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      SuggestionStatus::Pending => "pending",
+      SuggestionStatus::Approved => "approved",
+      SuggestionStatus::Rejected => "rejected",
+    }
+  }
+
+  pub fn from_str(status: &str) -> Option<SuggestionStatus> {
+    match status {
+      "pending" => Some(SuggestionStatus::Pending),
+      "approved" => Some(SuggestionStatus::Approved),
+      "rejected" => Some(SuggestionStatus::Rejected),
+      _ => None,
+    }
+  }
+}
+
 #[derive(Debug)]
 pub struct TimeframeStats {
   pub sum: Option<i64>,
@@ -79,6 +331,9 @@ pub struct EraseData {
   pub id: String,
   pub user_id: serenity::UserId,
   pub message_link: String,
+  pub reason: Option<String>,
+  pub notification_method: Option<NotificationMethod>,
+  pub appeal_status: Option<AppealStatus>,
   pub occurred_at: chrono::DateTime<Utc>,
 }
 
@@ -92,12 +347,137 @@ impl PageRow for EraseData {
   }
 
   fn body(&self) -> String {
-    if self.message_link == "None" {
+    let mut body = if self.message_link == "None" {
       "Notification not available".to_string()
     } else {
       format!("[Go to erase notification]({})", self.message_link)
+    };
+
+    if let Some(reason) = &self.reason {
+      body = format!("Reason: {}\n{}", reason, body);
+    }
+
+    if let Some(method) = self.notification_method {
+      body = format!("{}\n{}", body, method.description());
+    }
+
+    match self.appeal_status {
+      Some(status) => format!("{}\nAppeal: {}", body, status.name()),
+      None => body,
+    }
+  }
+}
+
+/// An entry in the moderation log. Erases recorded before the log existed have no moderator or
+/// reason, and those without a date use the epoch.
+pub struct ModCase {
+  pub id: String,
+  pub guild_id: serenity::GuildId,
+  pub case_number: i32,
+  pub user_id: serenity::UserId,
+  pub moderator_id: Option<serenity::UserId>,
+  pub action: ModAction,
+  pub reason: Option<String>,
+  pub evidence_link: Option<String>,
+  pub notification_method: Option<NotificationMethod>,
+  pub created_at: chrono::DateTime<Utc>,
+}
+
+impl PageRow for ModCase {
+  fn title(&self) -> String {
+    format!("Case #{} — {}", self.case_number, self.action.name())
+  }
+
+  fn body(&self) -> String {
+    let date = match self.created_at == chrono::DateTime::<Utc>::default() {
+      true => "Date not available".to_string(),
+      false => format!("<t:{}:f>", self.created_at.timestamp()),
+    };
+    let moderator = match self.moderator_id {
+      Some(moderator_id) => format!(" by {}", moderator_id.mention()),
+      None => String::new(),
+    };
+    let reason = match &self.reason {
+      Some(reason) => format!("\nReason: {}", reason),
+      None => String::new(),
+    };
+    let evidence = match &self.evidence_link {
+      Some(evidence_link) => format!("\n[Evidence]({})", evidence_link),
+      None => String::new(),
+    };
+    let notification = match self.notification_method {
+      Some(method) => format!("\n{}", method.description()),
+      None => String::new(),
+    };
+
+    format!(
+      "{}{}{}{}{}",
+      date, moderator, reason, evidence, notification
+    )
+  }
+}
+
+/// A reason template that moderators can pick instead of typing a reason.
+pub struct ModReason {
+  pub id: String,
+  pub key: String,
+  pub text: String,
+  pub rule_link: Option<String>,
+  pub uses: i64,
+}
+
+impl ModReason {
+  /// The reason as recorded and shown to members, including the rule link if there is one.
+  pub fn full_text(&self) -> String {
+    match &self.rule_link {
+      Some(rule_link) => format!("{} ({})", self.text, rule_link),
+      None => self.text.clone(),
+    }
+  }
+}
+
+impl PageRow for ModReason {
+  fn title(&self) -> String {
+    match self.uses {
+      1 => format!("{} — 1 use", self.key),
+      uses => format!("{} — {} uses", self.key, uses),
     }
   }
+
+  fn body(&self) -> String {
+    self.full_text()
+  }
+}
+
+pub struct AppealData {
+  pub id: String,
+  pub guild_id: serenity::GuildId,
+  pub case_id: String,
+  pub user_id: serenity::UserId,
+  pub appeal_text: String,
+  pub status: AppealStatus,
+  pub reviewer_id: Option<serenity::UserId>,
+  pub decision_notes: Option<String>,
+  pub staff_message_id: Option<serenity::MessageId>,
+  pub created_at: chrono::DateTime<Utc>,
+}
+
+pub struct TermSuggestion {
+  pub id: String,
+  pub guild_id: serenity::GuildId,
+  pub term_name: String,
+  pub meaning: Option<String>,
+  pub suggester_id: serenity::UserId,
+  pub status: SuggestionStatus,
+  pub reviewer_id: Option<serenity::UserId>,
+  pub decision_notes: Option<String>,
+  pub created_at: chrono::DateTime<Utc>,
+}
+
+/// The channel a guild's term of the day is posted to.
+pub struct TermOfTheDayChannel {
+  pub guild_id: serenity::GuildId,
+  pub channel_id: serenity::ChannelId,
 }
 
 pub struct MeditationData {
@@ -279,11 +659,27 @@ impl PageRow for Term {
   }
 }
 
-#[derive(Debug, sqlx::FromRow)]
+#[derive(Debug)]
 pub struct TermSearchResult {
   pub term_name: String,
   pub meaning: String,
-  pub distance_score: Option<f64>,
+  pub category: Option<String>,
+  pub score: HybridScore,
+}
+
+/// The embedding of a search query, along with the model that created it. Only terms embedded by
+/// the same model are compared with it.
+pub struct SearchVector {
+  pub vector: pgvector::Vector,
+  pub model: String,
+}
+
+/// A term linked from another term's entry, either by a "see also" reference or because their
+/// embeddings are close.
+#[derive(Debug, Clone)]
+pub struct RelatedTerm {
+  pub id: String,
+  pub term_name: String,
 }
 
 pub struct TermNames {
@@ -291,96 +687,494 @@ pub struct TermNames {
   pub aliases: Option<Vec<String>>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TermRevisionAction {
+  Add,
+  Edit,
+  Remove,
+  Revert,
+}
+
+impl TermRevisionAction {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      TermRevisionAction::Add => "add",
+      TermRevisionAction::Edit => "edit",
+      TermRevisionAction::Remove => "remove",
+      TermRevisionAction::Revert => "revert",
+    }
+  }
+
+  pub fn from_str(action: &str) -> Option<TermRevisionAction> {
+    match action {
+      "add" => Some(TermRevisionAction::Add),
+      "edit" => Some(TermRevisionAction::Edit),
+      "remove" => Some(TermRevisionAction::Remove),
+      "revert" => Some(TermRevisionAction::Revert),
+      _ => None,
+    }
+  }
+}
+
+/// A snapshot of a term after a change. For removals, the term as it was before it was removed.
+pub struct TermRevision {
+  pub revision_number: i32,
+  pub action: TermRevisionAction,
+  pub term_name: String,
+  pub meaning: String,
+  pub usage: Option<String>,
+  pub links: Vec<String>,
+  pub category: Option<String>,
+  pub aliases: Vec<String>,
+  pub editor_id: Option<serenity::UserId>,
+  pub reverted_to: Option<i32>,
+  pub created_at: chrono::DateTime<Utc>,
+}
+
+/// An embedding for a term, with the model that created it and a hash of the text it was created
+/// from, used to tell when it needs to be recomputed.
+pub struct TermEmbedding {
+  pub vector: pgvector::Vector,
+  pub model: String,
+  pub dimensions: i32,
+  pub input_hash: String,
+}
+
+/// A term's embedded fields and the metadata of its current embedding.
+pub struct TermIndexData {
+  pub id: String,
+  pub term_name: String,
+  pub meaning: String,
+  pub usage: Option<String>,
+  pub category: Option<String>,
+  pub aliases: Vec<String>,
+  pub embedding_model: Option<String>,
+  pub embedding_hash: Option<String>,
+}
+
 pub struct StarMessage {
   pub record_id: String,
+  pub board_id: String,
   pub starred_message_id: serenity::MessageId,
   pub board_message_id: serenity::MessageId,
   pub starred_channel_id: serenity::ChannelId,
+  pub star_count: i32,
 }
 
-impl DatabaseHandler {
-  pub async fn new() -> Result<Self> {
-    let database_url =
-      std::env::var("DATABASE_URL").with_context(|| "Missing DATABASE_URL environment variable")?;
-    // let pool = sqlx::PgPool::connect(&database_url).await?;
-    let max_retries = 5;
-    let mut attempts = 0;
+pub struct StarredMessage {
+  pub rank: usize,
+  pub guild_id: serenity::GuildId,
+  pub starred_message_id: serenity::MessageId,
+  pub starred_channel_id: serenity::ChannelId,
+  pub author_id: Option<serenity::UserId>,
+  pub star_count: i32,
+}
 
-    loop {
-      let pool = match sqlx::PgPool::connect(&database_url).await {
-        Ok(pool) => pool,
-        Err(e) => {
-          if attempts >= max_retries {
-            return Err(e.into());
-          }
+impl PageRow for StarredMessage {
+  fn title(&self) -> String {
+    format!("#{} — ⭐ {}", self.rank, self.star_count)
+  }
 
-          // Check if the error is a sqlx::Error
-          if let Some(sqlx_error) = Some(&e) {
-            // Now we can handle the sqlx::Error specifically
-            if let sqlx::Error::Io(io_error) = sqlx_error {
-              if io_error.kind() == std::io::ErrorKind::ConnectionReset {
-                attempts += 1;
-                // Log warning
-                warn!("Error establishing a database connection: retry attempt {} of {}", attempts, max_retries);
-                // Wait before retrying
-                tokio::time::sleep(std::time::Duration::from_secs(60)).await;
-                continue;
-              }
-            }
-          }
+  fn body(&self) -> String {
+    let author = match self.author_id {
+      Some(author_id) => format!("{} in ", author_id.mention()),
+      None => String::new(),
+    };
 
-          // If it's a different kind of error, we might want to return it immediately
-          return Err(e.into());
-        }
-      };
+    format!(
+      "{}{}\n[Go to message]({})",
+      author,
+      self.starred_channel_id.mention(),
+      self
+        .starred_message_id
+        .link(self.starred_channel_id, Some(self.guild_id)),
+    )
+  }
+}
 
-      sqlx::migrate!("./migrations").run(&pool).await?;
-  
-      info!("Successfully applied migrations.");
-  
-      return Ok(Self { pool });
-    }
+pub struct StarRecipient {
+  pub rank: usize,
+  pub author_id: serenity::UserId,
+  pub total_stars: i64,
+  pub message_count: i64,
+}
+
+impl PageRow for StarRecipient {
+  fn title(&self) -> String {
+    format!("#{} — ⭐ {}", self.rank, self.total_stars)
   }
 
-  pub async fn get_connection(&self) -> Result<sqlx::pool::PoolConnection<sqlx::Postgres>> {
-    Ok(self.pool.acquire().await?)
+  fn body(&self) -> String {
+    format!(
+      "{}\n{} starred message{}",
+      self.author_id.mention(),
+      self.message_count,
+      match self.message_count {
+        1 => "",
+        _ => "s",
+      }
+    )
   }
+}
 
-  pub async fn get_connection_with_retry(
-    &self,
-    max_retries: usize,
-  ) -> Result<sqlx::pool::PoolConnection<sqlx::Postgres>> {
-    let mut attempts = 0;
+pub struct ChallengeData {
+  pub id: String,
+  pub challenge_name: String,
+  pub start_date: chrono::NaiveDate,
+  pub end_date: chrono::NaiveDate,
+  pub goal_type: ChallengeGoal,
+  pub goal_target: i32,
+  pub reward_role: Option<serenity::RoleId>,
+}
 
-    loop {
-      match self.get_connection().await {
-        Ok(connection) => return Ok(connection),
-        Err(e) => {
-          if attempts >= max_retries {
-            return Err(e);
-          }
+impl ChallengeData {
+  pub fn is_active(&self) -> bool {
+    let today = Utc::now().date_naive();
+    self.start_date <= today && today <= self.end_date
+  }
 
-          // Check if the error is a sqlx::Error
-          if let Some(sqlx_error) = e.downcast_ref::<sqlx::Error>() {
-            // Now we can handle the sqlx::Error specifically
-            if let sqlx::Error::Io(io_error) = sqlx_error {
-              if io_error.kind() == std::io::ErrorKind::ConnectionReset {
-                attempts += 1;
-                // Wait for a moment before retrying
-                tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-                continue;
-              }
-            }
-          }
+  pub fn has_ended(&self) -> bool {
+    Utc::now().date_naive() > self.end_date
+  }
 
-          // If it's a different kind of error, we might want to return it immediately
-          return Err(e);
-        }
-      }
+  pub fn describe_goal(&self) -> String {
+    match self.goal_type {
+      ChallengeGoal::Minutes => format!("{} minutes", self.goal_target),
+      ChallengeGoal::Sessions => format!("{} sessions", self.goal_target),
+      ChallengeGoal::Days => format!("{} days practiced", self.goal_target),
     }
   }
+}
 
-  pub async fn start_transaction(&self) -> Result<sqlx::Transaction<'_, sqlx::Postgres>> {
+impl PageRow for ChallengeData {
+  fn title(&self) -> String {
+    self.challenge_name.clone()
+  }
+
+  fn body(&self) -> String {
+    format!(
+      "Dates: `{}` to `{}`\nGoal: {}\nReward: {}",
+      self.start_date.format("%Y-%m-%d"),
+      self.end_date.format("%Y-%m-%d"),
+      self.describe_goal(),
+      match self.reward_role {
+        Some(reward_role) => reward_role.mention().to_string(),
+        None => "None".to_string(),
+      },
+    )
+  }
+}
+
+pub struct ChallengeProgress {
+  pub user_id: serenity::UserId,
+  pub minutes: i64,
+  pub sessions: i64,
+  pub days: i64,
+  pub completed_at: Option<chrono::DateTime<Utc>>,
+}
+
+impl ChallengeProgress {
+  pub fn value(&self, goal_type: ChallengeGoal) -> i64 {
+    match goal_type {
+      ChallengeGoal::Minutes => self.minutes,
+      ChallengeGoal::Sessions => self.sessions,
+      ChallengeGoal::Days => self.days,
+    }
+  }
+}
+
+pub struct ChallengeStanding {
+  pub rank: usize,
+  pub goal_type: ChallengeGoal,
+  pub goal_target: i32,
+  pub progress: ChallengeProgress,
+}
+
+impl PageRow for ChallengeStanding {
+  fn title(&self) -> String {
+    format!("#{}", self.rank)
+  }
+
+  fn body(&self) -> String {
+    let value = self.progress.value(self.goal_type);
+    let completed = match self.progress.completed_at {
+      Some(_) => " :white_check_mark:",
+      None => "",
+    };
+
+    format!(
+      "{}\n{} / {} {}{}",
+      self.progress.user_id.mention(),
+      value,
+      self.goal_target,
+      self.goal_type.as_str(),
+      completed,
+    )
+  }
+}
+
+pub struct ChallengeTeam {
+  pub id: String,
+  pub team_name: String,
+}
+
+pub struct TeamStanding {
+  pub team_id: String,
+  pub team_name: String,
+  pub member_count: i64,
+  pub total_minutes: i64,
+}
+
+/// A team challenge that still needs its standings refreshed or its results posted.
+pub struct LiveTeamChallenge {
+  pub guild_id: serenity::GuildId,
+  pub challenge: ChallengeData,
+  pub standings_channel_id: Option<serenity::ChannelId>,
+  pub standings_message_id: Option<serenity::MessageId>,
+}
+
+pub struct Buddy {
+  pub buddy_id: serenity::UserId,
+  pub notifications: BuddyNotifications,
+}
+
+/// Meditation a buddy has logged since the last daily digest was sent.
+pub struct BuddyDigest {
+  pub record_id: String,
+  pub guild_id: serenity::GuildId,
+  pub user_id: serenity::UserId,
+  pub buddy_id: serenity::UserId,
+  pub minutes: i64,
+  pub sessions: i64,
+  /// Whether the buddy keeps their stats private or tracks anonymously, in which case the digest
+  /// leaves out their minutes.
+  pub stats_private: bool,
+}
+
+/// The figures badge unlock predicates are evaluated against.
+pub struct BadgeStats {
+  pub session_count: i64,
+  pub longest_session: i32,
+  /// Distinct days with a session started before 7am local time, in ascending order.
+  pub early_days: Vec<chrono::NaiveDate>,
+  pub challenges_joined: i64,
+}
+
+pub struct UnlockedBadge {
+  pub badge_key: String,
+  pub unlocked_at: chrono::DateTime<Utc>,
+}
+
+pub struct ReportData {
+  pub id: String,
+  pub guild_id: serenity::GuildId,
+  pub case_number: i32,
+  pub reporter_id: serenity::UserId,
+  pub author_id: serenity::UserId,
+  pub channel_id: serenity::ChannelId,
+  pub message_id: serenity::MessageId,
+  pub message_content: String,
+  pub source: String,
+  pub status: ReportStatus,
+  pub assignee_id: Option<serenity::UserId>,
+  pub resolution_notes: Option<String>,
+  pub report_message_id: Option<serenity::MessageId>,
+  pub reporter_count: i64,
+  pub created_at: chrono::DateTime<Utc>,
+}
+
+impl ReportData {
+  pub fn message_link(&self) -> String {
+    self.message_id.link(self.channel_id, Some(self.guild_id))
+  }
+}
+
+impl PageRow for ReportData {
+  fn title(&self) -> String {
+    format!("Case #{} — {}", self.case_number, self.status.name())
+  }
+
+  fn body(&self) -> String {
+    let assignee = match self.assignee_id {
+      Some(assignee_id) => format!("\nAssignee: {}", assignee_id.mention()),
+      None => String::new(),
+    };
+    let report_link = match self.report_message_id {
+      Some(report_message_id) => format!(
+        " · [Go to report]({})",
+        report_message_id.link(
+          serenity::ChannelId::new(crate::config::CHANNELS.reportchannel),
+          Some(self.guild_id)
+        )
+      ),
+      None => String::new(),
+    };
+
+    format!(
+      "Author: {}\nReported by {}{} <t:{}:R>{}\n[Go to message]({}){}",
+      self.author_id.mention(),
+      self.reporter_id.mention(),
+      match self.reporter_count {
+        count if count > 1 => format!(" and {} others", count - 1),
+        _ => String::new(),
+      },
+      self.created_at.timestamp(),
+      assignee,
+      self.message_link(),
+      report_link,
+    )
+  }
+}
+
+/// A board that messages are posted to once they receive enough of its reaction. The starboard
+/// is configured in code with [`ReactionBoard::starboard`], while other boards are stored per guild.
+pub struct ReactionBoard {
+  pub id: String,
+  pub name: String,
+  pub emoji: serenity::ReactionType,
+  pub channel_id: serenity::ChannelId,
+  pub min_reactions: u64,
+  pub color: u32,
+  pub footer_label: String,
+}
+
+impl ReactionBoard {
+  /// The board ID used for starboard entries in the `star` table.
+  pub const STARBOARD_ID: &'static str = "starboard";
+
+  pub fn starboard() -> Self {
+    Self {
+      id: Self::STARBOARD_ID.to_string(),
+      name: "starboard".to_string(),
+      emoji: serenity::ReactionType::Unicode(crate::config::EMOTES.star.to_string()),
+      channel_id: serenity::ChannelId::new(crate::config::CHANNELS.starchannel),
+      min_reactions: crate::config::MIN_STARS,
+      color: crate::config::EMBED_COLOR,
+      footer_label: "Times starred".to_string(),
+    }
+  }
+
+  pub fn is_starboard(&self) -> bool {
+    self.id == Self::STARBOARD_ID
+  }
+
+  /// Whether a reaction is this board's emoji. Custom emojis are compared by ID, since their
+  /// names can change.
+  pub fn matches(&self, emoji: &serenity::ReactionType) -> bool {
+    match (&self.emoji, emoji) {
+      (
+        serenity::ReactionType::Custom { id, .. },
+        serenity::ReactionType::Custom { id: other_id, .. },
+      ) => id == other_id,
+      (serenity::ReactionType::Unicode(name), serenity::ReactionType::Unicode(other_name)) => {
+        name == other_name
+      }
+      _ => false,
+    }
+  }
+
+  /// Footer text for a board message. Custom emojis are shown by name, since footers can't
+  /// render them.
+  pub fn footer(&self, count: u64) -> String {
+    let emoji = match &self.emoji {
+      serenity::ReactionType::Custom { name: Some(name), .. } => format!(":{}:", name),
+      emoji => emoji.to_string(),
+    };
+
+    format!("{} {}: {}", emoji, self.footer_label, count)
+  }
+}
+
+/// Starboard settings for a single channel. Channels without a row use [`crate::config::MIN_STARS`].
+pub struct StarboardChannel {
+  pub channel_id: serenity::ChannelId,
+  pub min_stars: Option<i32>,
+  pub excluded: bool,
+}
+
+impl DatabaseHandler {
+  pub async fn new() -> Result<Self> {
+    let database_url =
+      std::env::var("DATABASE_URL").with_context(|| "Missing DATABASE_URL environment variable")?;
+    // let pool = sqlx::PgPool::connect(&database_url).await?;
+    let max_retries = 5;
+    let mut attempts = 0;
+
+    loop {
+      let pool = match sqlx::PgPool::connect(&database_url).await {
+        Ok(pool) => pool,
+        Err(e) => {
+          if attempts >= max_retries {
+            return Err(e.into());
+          }
+
+          // Check if the error is a sqlx::Error
+          if let Some(sqlx_error) = Some(&e) {
+            // Now we can handle the sqlx::Error specifically
+            if let sqlx::Error::Io(io_error) = sqlx_error {
+              if io_error.kind() == std::io::ErrorKind::ConnectionReset {
+                attempts += 1;
+                // Log warning
+                warn!("Error establishing a database connection: retry attempt {} of {}", attempts, max_retries);
+                // Wait before retrying
+                tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+                continue;
+              }
+            }
+          }
+
+          // If it's a different kind of error, we might want to return it immediately
+          return Err(e.into());
+        }
+      };
+
+      sqlx::migrate!("./migrations").run(&pool).await?;
+  
+      info!("Successfully applied migrations.");
+  
+      return Ok(Self { pool });
+    }
+  }
+
+  pub async fn get_connection(&self) -> Result<sqlx::pool::PoolConnection<sqlx::Postgres>> {
+    Ok(self.pool.acquire().await?)
+  }
+
+  pub async fn get_connection_with_retry(
+    &self,
+    max_retries: usize,
+  ) -> Result<sqlx::pool::PoolConnection<sqlx::Postgres>> {
+    let mut attempts = 0;
+
+    loop {
+      match self.get_connection().await {
+        Ok(connection) => return Ok(connection),
+        Err(e) => {
+          if attempts >= max_retries {
+            return Err(e);
+          }
+
+          // Check if the error is a sqlx::Error
+          if let Some(sqlx_error) = e.downcast_ref::<sqlx::Error>() {
+            // Now we can handle the sqlx::Error specifically
+            if let sqlx::Error::Io(io_error) = sqlx_error {
+              if io_error.kind() == std::io::ErrorKind::ConnectionReset {
+                attempts += 1;
+                // Wait for a moment before retrying
+                tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                continue;
+              }
+            }
+          }
+
+          // If it's a different kind of error, we might want to return it immediately
+          return Err(e);
+        }
+      }
+    }
+  }
+
+  pub async fn start_transaction(&self) -> Result<sqlx::Transaction<'_, sqlx::Postgres>> {
     Ok(self.pool.begin().await?)
   }
 
@@ -536,7 +1330,7 @@ impl DatabaseHandler {
   ) -> Result<Option<TrackingProfile>> {
     let row = sqlx::query!(
       r#"
-        SELECT user_id, guild_id, utc_offset, anonymous_tracking, streaks_active, streaks_private, stats_private FROM tracking_profile WHERE user_id = $1 AND guild_id = $2
+        SELECT user_id, guild_id, utc_offset, anonymous_tracking, streaks_active, streaks_private, stats_private, weekly_recap FROM tracking_profile WHERE user_id = $1 AND guild_id = $2
       "#,
       user_id.to_string(),
       guild_id.to_string(),
@@ -553,6 +1347,7 @@ impl DatabaseHandler {
         streaks_active: row.streaks_active,
         streaks_private: row.streaks_private,
         stats_private: row.stats_private,
+        weekly_recap: row.weekly_recap,
       }),
       None => None,
     };
@@ -560,6 +1355,79 @@ impl DatabaseHandler {
     Ok(tracking_profile)
   }
 
+  pub async fn update_weekly_recap(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    user_id: &serenity::UserId,
+    weekly_recap: bool,
+  ) -> Result<()> {
+    sqlx::query!(
+      r#"
+        UPDATE tracking_profile SET weekly_recap = $1 WHERE user_id = $2 AND guild_id = $3
+      "#,
+      weekly_recap,
+      user_id.to_string(),
+      guild_id.to_string(),
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+  }
+
+  /// Gets the profiles of everyone who is due a weekly recap: it is Sunday evening in their
+  /// local time and they have not been sent a recap in the last few days.
+  pub async fn get_due_weekly_recaps(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+  ) -> Result<Vec<TrackingProfile>> {
+    let rows = sqlx::query!(
+      r#"
+        SELECT user_id, guild_id, utc_offset, anonymous_tracking, streaks_active, streaks_private, stats_private, weekly_recap
+        FROM tracking_profile
+        WHERE weekly_recap = TRUE
+          AND (last_recap_at IS NULL OR last_recap_at < NOW() - INTERVAL '3 days')
+          AND EXTRACT(DOW FROM (NOW() AT TIME ZONE 'UTC') + utc_offset * INTERVAL '1 minute') = 0
+          AND EXTRACT(HOUR FROM (NOW() AT TIME ZONE 'UTC') + utc_offset * INTERVAL '1 minute') >= 18
+      "#,
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    let profiles = rows
+      .into_iter()
+      .map(|row| TrackingProfile {
+        user_id: serenity::UserId::new(row.user_id.parse::<u64>().unwrap()),
+        guild_id: serenity::GuildId::new(row.guild_id.parse::<u64>().unwrap()),
+        utc_offset: row.utc_offset,
+        anonymous_tracking: row.anonymous_tracking,
+        streaks_active: row.streaks_active,
+        streaks_private: row.streaks_private,
+        stats_private: row.stats_private,
+        weekly_recap: row.weekly_recap,
+      })
+      .collect();
+
+    Ok(profiles)
+  }
+
+  pub async fn mark_weekly_recap_sent(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    user_id: &serenity::UserId,
+  ) -> Result<()> {
+    sqlx::query!(
+      r#"
+        UPDATE tracking_profile SET last_recap_at = NOW() WHERE user_id = $1 AND guild_id = $2
+      "#,
+      user_id.to_string(),
+      guild_id.to_string(),
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+  }
+
   pub async fn add_steamkey_recipient(
     transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
     guild_id: &serenity::GuildId,
@@ -747,27 +1615,31 @@ impl DatabaseHandler {
     Ok(())
   }
 
+  /// Records an erase in the moderation log.
+  #[allow(clippy::too_many_arguments)]
   pub async fn add_erase(
     transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
     guild_id: &serenity::GuildId,
     user_id: &serenity::UserId,
+    moderator_id: Option<&serenity::UserId>,
+    reason: Option<&str>,
+    reason_key: Option<&str>,
     message_link: &str,
     occurred_at: chrono::DateTime<Utc>,
-  ) -> Result<()> {
-    sqlx::query!(
-      r#"
-        INSERT INTO erases (record_id, user_id, guild_id, message_link, occurred_at) VALUES ($1, $2, $3, $4, $5)
-      "#,
-      Ulid::new().to_string(),
-      user_id.to_string(),
-      guild_id.to_string(),
-      message_link,
+  ) -> Result<ModCase> {
+    DatabaseHandler::add_mod_case(
+      transaction,
+      guild_id,
+      user_id,
+      moderator_id,
+      ModAction::Erase,
+      reason,
+      reason_key,
+      Some(message_link),
+      None,
       occurred_at,
     )
-    .execute(&mut **transaction)
-    .await?;
-
-    Ok(())
+    .await
   }
 
   pub async fn get_erases(
@@ -777,7 +1649,11 @@ impl DatabaseHandler {
   ) -> Result<Vec<EraseData>> {
     let rows = sqlx::query!(
       r#"
-        SELECT record_id, user_id, message_link, occurred_at FROM erases WHERE user_id = $1 AND guild_id = $2 ORDER BY occurred_at DESC
+        SELECT mod_case.record_id, mod_case.user_id, mod_case.evidence_link, mod_case.reason, mod_case.notification_method, mod_case.created_at, appeal.status AS "appeal_status?"
+        FROM mod_case
+        LEFT JOIN appeal ON appeal.case_id = mod_case.record_id
+        WHERE mod_case.user_id = $1 AND mod_case.guild_id = $2 AND mod_case.action = 'erase'
+        ORDER BY mod_case.created_at DESC
       "#,
       user_id.to_string(),
       guild_id.to_string(),
@@ -790,8 +1666,15 @@ impl DatabaseHandler {
       .map(|row| EraseData {
         id: row.record_id,
         user_id: serenity::UserId::new(row.user_id.parse::<u64>().unwrap()),
-        message_link: row.message_link.unwrap_or(String::from("None")),
-        occurred_at: row.occurred_at.unwrap_or(chrono::DateTime::<Utc>::default()),
+        message_link: row.evidence_link.unwrap_or(String::from("None")),
+        reason: row.reason,
+        notification_method: row
+          .notification_method
+          .and_then(|method| NotificationMethod::from_str(&method)),
+        appeal_status: row
+          .appeal_status
+          .and_then(|status| AppealStatus::from_str(&status)),
+        occurred_at: row.created_at,
       })
       .collect();
 
@@ -1068,45 +1951,89 @@ impl DatabaseHandler {
     Ok(user_total)
   }
 
-  pub async fn get_user_meditation_count(
+  pub async fn get_user_meditation_sum_between(
     transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
     guild_id: &serenity::GuildId,
     user_id: &serenity::UserId,
-  ) -> Result<u64> {
+    start_time: chrono::DateTime<Utc>,
+    end_time: chrono::DateTime<Utc>,
+  ) -> Result<i64> {
     let row = sqlx::query!(
       r#"
-        SELECT COUNT(record_id) AS user_total FROM meditation WHERE user_id = $1 AND guild_id = $2
+        SELECT COALESCE(SUM(meditation_minutes), 0) AS "user_total!" FROM meditation WHERE user_id = $1 AND guild_id = $2 AND occurred_at >= $3 AND occurred_at < $4
       "#,
       user_id.to_string(),
       guild_id.to_string(),
+      start_time,
+      end_time,
     )
     .fetch_one(&mut **transaction)
     .await?;
 
-    let user_total = row.user_total.unwrap();
-
-    Ok(user_total.try_into().unwrap())
+    Ok(row.user_total)
   }
 
-  pub async fn get_guild_meditation_sum(
+  pub async fn get_user_meditation_count_between(
     transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
     guild_id: &serenity::GuildId,
+    user_id: &serenity::UserId,
+    start_time: chrono::DateTime<Utc>,
+    end_time: chrono::DateTime<Utc>,
   ) -> Result<i64> {
     let row = sqlx::query!(
       r#"
-        SELECT SUM(meditation_minutes) AS guild_total FROM meditation WHERE guild_id = $1
+        SELECT COUNT(record_id) AS "user_total!" FROM meditation WHERE user_id = $1 AND guild_id = $2 AND occurred_at >= $3 AND occurred_at < $4
       "#,
+      user_id.to_string(),
       guild_id.to_string(),
+      start_time,
+      end_time,
     )
     .fetch_one(&mut **transaction)
     .await?;
 
-    let guild_total = row.guild_total.unwrap();
-
-    Ok(guild_total)
+    Ok(row.user_total)
   }
 
-  pub async fn get_guild_meditation_count(
+  pub async fn get_user_meditation_count(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    user_id: &serenity::UserId,
+  ) -> Result<u64> {
+    let row = sqlx::query!(
+      r#"
+        SELECT COUNT(record_id) AS user_total FROM meditation WHERE user_id = $1 AND guild_id = $2
+      "#,
+      user_id.to_string(),
+      guild_id.to_string(),
+    )
+    .fetch_one(&mut **transaction)
+    .await?;
+
+    let user_total = row.user_total.unwrap();
+
+    Ok(user_total.try_into().unwrap())
+  }
+
+  pub async fn get_guild_meditation_sum(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+  ) -> Result<i64> {
+    let row = sqlx::query!(
+      r#"
+        SELECT SUM(meditation_minutes) AS guild_total FROM meditation WHERE guild_id = $1
+      "#,
+      guild_id.to_string(),
+    )
+    .fetch_one(&mut **transaction)
+    .await?;
+
+    let guild_total = row.guild_total.unwrap();
+
+    Ok(guild_total)
+  }
+
+  pub async fn get_guild_meditation_count(
     transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
     guild_id: &serenity::GuildId,
   ) -> Result<u64> {
@@ -1422,11 +2349,21 @@ impl DatabaseHandler {
     category: Option<&str>,
     aliases: &[String],
     guild_id: &serenity::GuildId,
-    vector: pgvector::Vector,
+    embedding: Option<TermEmbedding>,
   ) -> Result<()> {
+    let (vector, model, dimensions, input_hash) = match embedding {
+      Some(embedding) => (
+        Some(embedding.vector),
+        Some(embedding.model),
+        Some(embedding.dimensions),
+        Some(embedding.input_hash),
+      ),
+      None => (None, None, None, None),
+    };
+
     sqlx::query(
       r#"
-        INSERT INTO term (record_id, term_name, meaning, usage, links, category, aliases, guild_id, embedding) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        INSERT INTO term (record_id, term_name, meaning, usage, links, category, aliases, guild_id, embedding, embedding_model, embedding_dimensions, embedding_hash) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
       "#)
       .bind(Ulid::new().to_string())
       .bind(term_name)
@@ -1437,40 +2374,226 @@ impl DatabaseHandler {
       .bind(aliases)
       .bind(guild_id.to_string())
       .bind(vector)
+      .bind(model)
+      .bind(dimensions)
+      .bind(input_hash)
       .execute(&mut **transaction)
       .await?;
 
     Ok(())
   }
 
-  pub async fn search_terms_by_vector(
+  /// Searches terms by fusing the rankings of term name similarity, alias similarity, and, when a
+  /// search vector is given, embedding similarity. See [`term_search::fuse`] for how the rankings
+  /// are combined. Only embeddings created by the same model, with the same size, are compared.
+  pub async fn search_terms(
     transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
     guild_id: &serenity::GuildId,
-    search_vector: pgvector::Vector,
+    search: &str,
+    search_vector: Option<SearchVector>,
+    category: Option<&str>,
     limit: usize,
   ) -> Result<Vec<TermSearchResult>> {
-    // For some reason, pgvector wants a vector to look like a string [1,2,3] instead of an array.
-    // I'm sorry for what you are about to see.
-    // let pgvector_format = format!("{:?}", search_vector);
+    let mut rankings = vec![
+      (
+        Signal::Trigram,
+        DatabaseHandler::get_term_name_candidates(transaction, guild_id, search, category).await?,
+      ),
+      (
+        Signal::Alias,
+        DatabaseHandler::get_term_alias_candidates(transaction, guild_id, search, category).await?,
+      ),
+    ];
+
+    if let Some(search_vector) = search_vector {
+      rankings.push((
+        Signal::Vector,
+        DatabaseHandler::get_term_vector_candidates(transaction, guild_id, search_vector, category)
+          .await?,
+      ));
+    }
+
+    let ranked = term_search::fuse(rankings, limit);
+    let ids: Vec<String> = ranked.iter().map(|term| term.id.clone()).collect();
 
-    let terms: Vec<TermSearchResult> = sqlx::query_as(
+    let rows = sqlx::query!(
       r#"
-        SELECT term_name, meaning, embedding <=> $1 AS distance_score
+        SELECT record_id, term_name, meaning, category
         FROM term
-        WHERE guild_id = $2
-        ORDER BY distance_score ASC
-        LIMIT $3
+        WHERE record_id = ANY($1)
       "#,
+      &ids,
     )
-    .bind(search_vector)
-    .bind(guild_id.to_string())
-    .bind(limit as i64)
     .fetch_all(&mut **transaction)
     .await?;
 
+    let mut rows: HashMap<String, _> = rows
+      .into_iter()
+      .map(|row| (row.record_id.clone(), row))
+      .collect();
+
+    let terms = ranked
+      .into_iter()
+      .filter_map(|term| {
+        rows.remove(&term.id).map(|row| TermSearchResult {
+          term_name: row.term_name,
+          meaning: row.meaning,
+          category: row.category,
+          score: term.score,
+        })
+      })
+      .collect();
+
     Ok(terms)
   }
 
+  /// Returns the cached embedding of a normalized search query, counting the lookup as a hit.
+  pub async fn get_query_embedding(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    query_text: &str,
+    model: &str,
+  ) -> Result<Option<pgvector::Vector>> {
+    let row: Option<(pgvector::Vector,)> = sqlx::query_as(
+      r#"
+        UPDATE query_embedding
+        SET hit_count = hit_count + 1, last_used_at = NOW()
+        WHERE embedding_model = $1 AND query_text = $2
+        RETURNING embedding
+      "#,
+    )
+    .bind(model)
+    .bind(query_text)
+    .fetch_optional(&mut **transaction)
+    .await?;
+
+    Ok(row.map(|(embedding,)| embedding))
+  }
+
+  pub async fn add_query_embedding(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    query_text: &str,
+    model: &str,
+    embedding: pgvector::Vector,
+  ) -> Result<()> {
+    sqlx::query(
+      r#"
+        INSERT INTO query_embedding (query_text, embedding_model, embedding) VALUES ($1, $2, $3)
+        ON CONFLICT (embedding_model, query_text) DO UPDATE
+        SET embedding = EXCLUDED.embedding, last_used_at = NOW()
+      "#,
+    )
+    .bind(query_text)
+    .bind(model)
+    .bind(embedding)
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+  }
+
+  async fn get_term_name_candidates(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    search: &str,
+    category: Option<&str>,
+  ) -> Result<Vec<Candidate>> {
+    let rows = sqlx::query!(
+      r#"
+        SELECT record_id, GREATEST(SIMILARITY(LOWER(term_name), LOWER($1)), WORD_SIMILARITY(LOWER(term_name), LOWER($1)))::float8 AS "similarity!"
+        FROM term
+        WHERE guild_id = $2 AND ($3::text IS NULL OR LOWER($3) IN (SELECT LOWER(TRIM(c)) FROM UNNEST(STRING_TO_ARRAY(category, ',')) AS c))
+        ORDER BY 2 DESC
+        LIMIT $4
+      "#,
+      search,
+      guild_id.to_string(),
+      category,
+      crate::config::GLOSSARY_SEARCH_CANDIDATES,
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    let candidates = rows
+      .into_iter()
+      .map(|row| Candidate {
+        id: row.record_id,
+        similarity: row.similarity,
+      })
+      .collect();
+
+    Ok(candidates)
+  }
+
+  async fn get_term_alias_candidates(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    search: &str,
+    category: Option<&str>,
+  ) -> Result<Vec<Candidate>> {
+    let rows = sqlx::query!(
+      r#"
+        SELECT record_id, MAX(GREATEST(SIMILARITY(LOWER(alias), LOWER($1)), WORD_SIMILARITY(LOWER(alias), LOWER($1))))::float8 AS "similarity!"
+        FROM term
+        CROSS JOIN LATERAL UNNEST(aliases) AS a(alias)
+        WHERE guild_id = $2 AND ($3::text IS NULL OR LOWER($3) IN (SELECT LOWER(TRIM(c)) FROM UNNEST(STRING_TO_ARRAY(category, ',')) AS c))
+        GROUP BY record_id
+        ORDER BY 2 DESC
+        LIMIT $4
+      "#,
+      search,
+      guild_id.to_string(),
+      category,
+      crate::config::GLOSSARY_SEARCH_CANDIDATES,
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    let candidates = rows
+      .into_iter()
+      .map(|row| Candidate {
+        id: row.record_id,
+        similarity: row.similarity,
+      })
+      .collect();
+
+    Ok(candidates)
+  }
+
+  async fn get_term_vector_candidates(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    search_vector: SearchVector,
+    category: Option<&str>,
+  ) -> Result<Vec<Candidate>> {
+    let dimensions = search_vector.vector.as_slice().len() as i32;
+
+    let rows: Vec<(String, f64)> = sqlx::query_as(
+      r#"
+        SELECT record_id, 1 - (embedding <=> $1) AS similarity
+        FROM term
+        WHERE guild_id = $2 AND embedding_model = $3 AND embedding_dimensions = $4
+        AND ($5::text IS NULL OR LOWER($5) IN (SELECT LOWER(TRIM(c)) FROM UNNEST(STRING_TO_ARRAY(category, ',')) AS c))
+        ORDER BY embedding <=> $1 ASC
+        LIMIT $6
+      "#,
+    )
+    .bind(search_vector.vector)
+    .bind(guild_id.to_string())
+    .bind(search_vector.model)
+    .bind(dimensions)
+    .bind(category)
+    .bind(crate::config::GLOSSARY_SEARCH_CANDIDATES)
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    let candidates = rows
+      .into_iter()
+      .map(|(id, similarity)| Candidate { id, similarity })
+      .collect();
+
+    Ok(candidates)
+  }
+
   pub async fn get_term(
     transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
     guild_id: &serenity::GuildId,
@@ -1546,13 +2669,12 @@ impl DatabaseHandler {
     links: &[String],
     category: Option<&str>,
     aliases: &[String],
-    vector: Option<pgvector::Vector>,
   ) -> Result<()> {
     sqlx::query(
       r#"
         UPDATE term
-        SET meaning = $1, usage = $2, links = $3, category = $4, aliases = $5, embedding = COALESCE($6, embedding)
-        WHERE record_id = $7
+        SET meaning = $1, usage = $2, links = $3, category = $4, aliases = $5
+        WHERE record_id = $6
       "#,
     )
     .bind(meaning)
@@ -1560,7 +2682,6 @@ impl DatabaseHandler {
     .bind(links)
     .bind(category)
     .bind(aliases)
-    .bind(vector)
     .bind(original_id)
     .execute(&mut **transaction)
     .await?;
@@ -1568,6 +2689,63 @@ impl DatabaseHandler {
     Ok(())
   }
 
+  pub async fn set_term_embedding(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    record_id: &str,
+    embedding: TermEmbedding,
+  ) -> Result<()> {
+    sqlx::query(
+      r#"
+        UPDATE term
+        SET embedding = $1, embedding_model = $2, embedding_dimensions = $3, embedding_hash = $4
+        WHERE record_id = $5
+      "#,
+    )
+    .bind(embedding.vector)
+    .bind(embedding.model)
+    .bind(embedding.dimensions)
+    .bind(embedding.input_hash)
+    .bind(record_id)
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+  }
+
+  /// Returns the terms of a guild, or of every guild, with the metadata of their embeddings.
+  pub async fn get_terms_for_reindex(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: Option<&serenity::GuildId>,
+  ) -> Result<Vec<TermIndexData>> {
+    let rows = sqlx::query!(
+      r#"
+        SELECT record_id, term_name, meaning, usage, category, aliases, embedding_model, embedding_hash
+        FROM term
+        WHERE $1::text IS NULL OR guild_id = $1
+        ORDER BY term_name ASC
+      "#,
+      guild_id.map(|guild_id| guild_id.to_string()),
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    let terms = rows
+      .into_iter()
+      .map(|row| TermIndexData {
+        id: row.record_id,
+        term_name: row.term_name,
+        meaning: row.meaning,
+        usage: row.usage,
+        category: row.category,
+        aliases: row.aliases.unwrap_or_default(),
+        embedding_model: row.embedding_model,
+        embedding_hash: row.embedding_hash,
+      })
+      .collect();
+
+    Ok(terms)
+  }
+
   pub async fn get_all_courses(
     transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
     guild_id: &serenity::GuildId,
@@ -1653,6 +2831,36 @@ impl DatabaseHandler {
     Ok(extended_course_data)
   }
 
+  /// Returns the courses of every guild, for commands used in DMs.
+  pub async fn get_courses_in_dm(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+  ) -> Result<Vec<ExtendedCourseData>> {
+    let rows = sqlx::query!(
+      r#"
+        SELECT course_name, participant_role, graduate_role, guild_id
+        FROM course
+        WHERE guild_id IS NOT NULL
+        ORDER BY course_name ASC
+      "#,
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    let courses = rows
+      .into_iter()
+      .filter_map(|row| {
+        Some(ExtendedCourseData {
+          course_name: row.course_name,
+          participant_role: serenity::RoleId::new(row.participant_role.parse::<u64>().ok()?),
+          graduate_role: serenity::RoleId::new(row.graduate_role.parse::<u64>().ok()?),
+          guild_id: serenity::GuildId::new(row.guild_id?.parse::<u64>().ok()?),
+        })
+      })
+      .collect();
+
+    Ok(courses)
+  }
+
   pub async fn get_possible_course(
     transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
     guild_id: &serenity::GuildId,
@@ -1742,18 +2950,22 @@ impl DatabaseHandler {
     Ok(term_count.try_into().unwrap())
   }
 
+  /// Gets the names and aliases of the terms in a guild, optionally only those in a category.
   pub async fn get_term_list(
     transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
     guild_id: &serenity::GuildId,
+    category: Option<&str>,
   ) -> Result<Vec<TermNames>> {
     let rows = sqlx::query!(
       r#"
         SELECT term_name, aliases
         FROM term
         WHERE guild_id = $1
+        AND ($2::text IS NULL OR LOWER($2) IN (SELECT LOWER(TRIM(c)) FROM UNNEST(STRING_TO_ARRAY(category, ',')) AS c))
         ORDER BY term_name ASC
       "#,
       guild_id.to_string(),
+      category,
     )
     .fetch_all(&mut **transaction)
     .await?;
@@ -1769,18 +2981,22 @@ impl DatabaseHandler {
     Ok(term_list)
   }
 
+  /// Gets the terms in a guild for browsing, optionally only those in a category.
   pub async fn get_all_glossary_terms(
     transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
     guild_id: &serenity::GuildId,
+    category: Option<&str>,
   ) -> Result<Vec<Term>> {
     let rows = sqlx::query!(
       r#"
-        SELECT record_id, term_name, meaning
+        SELECT record_id, term_name, meaning, category
         FROM term
         WHERE guild_id = $1
+        AND ($2::text IS NULL OR LOWER($2) IN (SELECT LOWER(TRIM(c)) FROM UNNEST(STRING_TO_ARRAY(category, ',')) AS c))
         ORDER BY term_name ASC
       "#,
       guild_id.to_string(),
+      category,
     )
     .fetch_all(&mut **transaction)
     .await?;
@@ -1793,7 +3009,7 @@ impl DatabaseHandler {
         meaning: row.meaning,
         usage: None,
         links: None,
-        category: None,
+        category: row.category,
         aliases: None,
       })
       .collect();
@@ -1801,95 +3017,248 @@ impl DatabaseHandler {
     Ok(glossary)
   }
 
-  pub async fn unused_key_exists(
+  /// Gets the distinct categories used by the terms in a guild. Terms may list several
+  /// categories separated by commas.
+  pub async fn get_term_categories(
     transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
     guild_id: &serenity::GuildId,
-  ) -> Result<bool> {
-    let row = sqlx::query!(
+  ) -> Result<Vec<String>> {
+    let rows = sqlx::query!(
       r#"
-        SELECT EXISTS(SELECT 1 FROM steamkey WHERE used = FALSE AND reserved IS NULL AND guild_id = $1)
+        SELECT DISTINCT ON (LOWER(TRIM(c))) TRIM(c) AS "category!"
+        FROM term, UNNEST(STRING_TO_ARRAY(term.category, ',')) AS c
+        WHERE guild_id = $1 AND TRIM(c) <> ''
+        ORDER BY LOWER(TRIM(c)) ASC
       "#,
       guild_id.to_string(),
     )
-    .fetch_one(&mut **transaction)
+    .fetch_all(&mut **transaction)
     .await?;
 
-    Ok(row.exists.unwrap())
+    Ok(rows.into_iter().map(|row| row.category).collect())
   }
 
-  pub async fn reserve_key(
+  pub async fn get_term_by_id(
     transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
     guild_id: &serenity::GuildId,
-    user_id: &serenity::UserId,
-  ) -> Result<Option<String>> {
+    term_id: &str,
+  ) -> Result<Option<Term>> {
     let row = sqlx::query!(
       r#"
-        UPDATE steamkey SET reserved = $1 WHERE steam_key = (SELECT steam_key FROM steamkey WHERE used = FALSE AND reserved IS NULL AND guild_id = $2 ORDER BY RANDOM() LIMIT 1) RETURNING steam_key
+        SELECT record_id, term_name, meaning, usage, links, category, aliases
+        FROM term
+        WHERE record_id = $1 AND guild_id = $2
       "#,
-      user_id.to_string(),
+      term_id,
       guild_id.to_string(),
     )
     .fetch_optional(&mut **transaction)
     .await?;
 
-    Ok(row.map(|row| row.steam_key))
-  }
-
-  pub async fn unreserve_key(
-    connection: &mut sqlx::pool::PoolConnection<sqlx::Postgres>,
-    key: &str,
-  ) -> Result<()> {
-    sqlx::query!(
-      r#"
-        UPDATE steamkey SET reserved = NULL WHERE steam_key = $1
-      "#,
-      key,
-    )
-    .execute(&mut **connection)
-    .await?;
-
-    Ok(())
-  }
-
-  pub async fn mark_key_used(
-    connection: &mut sqlx::pool::PoolConnection<sqlx::Postgres>,
-    key: &str,
-  ) -> Result<()> {
-    sqlx::query!(
-      r#"
-        UPDATE steamkey SET used = TRUE WHERE steam_key = $1
-      "#,
-      key,
-    )
-    .execute(&mut **connection)
-    .await?;
+    let term = row.map(|row| Term {
+      id: row.record_id,
+      term_name: row.term_name,
+      meaning: row.meaning,
+      usage: row.usage,
+      links: row.links,
+      category: row.category,
+      aliases: row.aliases,
+    });
 
-    Ok(())
+    Ok(term)
   }
 
-  pub async fn get_key_and_mark_used(
+  /// Finds the term a "see also" reference names, by its exact name or one of its aliases,
+  /// ignoring case.
+  pub async fn get_referenced_term(
     transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
     guild_id: &serenity::GuildId,
-  ) -> Result<Option<String>> {
+    reference: &str,
+  ) -> Result<Option<RelatedTerm>> {
     let row = sqlx::query!(
       r#"
-        UPDATE steamkey SET used = TRUE WHERE steam_key = (SELECT steam_key FROM steamkey WHERE used = FALSE AND reserved IS NULL AND guild_id = $1 ORDER BY RANDOM() LIMIT 1) RETURNING steam_key
+        SELECT record_id, term_name
+        FROM term
+        WHERE guild_id = $2
+        AND (
+          LOWER(term_name) = LOWER($1)
+          OR LOWER($1) = ANY(SELECT LOWER(alias) FROM UNNEST(aliases) AS alias)
+        )
+        ORDER BY LOWER(term_name) = LOWER($1) DESC
+        LIMIT 1
       "#,
+      reference,
       guild_id.to_string(),
     )
     .fetch_optional(&mut **transaction)
     .await?;
 
-    Ok(row.map(|row| row.steam_key))
+    Ok(row.map(|row| RelatedTerm {
+      id: row.record_id,
+      term_name: row.term_name,
+    }))
   }
 
-  pub async fn get_random_quote(
+  /// Gets the terms whose embeddings are closest to a term's, best first. Only terms embedded by
+  /// the same model are compared, and terms less similar than `MIN_VECTOR_SIMILARITY` are left out.
+  pub async fn get_related_terms(
     transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
     guild_id: &serenity::GuildId,
-  ) -> Result<Option<QuoteData>> {
-    let row = sqlx::query!(
+    term_id: &str,
+    limit: i64,
+  ) -> Result<Vec<RelatedTerm>> {
+    let rows: Vec<(String, String)> = sqlx::query_as(
       r#"
-        SELECT record_id, quote, author FROM quote WHERE guild_id = $1 ORDER BY RANDOM() LIMIT 1
+        SELECT related.record_id, related.term_name
+        FROM term AS source
+        JOIN term AS related
+          ON related.guild_id = source.guild_id
+          AND related.record_id <> source.record_id
+          AND related.embedding_model = source.embedding_model
+          AND related.embedding_dimensions = source.embedding_dimensions
+        WHERE source.record_id = $1 AND source.guild_id = $2
+        AND 1 - (related.embedding <=> source.embedding) >= $3
+        ORDER BY related.embedding <=> source.embedding ASC
+        LIMIT $4
+      "#,
+    )
+    .bind(term_id)
+    .bind(guild_id.to_string())
+    .bind(crate::term_search::MIN_VECTOR_SIMILARITY)
+    .bind(limit)
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    let related = rows
+      .into_iter()
+      .map(|(id, term_name)| RelatedTerm { id, term_name })
+      .collect();
+
+    Ok(related)
+  }
+
+  /// Gets every term in a guild with all of its fields, for exporting the glossary.
+  pub async fn get_terms_for_export(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+  ) -> Result<Vec<Term>> {
+    let rows = sqlx::query!(
+      r#"
+        SELECT record_id, term_name, meaning, usage, links, category, aliases
+        FROM term
+        WHERE guild_id = $1
+        ORDER BY term_name ASC
+      "#,
+      guild_id.to_string(),
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    let terms = rows
+      .into_iter()
+      .map(|row| Term {
+        id: row.record_id,
+        term_name: row.term_name,
+        meaning: row.meaning,
+        usage: row.usage,
+        links: row.links,
+        category: row.category,
+        aliases: row.aliases,
+      })
+      .collect();
+
+    Ok(terms)
+  }
+
+  pub async fn unused_key_exists(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+  ) -> Result<bool> {
+    let row = sqlx::query!(
+      r#"
+        SELECT EXISTS(SELECT 1 FROM steamkey WHERE used = FALSE AND reserved IS NULL AND guild_id = $1)
+      "#,
+      guild_id.to_string(),
+    )
+    .fetch_one(&mut **transaction)
+    .await?;
+
+    Ok(row.exists.unwrap())
+  }
+
+  pub async fn reserve_key(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    user_id: &serenity::UserId,
+  ) -> Result<Option<String>> {
+    let row = sqlx::query!(
+      r#"
+        UPDATE steamkey SET reserved = $1 WHERE steam_key = (SELECT steam_key FROM steamkey WHERE used = FALSE AND reserved IS NULL AND guild_id = $2 ORDER BY RANDOM() LIMIT 1) RETURNING steam_key
+      "#,
+      user_id.to_string(),
+      guild_id.to_string(),
+    )
+    .fetch_optional(&mut **transaction)
+    .await?;
+
+    Ok(row.map(|row| row.steam_key))
+  }
+
+  pub async fn unreserve_key(
+    connection: &mut sqlx::pool::PoolConnection<sqlx::Postgres>,
+    key: &str,
+  ) -> Result<()> {
+    sqlx::query!(
+      r#"
+        UPDATE steamkey SET reserved = NULL WHERE steam_key = $1
+      "#,
+      key,
+    )
+    .execute(&mut **connection)
+    .await?;
+
+    Ok(())
+  }
+
+  pub async fn mark_key_used(
+    connection: &mut sqlx::pool::PoolConnection<sqlx::Postgres>,
+    key: &str,
+  ) -> Result<()> {
+    sqlx::query!(
+      r#"
+        UPDATE steamkey SET used = TRUE WHERE steam_key = $1
+      "#,
+      key,
+    )
+    .execute(&mut **connection)
+    .await?;
+
+    Ok(())
+  }
+
+  pub async fn get_key_and_mark_used(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+  ) -> Result<Option<String>> {
+    let row = sqlx::query!(
+      r#"
+        UPDATE steamkey SET used = TRUE WHERE steam_key = (SELECT steam_key FROM steamkey WHERE used = FALSE AND reserved IS NULL AND guild_id = $1 ORDER BY RANDOM() LIMIT 1) RETURNING steam_key
+      "#,
+      guild_id.to_string(),
+    )
+    .fetch_optional(&mut **transaction)
+    .await?;
+
+    Ok(row.map(|row| row.steam_key))
+  }
+
+  pub async fn get_random_quote(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+  ) -> Result<Option<QuoteData>> {
+    let row = sqlx::query!(
+      r#"
+        SELECT record_id, quote, author FROM quote WHERE guild_id = $1 ORDER BY RANDOM() LIMIT 1
       "#,
       guild_id.to_string(),
     )
@@ -1980,6 +3349,80 @@ impl DatabaseHandler {
     Ok(row.exists.unwrap())
   }
 
+  /// Records a revision with a snapshot of the term as it is now. Call it after adding or editing
+  /// a term, and before removing one.
+  pub async fn add_term_revision(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    term_name: &str,
+    action: TermRevisionAction,
+    editor_id: &serenity::UserId,
+    reverted_to: Option<i32>,
+  ) -> Result<()> {
+    sqlx::query!(
+      r#"
+        INSERT INTO term_revision (record_id, guild_id, term_name, revision_number, action, meaning, usage, links, category, aliases, editor_id, reverted_to)
+        SELECT $1, guild_id, term_name,
+          (SELECT COALESCE(MAX(revision_number), 0) + 1 FROM term_revision WHERE guild_id = $2 AND LOWER(term_name) = LOWER($3)),
+          $4, meaning, usage, links, category, aliases, $5, $6
+        FROM term
+        WHERE guild_id = $2 AND LOWER(term_name) = LOWER($3)
+      "#,
+      Ulid::new().to_string(),
+      guild_id.to_string(),
+      term_name,
+      action.as_str(),
+      editor_id.to_string(),
+      reverted_to,
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+  }
+
+  /// Returns the revisions of a term, oldest first.
+  pub async fn get_term_revisions(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    term_name: &str,
+  ) -> Result<Vec<TermRevision>> {
+    let rows = sqlx::query!(
+      r#"
+        SELECT revision_number, action, term_name, meaning, usage, links, category, aliases, editor_id, reverted_to, created_at
+        FROM term_revision
+        WHERE guild_id = $1 AND LOWER(term_name) = LOWER($2)
+        ORDER BY revision_number ASC
+      "#,
+      guild_id.to_string(),
+      term_name,
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    let revisions = rows
+      .into_iter()
+      .map(|row| TermRevision {
+        revision_number: row.revision_number,
+        action: TermRevisionAction::from_str(&row.action).unwrap_or(TermRevisionAction::Edit),
+        term_name: row.term_name,
+        meaning: row.meaning,
+        usage: row.usage,
+        links: row.links.unwrap_or_default(),
+        category: row.category,
+        aliases: row.aliases.unwrap_or_default(),
+        editor_id: row
+          .editor_id
+          .and_then(|editor_id| editor_id.parse::<u64>().ok())
+          .map(serenity::UserId::new),
+        reverted_to: row.reverted_to,
+        created_at: row.created_at,
+      })
+      .collect();
+
+    Ok(revisions)
+  }
+
   pub async fn remove_term(
     transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
     term_name: &str,
@@ -2303,14 +3746,16 @@ impl DatabaseHandler {
 
   pub async fn get_star_message_by_message_id(
     transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    board_id: &str,
     message_id: &serenity::MessageId,
   ) -> Result<Option<StarMessage>> {
     let row = sqlx::query!(
       r#"
-        SELECT record_id, starred_message_id, board_message_id, starred_channel_id
+        SELECT record_id, board_id, starred_message_id, board_message_id, starred_channel_id, star_count
         FROM "star"
-        WHERE starred_message_id = $1
+        WHERE board_id = $1 AND starred_message_id = $2
       "#,
+      board_id,
       message_id.to_string(),
     )
     .fetch_optional(&mut **transaction)
@@ -2319,9 +3764,15 @@ impl DatabaseHandler {
     let star_message = match row {
       Some(row) => Some(StarMessage {
         record_id: row.record_id,
-        starred_message_id: serenity::MessageId::new(row.starred_message_id.parse::<u64>().unwrap()),
+        board_id: row.board_id,
+        starred_message_id: serenity::MessageId::new(
+          row.starred_message_id.parse::<u64>().unwrap(),
+        ),
         board_message_id: serenity::MessageId::new(row.board_message_id.parse::<u64>().unwrap()),
-        starred_channel_id: serenity::ChannelId::new(row.starred_channel_id.parse::<u64>().unwrap()),
+        starred_channel_id: serenity::ChannelId::new(
+          row.starred_channel_id.parse::<u64>().unwrap(),
+        ),
+        star_count: row.star_count,
       }),
       None => None,
     };
@@ -2329,6 +3780,41 @@ impl DatabaseHandler {
     Ok(star_message)
   }
 
+  /// Returns the entries for a message on every board it has been posted to.
+  pub async fn get_star_messages_by_message_id(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    message_id: &serenity::MessageId,
+  ) -> Result<Vec<StarMessage>> {
+    let rows = sqlx::query!(
+      r#"
+        SELECT record_id, board_id, starred_message_id, board_message_id, starred_channel_id, star_count
+        FROM "star"
+        WHERE starred_message_id = $1
+      "#,
+      message_id.to_string(),
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    let star_messages = rows
+      .into_iter()
+      .map(|row| StarMessage {
+        record_id: row.record_id,
+        board_id: row.board_id,
+        starred_message_id: serenity::MessageId::new(
+          row.starred_message_id.parse::<u64>().unwrap(),
+        ),
+        board_message_id: serenity::MessageId::new(row.board_message_id.parse::<u64>().unwrap()),
+        starred_channel_id: serenity::ChannelId::new(
+          row.starred_channel_id.parse::<u64>().unwrap(),
+        ),
+        star_count: row.star_count,
+      })
+      .collect();
+
+    Ok(star_messages)
+  }
+
   pub async fn delete_star_message(
     transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
     record_id: &str,
@@ -2345,24 +3831,2334 @@ impl DatabaseHandler {
     Ok(())
   }
 
+  #[allow(clippy::too_many_arguments)]
   pub async fn insert_star_message(
     transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    board_id: &str,
+    guild_id: &serenity::GuildId,
     starred_message_id: &serenity::MessageId,
     board_message_id: &serenity::MessageId,
     starred_channel_id: &serenity::ChannelId,
+    author_id: &serenity::UserId,
+    star_count: i32,
   ) -> Result<()> {
     sqlx::query!(
       r#"
-        INSERT INTO "star" (record_id, starred_message_id, board_message_id, starred_channel_id) VALUES ($1, $2, $3, $4)
+        INSERT INTO "star" (record_id, board_id, guild_id, starred_message_id, board_message_id, starred_channel_id, author_id, star_count) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
       "#,
       Ulid::new().to_string(),
+      board_id,
+      guild_id.to_string(),
       starred_message_id.to_string(),
       board_message_id.to_string(),
       starred_channel_id.to_string(),
+      author_id.to_string(),
+      star_count,
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+  }
+
+  pub async fn update_star_count(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    record_id: &str,
+    star_count: i32,
+  ) -> Result<()> {
+    sqlx::query!(
+      r#"
+        UPDATE "star" SET star_count = $1, updated_at = NOW() WHERE record_id = $2
+      "#,
+      star_count,
+      record_id,
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+  }
+
+  /// Returns the starboard entries that were created before star stats were stored.
+  pub async fn get_unfilled_star_messages(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+  ) -> Result<Vec<StarMessage>> {
+    let rows = sqlx::query!(
+      r#"
+        SELECT record_id, board_id, starred_message_id, board_message_id, starred_channel_id, star_count
+        FROM "star"
+        WHERE guild_id IS NULL
+      "#,
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    let star_messages = rows
+      .into_iter()
+      .map(|row| StarMessage {
+        record_id: row.record_id,
+        board_id: row.board_id,
+        starred_message_id: serenity::MessageId::new(
+          row.starred_message_id.parse::<u64>().unwrap(),
+        ),
+        board_message_id: serenity::MessageId::new(row.board_message_id.parse::<u64>().unwrap()),
+        starred_channel_id: serenity::ChannelId::new(
+          row.starred_channel_id.parse::<u64>().unwrap(),
+        ),
+        star_count: row.star_count,
+      })
+      .collect();
+
+    Ok(star_messages)
+  }
+
+  pub async fn backfill_star_message(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    record_id: &str,
+    guild_id: &serenity::GuildId,
+    author_id: Option<&serenity::UserId>,
+    star_count: i32,
+    created_at: chrono::DateTime<Utc>,
+  ) -> Result<()> {
+    sqlx::query!(
+      r#"
+        UPDATE "star" SET guild_id = $1, author_id = $2, star_count = $3, created_at = $4, updated_at = NOW() WHERE record_id = $5
+      "#,
+      guild_id.to_string(),
+      author_id.map(|author_id| author_id.to_string()),
+      star_count,
+      created_at,
+      record_id,
     )
     .execute(&mut **transaction)
     .await?;
 
     Ok(())
   }
+
+  /// Returns the most-starred messages, optionally limited to those that reached the starboard
+  /// since the given time.
+  pub async fn get_top_star_messages(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    board_id: &str,
+    since: Option<chrono::DateTime<Utc>>,
+  ) -> Result<Vec<StarredMessage>> {
+    let rows = sqlx::query!(
+      r#"
+        SELECT starred_message_id, starred_channel_id, author_id, star_count
+        FROM "star"
+        WHERE guild_id = $1 AND board_id = $2 AND ($3::timestamptz IS NULL OR created_at >= $3)
+        ORDER BY star_count DESC, created_at DESC
+      "#,
+      guild_id.to_string(),
+      board_id,
+      since,
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    let star_messages = rows
+      .into_iter()
+      .enumerate()
+      .map(|(index, row)| StarredMessage {
+        rank: index + 1,
+        guild_id: *guild_id,
+        starred_message_id: serenity::MessageId::new(
+          row.starred_message_id.parse::<u64>().unwrap(),
+        ),
+        starred_channel_id: serenity::ChannelId::new(
+          row.starred_channel_id.parse::<u64>().unwrap(),
+        ),
+        author_id: row
+          .author_id
+          .map(|author_id| serenity::UserId::new(author_id.parse::<u64>().unwrap())),
+        star_count: row.star_count,
+      })
+      .collect();
+
+    Ok(star_messages)
+  }
+
+  /// Returns the members who received the most stars, optionally limited to messages that
+  /// reached the starboard since the given time.
+  pub async fn get_star_recipients(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    board_id: &str,
+    since: Option<chrono::DateTime<Utc>>,
+  ) -> Result<Vec<StarRecipient>> {
+    let rows = sqlx::query!(
+      r#"
+        SELECT author_id AS "author_id!", SUM(star_count) AS "total_stars!", COUNT(*) AS "message_count!"
+        FROM "star"
+        WHERE guild_id = $1 AND board_id = $2 AND author_id IS NOT NULL AND ($3::timestamptz IS NULL OR created_at >= $3)
+        GROUP BY author_id
+        ORDER BY "total_stars!" DESC, "message_count!" DESC
+      "#,
+      guild_id.to_string(),
+      board_id,
+      since,
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    let recipients = rows
+      .into_iter()
+      .enumerate()
+      .map(|(index, row)| StarRecipient {
+        rank: index + 1,
+        author_id: serenity::UserId::new(row.author_id.parse::<u64>().unwrap()),
+        total_stars: row.total_stars,
+        message_count: row.message_count,
+      })
+      .collect();
+
+    Ok(recipients)
+  }
+
+  pub async fn add_challenge(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    challenge: &ChallengeData,
+  ) -> Result<()> {
+    sqlx::query!(
+      r#"
+        INSERT INTO challenge (record_id, guild_id, challenge_name, start_date, end_date, goal_type, goal_target, reward_role) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+      "#,
+      challenge.id,
+      guild_id.to_string(),
+      challenge.challenge_name,
+      challenge.start_date,
+      challenge.end_date,
+      challenge.goal_type.as_str(),
+      challenge.goal_target,
+      challenge.reward_role.map(|role| role.to_string()),
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+  }
+
+  pub async fn edit_challenge(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    challenge: &ChallengeData,
+  ) -> Result<()> {
+    sqlx::query!(
+      r#"
+        UPDATE challenge SET start_date = $1, end_date = $2, goal_type = $3, goal_target = $4, reward_role = $5 WHERE record_id = $6
+      "#,
+      challenge.start_date,
+      challenge.end_date,
+      challenge.goal_type.as_str(),
+      challenge.goal_target,
+      challenge.reward_role.map(|role| role.to_string()),
+      challenge.id,
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+  }
+
+  pub async fn remove_challenge(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    challenge_id: &str,
+  ) -> Result<()> {
+    sqlx::query!(
+      r#"
+        DELETE FROM challenge WHERE record_id = $1
+      "#,
+      challenge_id,
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+  }
+
+  pub async fn get_challenge(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    challenge_name: &str,
+  ) -> Result<Option<ChallengeData>> {
+    let row = sqlx::query_as!(
+      ChallengeRow,
+      r#"
+        SELECT record_id, challenge_name, start_date, end_date, goal_type, goal_target, reward_role
+        FROM challenge
+        WHERE guild_id = $1 AND LOWER(challenge_name) = LOWER($2)
+      "#,
+      guild_id.to_string(),
+      challenge_name,
+    )
+    .fetch_optional(&mut **transaction)
+    .await?;
+
+    Ok(row.map(ChallengeData::from))
+  }
+
+  pub async fn get_all_challenges(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+  ) -> Result<Vec<ChallengeData>> {
+    let rows = sqlx::query_as!(
+      ChallengeRow,
+      r#"
+        SELECT record_id, challenge_name, start_date, end_date, goal_type, goal_target, reward_role
+        FROM challenge
+        WHERE guild_id = $1
+        ORDER BY start_date DESC
+      "#,
+      guild_id.to_string(),
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    Ok(rows.into_iter().map(ChallengeData::from).collect())
+  }
+
+  pub async fn get_open_challenges(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+  ) -> Result<Vec<ChallengeData>> {
+    let rows = sqlx::query_as!(
+      ChallengeRow,
+      r#"
+        SELECT record_id, challenge_name, start_date, end_date, goal_type, goal_target, reward_role
+        FROM challenge
+        WHERE guild_id = $1 AND end_date >= $2
+        ORDER BY start_date ASC
+      "#,
+      guild_id.to_string(),
+      Utc::now().date_naive(),
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    Ok(rows.into_iter().map(ChallengeData::from).collect())
+  }
+
+  pub async fn challenge_participant_exists(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    challenge_id: &str,
+    user_id: &serenity::UserId,
+  ) -> Result<bool> {
+    let row = sqlx::query!(
+      r#"
+        SELECT EXISTS(SELECT 1 FROM challenge_participant WHERE challenge_id = $1 AND user_id = $2)
+      "#,
+      challenge_id,
+      user_id.to_string(),
+    )
+    .fetch_one(&mut **transaction)
+    .await?;
+
+    Ok(row.exists.unwrap())
+  }
+
+  pub async fn join_challenge(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    user_id: &serenity::UserId,
+    challenge_id: &str,
+    team_id: Option<&str>,
+  ) -> Result<()> {
+    sqlx::query!(
+      r#"
+        INSERT INTO challenge_participant (record_id, challenge_id, user_id, guild_id, team_id) VALUES ($1, $2, $3, $4, $5)
+      "#,
+      Ulid::new().to_string(),
+      challenge_id,
+      user_id.to_string(),
+      guild_id.to_string(),
+      team_id,
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+  }
+
+  pub async fn leave_challenge(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    user_id: &serenity::UserId,
+    challenge_id: &str,
+  ) -> Result<()> {
+    sqlx::query!(
+      r#"
+        DELETE FROM challenge_participant WHERE challenge_id = $1 AND user_id = $2
+      "#,
+      challenge_id,
+      user_id.to_string(),
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+  }
+
+  pub async fn complete_challenge(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    user_id: &serenity::UserId,
+    challenge_id: &str,
+  ) -> Result<()> {
+    sqlx::query!(
+      r#"
+        UPDATE challenge_participant SET completed_at = NOW() WHERE challenge_id = $1 AND user_id = $2 AND completed_at IS NULL
+      "#,
+      challenge_id,
+      user_id.to_string(),
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+  }
+
+  pub async fn get_challenge_standings(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    challenge: &ChallengeData,
+  ) -> Result<Vec<ChallengeProgress>> {
+    let rows = sqlx::query!(
+      r#"
+        SELECT p.user_id, p.completed_at,
+          COALESCE(SUM(m.meditation_minutes), 0) AS "minutes!",
+          COUNT(m.record_id) AS "sessions!",
+          COUNT(DISTINCT m.occurred_at::date) AS "days!"
+        FROM challenge_participant p
+        LEFT JOIN meditation m ON m.user_id = p.user_id AND m.guild_id = p.guild_id AND m.occurred_at::date BETWEEN $2 AND $3
+        WHERE p.challenge_id = $1
+        GROUP BY p.user_id, p.completed_at
+      "#,
+      challenge.id,
+      challenge.start_date,
+      challenge.end_date,
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    let mut standings: Vec<ChallengeProgress> = rows
+      .into_iter()
+      .map(|row| ChallengeProgress {
+        user_id: serenity::UserId::new(row.user_id.parse::<u64>().unwrap()),
+        minutes: row.minutes,
+        sessions: row.sessions,
+        days: row.days,
+        completed_at: row.completed_at,
+      })
+      .collect();
+
+    standings.sort_by_key(|progress| std::cmp::Reverse(progress.value(challenge.goal_type)));
+
+    Ok(standings)
+  }
+
+  pub async fn get_user_challenges(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    user_id: &serenity::UserId,
+  ) -> Result<Vec<(ChallengeData, ChallengeProgress)>> {
+    let rows = sqlx::query!(
+      r#"
+        SELECT c.record_id, c.challenge_name, c.start_date, c.end_date, c.goal_type, c.goal_target, c.reward_role, p.user_id, p.completed_at,
+          COALESCE(SUM(m.meditation_minutes), 0) AS "minutes!",
+          COUNT(m.record_id) AS "sessions!",
+          COUNT(DISTINCT m.occurred_at::date) AS "days!"
+        FROM challenge_participant p
+        JOIN challenge c ON c.record_id = p.challenge_id
+        LEFT JOIN meditation m ON m.user_id = p.user_id AND m.guild_id = p.guild_id AND m.occurred_at::date BETWEEN c.start_date AND c.end_date
+        WHERE p.guild_id = $1 AND p.user_id = $2 AND c.end_date >= $3
+        GROUP BY c.record_id, p.user_id, p.completed_at
+        ORDER BY c.end_date ASC
+      "#,
+      guild_id.to_string(),
+      user_id.to_string(),
+      Utc::now().date_naive(),
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    let challenges = rows
+      .into_iter()
+      .map(|row| {
+        let challenge = ChallengeData::from(ChallengeRow {
+          record_id: row.record_id,
+          challenge_name: row.challenge_name,
+          start_date: row.start_date,
+          end_date: row.end_date,
+          goal_type: row.goal_type,
+          goal_target: row.goal_target,
+          reward_role: row.reward_role,
+        });
+        let progress = ChallengeProgress {
+          user_id: serenity::UserId::new(row.user_id.parse::<u64>().unwrap()),
+          minutes: row.minutes,
+          sessions: row.sessions,
+          days: row.days,
+          completed_at: row.completed_at,
+        };
+
+        (challenge, progress)
+      })
+      .collect();
+
+    Ok(challenges)
+  }
+
+  pub async fn add_challenge_team(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    challenge_id: &str,
+    team_name: &str,
+  ) -> Result<()> {
+    sqlx::query!(
+      r#"
+        INSERT INTO challenge_team (record_id, challenge_id, team_name) VALUES ($1, $2, $3)
+      "#,
+      Ulid::new().to_string(),
+      challenge_id,
+      team_name,
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+  }
+
+  pub async fn remove_challenge_team(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    team_id: &str,
+  ) -> Result<()> {
+    sqlx::query!(
+      r#"
+        DELETE FROM challenge_team WHERE record_id = $1
+      "#,
+      team_id,
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+  }
+
+  pub async fn get_challenge_team(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    challenge_id: &str,
+    team_name: &str,
+  ) -> Result<Option<ChallengeTeam>> {
+    let row = sqlx::query!(
+      r#"
+        SELECT record_id, team_name FROM challenge_team WHERE challenge_id = $1 AND LOWER(team_name) = LOWER($2)
+      "#,
+      challenge_id,
+      team_name,
+    )
+    .fetch_optional(&mut **transaction)
+    .await?;
+
+    Ok(row.map(|row| ChallengeTeam {
+      id: row.record_id,
+      team_name: row.team_name,
+    }))
+  }
+
+  pub async fn get_challenge_teams(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    challenge_id: &str,
+  ) -> Result<Vec<ChallengeTeam>> {
+    let rows = sqlx::query!(
+      r#"
+        SELECT record_id, team_name FROM challenge_team WHERE challenge_id = $1 ORDER BY team_name ASC
+      "#,
+      challenge_id,
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    Ok(
+      rows
+        .into_iter()
+        .map(|row| ChallengeTeam {
+          id: row.record_id,
+          team_name: row.team_name,
+        })
+        .collect(),
+    )
+  }
+
+  pub async fn get_team_standings(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    challenge: &ChallengeData,
+  ) -> Result<Vec<TeamStanding>> {
+    let rows = sqlx::query!(
+      r#"
+        SELECT t.record_id, t.team_name,
+          COUNT(DISTINCT p.user_id) AS "member_count!",
+          COALESCE(SUM(m.meditation_minutes), 0) AS "total_minutes!"
+        FROM challenge_team t
+        LEFT JOIN challenge_participant p ON p.team_id = t.record_id
+        LEFT JOIN meditation m ON m.user_id = p.user_id AND m.guild_id = p.guild_id AND m.occurred_at::date BETWEEN $2 AND $3
+        WHERE t.challenge_id = $1
+        GROUP BY t.record_id, t.team_name
+        ORDER BY "total_minutes!" DESC, t.team_name ASC
+      "#,
+      challenge.id,
+      challenge.start_date,
+      challenge.end_date,
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    Ok(
+      rows
+        .into_iter()
+        .map(|row| TeamStanding {
+          team_id: row.record_id,
+          team_name: row.team_name,
+          member_count: row.member_count,
+          total_minutes: row.total_minutes,
+        })
+        .collect(),
+    )
+  }
+
+  pub async fn get_team_results(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    challenge_id: &str,
+  ) -> Result<Vec<TeamStanding>> {
+    let rows = sqlx::query!(
+      r#"
+        SELECT r.team_id, t.team_name, r.member_count, r.total_minutes
+        FROM challenge_team_result r
+        JOIN challenge_team t ON t.record_id = r.team_id
+        WHERE r.challenge_id = $1
+        ORDER BY r.placement ASC
+      "#,
+      challenge_id,
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    Ok(
+      rows
+        .into_iter()
+        .map(|row| TeamStanding {
+          team_id: row.team_id,
+          team_name: row.team_name,
+          member_count: i64::from(row.member_count),
+          total_minutes: row.total_minutes,
+        })
+        .collect(),
+    )
+  }
+
+  /// Stores the final team standings and marks the challenge as finished so the results are only posted once.
+  pub async fn record_team_results(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    challenge_id: &str,
+    standings: &[TeamStanding],
+  ) -> Result<()> {
+    for (index, standing) in standings.iter().enumerate() {
+      sqlx::query!(
+        r#"
+          INSERT INTO challenge_team_result (record_id, challenge_id, team_id, placement, total_minutes, member_count) VALUES ($1, $2, $3, $4, $5, $6)
+          ON CONFLICT (challenge_id, team_id) DO UPDATE SET placement = EXCLUDED.placement, total_minutes = EXCLUDED.total_minutes, member_count = EXCLUDED.member_count
+        "#,
+        Ulid::new().to_string(),
+        challenge_id,
+        standing.team_id,
+        (index + 1) as i32,
+        standing.total_minutes,
+        standing.member_count as i32,
+      )
+      .execute(&mut **transaction)
+      .await?;
+    }
+
+    sqlx::query!(
+      r#"
+        UPDATE challenge SET results_posted_at = NOW() WHERE record_id = $1
+      "#,
+      challenge_id,
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+  }
+
+  pub async fn set_challenge_standings_message(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    challenge_id: &str,
+    channel_id: &serenity::ChannelId,
+    message_id: &serenity::MessageId,
+  ) -> Result<()> {
+    sqlx::query!(
+      r#"
+        UPDATE challenge SET standings_channel_id = $1, standings_message_id = $2 WHERE record_id = $3
+      "#,
+      channel_id.to_string(),
+      message_id.to_string(),
+      challenge_id,
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+  }
+
+  /// Gets every team challenge, across all guilds, that has started and has not had its results posted yet.
+  pub async fn get_live_team_challenges(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+  ) -> Result<Vec<LiveTeamChallenge>> {
+    let rows = sqlx::query!(
+      r#"
+        SELECT record_id, guild_id, challenge_name, start_date, end_date, goal_type, goal_target, reward_role, standings_channel_id, standings_message_id
+        FROM challenge c
+        WHERE results_posted_at IS NULL AND start_date <= $1 AND EXISTS (SELECT 1 FROM challenge_team t WHERE t.challenge_id = c.record_id)
+      "#,
+      Utc::now().date_naive(),
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    let challenges = rows
+      .into_iter()
+      .map(|row| LiveTeamChallenge {
+        guild_id: serenity::GuildId::new(row.guild_id.parse::<u64>().unwrap()),
+        challenge: ChallengeData::from(ChallengeRow {
+          record_id: row.record_id,
+          challenge_name: row.challenge_name,
+          start_date: row.start_date,
+          end_date: row.end_date,
+          goal_type: row.goal_type,
+          goal_target: row.goal_target,
+          reward_role: row.reward_role,
+        }),
+        standings_channel_id: row
+          .standings_channel_id
+          .map(|id| serenity::ChannelId::new(id.parse::<u64>().unwrap())),
+        standings_message_id: row
+          .standings_message_id
+          .map(|id| serenity::MessageId::new(id.parse::<u64>().unwrap())),
+      })
+      .collect();
+
+    Ok(challenges)
+  }
+
+  /// Whether two members are buddies or have a pending request between them. Requests expire
+  /// after 24 hours, even if the bot restarted before it could remove them.
+  pub async fn buddy_request_exists(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    user_id: &serenity::UserId,
+    buddy_id: &serenity::UserId,
+  ) -> Result<bool> {
+    let row = sqlx::query!(
+      r#"
+        SELECT EXISTS(
+          SELECT 1 FROM buddies
+          WHERE guild_id = $1 AND ((user_id = $2 AND buddy_id = $3) OR (user_id = $3 AND buddy_id = $2))
+          AND (status = 'accepted' OR created_at > NOW() - INTERVAL '24 hours')
+        )
+      "#,
+      guild_id.to_string(),
+      user_id.to_string(),
+      buddy_id.to_string(),
+    )
+    .fetch_one(&mut **transaction)
+    .await?;
+
+    Ok(row.exists.unwrap())
+  }
+
+  /// Adds a pending buddy request, replacing an expired request in the same direction.
+  pub async fn add_buddy_request(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    user_id: &serenity::UserId,
+    buddy_id: &serenity::UserId,
+  ) -> Result<()> {
+    sqlx::query!(
+      r#"
+        INSERT INTO buddies (record_id, guild_id, user_id, buddy_id) VALUES ($1, $2, $3, $4)
+        ON CONFLICT (guild_id, user_id, buddy_id) DO UPDATE SET created_at = NOW()
+        WHERE buddies.status = 'pending'
+      "#,
+      Ulid::new().to_string(),
+      guild_id.to_string(),
+      user_id.to_string(),
+      buddy_id.to_string(),
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+  }
+
+  /// Accepts a pending request from `user_id` to `buddy_id`, creating the matching row for the other direction.
+  /// Returns false if the request no longer exists or has expired.
+  pub async fn accept_buddy_request(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    user_id: &serenity::UserId,
+    buddy_id: &serenity::UserId,
+  ) -> Result<bool> {
+    let result = sqlx::query!(
+      r#"
+        UPDATE buddies SET status = 'accepted' WHERE guild_id = $1 AND user_id = $2 AND buddy_id = $3 AND status = 'pending'
+        AND created_at > NOW() - INTERVAL '24 hours'
+      "#,
+      guild_id.to_string(),
+      user_id.to_string(),
+      buddy_id.to_string(),
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    if result.rows_affected() == 0 {
+      return Ok(false);
+    }
+
+    sqlx::query!(
+      r#"
+        INSERT INTO buddies (record_id, guild_id, user_id, buddy_id, status) VALUES ($1, $2, $3, $4, 'accepted')
+        ON CONFLICT (guild_id, user_id, buddy_id) DO UPDATE SET status = 'accepted'
+      "#,
+      Ulid::new().to_string(),
+      guild_id.to_string(),
+      buddy_id.to_string(),
+      user_id.to_string(),
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(true)
+  }
+
+  /// Removes a buddy pairing or pending request in both directions.
+  pub async fn remove_buddy(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    user_id: &serenity::UserId,
+    buddy_id: &serenity::UserId,
+  ) -> Result<u64> {
+    let result = sqlx::query!(
+      r#"
+        DELETE FROM buddies WHERE guild_id = $1 AND ((user_id = $2 AND buddy_id = $3) OR (user_id = $3 AND buddy_id = $2))
+      "#,
+      guild_id.to_string(),
+      user_id.to_string(),
+      buddy_id.to_string(),
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(result.rows_affected())
+  }
+
+  pub async fn get_buddies(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    user_id: &serenity::UserId,
+  ) -> Result<Vec<Buddy>> {
+    let rows = sqlx::query!(
+      r#"
+        SELECT buddy_id, notifications FROM buddies WHERE guild_id = $1 AND user_id = $2 AND status = 'accepted' ORDER BY created_at ASC
+      "#,
+      guild_id.to_string(),
+      user_id.to_string(),
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    let buddies = rows
+      .into_iter()
+      .map(|row| Buddy {
+        buddy_id: serenity::UserId::new(row.buddy_id.parse::<u64>().unwrap()),
+        notifications: BuddyNotifications::from_str(&row.notifications)
+          .unwrap_or(BuddyNotifications::Off),
+      })
+      .collect();
+
+    Ok(buddies)
+  }
+
+  /// Sets how `user_id` is notified about a buddy's meditation. If no buddy is given, the setting applies to all of their buddies.
+  pub async fn set_buddy_notifications(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    user_id: &serenity::UserId,
+    buddy_id: Option<&serenity::UserId>,
+    notifications: BuddyNotifications,
+  ) -> Result<u64> {
+    let result = sqlx::query!(
+      r#"
+        UPDATE buddies SET notifications = $1, last_notified_at = NOW()
+        WHERE guild_id = $2 AND user_id = $3 AND status = 'accepted' AND ($4::text IS NULL OR buddy_id = $4)
+      "#,
+      notifications.as_str(),
+      guild_id.to_string(),
+      user_id.to_string(),
+      buddy_id.map(|buddy_id| buddy_id.to_string()),
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(result.rows_affected())
+  }
+
+  /// Gets the users who want to be nudged whenever `buddy_id` logs a session.
+  pub async fn get_buddies_to_nudge(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    buddy_id: &serenity::UserId,
+  ) -> Result<Vec<serenity::UserId>> {
+    let rows = sqlx::query!(
+      r#"
+        SELECT user_id FROM buddies WHERE guild_id = $1 AND buddy_id = $2 AND status = 'accepted' AND notifications = 'nudge'
+      "#,
+      guild_id.to_string(),
+      buddy_id.to_string(),
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    Ok(
+      rows
+        .into_iter()
+        .map(|row| serenity::UserId::new(row.user_id.parse::<u64>().unwrap()))
+        .collect(),
+    )
+  }
+
+  /// Gets every daily digest that is due, along with the buddy's meditation since the last digest.
+  pub async fn get_due_buddy_digests(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+  ) -> Result<Vec<BuddyDigest>> {
+    let rows = sqlx::query!(
+      r#"
+        SELECT b.record_id, b.guild_id, b.user_id, b.buddy_id,
+          COALESCE(SUM(m.meditation_minutes), 0) AS "minutes!",
+          COUNT(m.record_id) AS "sessions!",
+          COALESCE(BOOL_OR(t.stats_private OR t.anonymous_tracking), FALSE) AS "stats_private!"
+        FROM buddies b
+        LEFT JOIN meditation m ON m.guild_id = b.guild_id AND m.user_id = b.buddy_id AND m.occurred_at >= COALESCE(b.last_notified_at, NOW() - INTERVAL '1 day')
+        LEFT JOIN tracking_profile t ON t.guild_id = b.guild_id AND t.user_id = b.buddy_id
+        WHERE b.status = 'accepted' AND b.notifications = 'digest' AND (b.last_notified_at IS NULL OR b.last_notified_at <= NOW() - INTERVAL '1 day')
+        GROUP BY b.record_id
+      "#,
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    let digests = rows
+      .into_iter()
+      .map(|row| BuddyDigest {
+        record_id: row.record_id,
+        guild_id: serenity::GuildId::new(row.guild_id.parse::<u64>().unwrap()),
+        user_id: serenity::UserId::new(row.user_id.parse::<u64>().unwrap()),
+        buddy_id: serenity::UserId::new(row.buddy_id.parse::<u64>().unwrap()),
+        minutes: row.minutes,
+        sessions: row.sessions,
+        stats_private: row.stats_private,
+      })
+      .collect();
+
+    Ok(digests)
+  }
+
+  pub async fn mark_buddy_notified(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    record_id: &str,
+  ) -> Result<()> {
+    sqlx::query!(
+      r#"
+        UPDATE buddies SET last_notified_at = NOW() WHERE record_id = $1
+      "#,
+      record_id,
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+  }
+
+  pub async fn get_badge_stats(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    user_id: &serenity::UserId,
+  ) -> Result<BadgeStats> {
+    let meditation = sqlx::query!(
+      r#"
+        SELECT COUNT(record_id) AS "session_count!", COALESCE(MAX(meditation_minutes), 0) AS "longest_session!"
+        FROM meditation
+        WHERE guild_id = $1 AND user_id = $2
+      "#,
+      guild_id.to_string(),
+      user_id.to_string(),
+    )
+    .fetch_one(&mut **transaction)
+    .await?;
+
+    // Entries are stored in the user's local time, so the hour can be compared directly
+    let early_days = sqlx::query!(
+      r#"
+        SELECT DISTINCT occurred_at::date AS "day!"
+        FROM meditation
+        WHERE guild_id = $1 AND user_id = $2 AND EXTRACT(HOUR FROM occurred_at) < 7
+        ORDER BY "day!" ASC
+      "#,
+      guild_id.to_string(),
+      user_id.to_string(),
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    let challenges = sqlx::query!(
+      r#"
+        SELECT COUNT(record_id) AS "challenges_joined!" FROM challenge_participant WHERE guild_id = $1 AND user_id = $2
+      "#,
+      guild_id.to_string(),
+      user_id.to_string(),
+    )
+    .fetch_one(&mut **transaction)
+    .await?;
+
+    Ok(BadgeStats {
+      session_count: meditation.session_count,
+      longest_session: meditation.longest_session,
+      early_days: early_days.into_iter().map(|row| row.day).collect(),
+      challenges_joined: challenges.challenges_joined,
+    })
+  }
+
+  pub async fn get_user_badges(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    user_id: &serenity::UserId,
+  ) -> Result<Vec<UnlockedBadge>> {
+    let badges = sqlx::query_as!(
+      UnlockedBadge,
+      r#"
+        SELECT badge_key, unlocked_at FROM badge WHERE guild_id = $1 AND user_id = $2 ORDER BY unlocked_at ASC
+      "#,
+      guild_id.to_string(),
+      user_id.to_string(),
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    Ok(badges)
+  }
+
+  pub async fn add_user_badge(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    user_id: &serenity::UserId,
+    badge_key: &str,
+  ) -> Result<()> {
+    sqlx::query!(
+      r#"
+        INSERT INTO badge (record_id, guild_id, user_id, badge_key) VALUES ($1, $2, $3, $4)
+        ON CONFLICT (guild_id, user_id, badge_key) DO NOTHING
+      "#,
+      Ulid::new().to_string(),
+      guild_id.to_string(),
+      user_id.to_string(),
+      badge_key,
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+  }
+
+  pub async fn get_starboard_channel(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    channel_id: &serenity::ChannelId,
+  ) -> Result<Option<StarboardChannel>> {
+    let row = sqlx::query!(
+      r#"
+        SELECT channel_id, min_stars, excluded FROM starboard_channel WHERE guild_id = $1 AND channel_id = $2
+      "#,
+      guild_id.to_string(),
+      channel_id.to_string(),
+    )
+    .fetch_optional(&mut **transaction)
+    .await?;
+
+    let starboard_channel = row.map(|row| StarboardChannel {
+      channel_id: serenity::ChannelId::new(row.channel_id.parse::<u64>().unwrap()),
+      min_stars: row.min_stars,
+      excluded: row.excluded,
+    });
+
+    Ok(starboard_channel)
+  }
+
+  pub async fn get_starboard_channels(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+  ) -> Result<Vec<StarboardChannel>> {
+    let rows = sqlx::query!(
+      r#"
+        SELECT channel_id, min_stars, excluded FROM starboard_channel
+        WHERE guild_id = $1 AND (min_stars IS NOT NULL OR excluded)
+        ORDER BY excluded DESC, channel_id
+      "#,
+      guild_id.to_string(),
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    let starboard_channels = rows
+      .into_iter()
+      .map(|row| StarboardChannel {
+        channel_id: serenity::ChannelId::new(row.channel_id.parse::<u64>().unwrap()),
+        min_stars: row.min_stars,
+        excluded: row.excluded,
+      })
+      .collect();
+
+    Ok(starboard_channels)
+  }
+
+  /// Sets the minimum number of stars for a channel. Passing `None` reverts the channel to the
+  /// default threshold.
+  pub async fn set_starboard_threshold(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    channel_id: &serenity::ChannelId,
+    min_stars: Option<i32>,
+  ) -> Result<()> {
+    sqlx::query!(
+      r#"
+        INSERT INTO starboard_channel (record_id, guild_id, channel_id, min_stars) VALUES ($1, $2, $3, $4)
+        ON CONFLICT (guild_id, channel_id) DO UPDATE SET min_stars = $4
+      "#,
+      Ulid::new().to_string(),
+      guild_id.to_string(),
+      channel_id.to_string(),
+      min_stars,
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+  }
+
+  pub async fn set_starboard_excluded(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    channel_id: &serenity::ChannelId,
+    excluded: bool,
+  ) -> Result<()> {
+    sqlx::query!(
+      r#"
+        INSERT INTO starboard_channel (record_id, guild_id, channel_id, excluded) VALUES ($1, $2, $3, $4)
+        ON CONFLICT (guild_id, channel_id) DO UPDATE SET excluded = $4
+      "#,
+      Ulid::new().to_string(),
+      guild_id.to_string(),
+      channel_id.to_string(),
+      excluded,
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+  }
+
+  pub async fn add_reaction_board(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    board: &ReactionBoard,
+  ) -> Result<()> {
+    sqlx::query!(
+      r#"
+        INSERT INTO reaction_board (record_id, guild_id, board_name, emoji, channel_id, min_reactions, color, footer_label) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+      "#,
+      board.id,
+      guild_id.to_string(),
+      board.name,
+      board.emoji.to_string(),
+      board.channel_id.to_string(),
+      i32::try_from(board.min_reactions).unwrap_or(i32::MAX),
+      i32::try_from(board.color).ok(),
+      board.footer_label,
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+  }
+
+  /// Removes a board and its entries. Returns `false` if there was no board with that name.
+  pub async fn remove_reaction_board(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    board_name: &str,
+  ) -> Result<bool> {
+    let row = sqlx::query!(
+      r#"
+        DELETE FROM reaction_board WHERE guild_id = $1 AND LOWER(board_name) = LOWER($2) RETURNING record_id
+      "#,
+      guild_id.to_string(),
+      board_name,
+    )
+    .fetch_optional(&mut **transaction)
+    .await?;
+
+    match row {
+      Some(row) => {
+        sqlx::query!(
+          r#"
+            DELETE FROM "star" WHERE board_id = $1
+          "#,
+          row.record_id,
+        )
+        .execute(&mut **transaction)
+        .await?;
+
+        Ok(true)
+      }
+      None => Ok(false),
+    }
+  }
+
+  /// Returns the boards stored for a guild, which does not include the starboard.
+  pub async fn get_reaction_boards(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+  ) -> Result<Vec<ReactionBoard>> {
+    let rows = sqlx::query!(
+      r#"
+        SELECT record_id, board_name, emoji, channel_id, min_reactions, color, footer_label
+        FROM reaction_board
+        WHERE guild_id = $1
+        ORDER BY board_name
+      "#,
+      guild_id.to_string(),
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    let boards = rows
+      .into_iter()
+      .filter_map(|row| {
+        let emoji = match serenity::ReactionType::try_from(row.emoji.as_str()) {
+          Ok(emoji) => emoji,
+          Err(_) => {
+            warn!(
+              "Invalid emoji for reaction board {}: {}",
+              row.record_id, row.emoji
+            );
+            return None;
+          }
+        };
+
+        Some(ReactionBoard {
+          id: row.record_id,
+          name: row.board_name,
+          emoji,
+          channel_id: serenity::ChannelId::new(row.channel_id.parse::<u64>().unwrap()),
+          min_reactions: u64::try_from(row.min_reactions).unwrap_or(1),
+          color: row
+            .color
+            .and_then(|color| u32::try_from(color).ok())
+            .unwrap_or(crate::config::EMBED_COLOR),
+          footer_label: row.footer_label.unwrap_or("Times reacted".to_string()),
+        })
+      })
+      .collect();
+
+    Ok(boards)
+  }
+
+  pub async fn reaction_board_exists(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    board_name: &str,
+  ) -> Result<bool> {
+    let row = sqlx::query!(
+      r#"
+        SELECT EXISTS (SELECT 1 FROM reaction_board WHERE guild_id = $1 AND LOWER(board_name) = LOWER($2)) AS "exists!"
+      "#,
+      guild_id.to_string(),
+      board_name,
+    )
+    .fetch_one(&mut **transaction)
+    .await?;
+
+    Ok(row.exists)
+  }
+
+  /// Adds a report with the next case number for the guild. The reporter is recorded as the
+  /// case's first reporter. Returns `None` if another report of the same message opened a case
+  /// first, in which case the reporter should be added to that case instead. The case number
+  /// taken by the losing report is skipped.
+  #[allow(clippy::too_many_arguments)]
+  pub async fn add_report(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    reporter_id: &serenity::UserId,
+    author_id: &serenity::UserId,
+    channel_id: &serenity::ChannelId,
+    message_id: &serenity::MessageId,
+    message_content: &str,
+    source: &str,
+  ) -> Result<Option<ReportData>> {
+    let row = sqlx::query!(
+      r#"
+        WITH counter AS (
+          INSERT INTO report_case_counter (guild_id, last_number) VALUES ($2, 1)
+          ON CONFLICT (guild_id) DO UPDATE SET last_number = report_case_counter.last_number + 1
+          RETURNING last_number
+        )
+        INSERT INTO reports (record_id, guild_id, case_number, reporter_id, author_id, channel_id, message_id, message_content, source)
+        VALUES ($1, $2, (SELECT last_number FROM counter), $3, $4, $5, $6, $7, $8)
+        ON CONFLICT (guild_id, message_id) WHERE status IN ('open', 'claimed') DO NOTHING
+        RETURNING record_id
+      "#,
+      Ulid::new().to_string(),
+      guild_id.to_string(),
+      reporter_id.to_string(),
+      author_id.to_string(),
+      channel_id.to_string(),
+      message_id.to_string(),
+      message_content,
+      source,
+    )
+    .fetch_optional(&mut **transaction)
+    .await?;
+
+    let row = match row {
+      Some(row) => row,
+      None => return Ok(None),
+    };
+
+    DatabaseHandler::add_report_reporter(transaction, guild_id, &row.record_id, reporter_id)
+      .await?;
+
+    let report = DatabaseHandler::get_report(transaction, &row.record_id)
+      .await?
+      .with_context(|| "Failed to get report after adding it")?;
+
+    Ok(Some(report))
+  }
+
+  pub async fn get_report(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    record_id: &str,
+  ) -> Result<Option<ReportData>> {
+    let row = sqlx::query!(
+      r#"
+        SELECT record_id, guild_id, case_number, reporter_id, author_id, channel_id, message_id, message_content, source, status, assignee_id, resolution_notes, report_message_id, created_at,
+          (SELECT COUNT(*) FROM report_reporter WHERE report_id = reports.record_id) AS "reporter_count!"
+        FROM reports
+        WHERE record_id = $1
+      "#,
+      record_id,
+    )
+    .fetch_optional(&mut **transaction)
+    .await?;
+
+    let report = row.map(|row| ReportData {
+      id: row.record_id,
+      guild_id: serenity::GuildId::new(row.guild_id.parse::<u64>().unwrap()),
+      case_number: row.case_number,
+      reporter_id: serenity::UserId::new(row.reporter_id.parse::<u64>().unwrap()),
+      author_id: serenity::UserId::new(row.author_id.parse::<u64>().unwrap()),
+      channel_id: serenity::ChannelId::new(row.channel_id.parse::<u64>().unwrap()),
+      message_id: serenity::MessageId::new(row.message_id.parse::<u64>().unwrap()),
+      message_content: row.message_content,
+      source: row.source,
+      status: ReportStatus::from_str(&row.status).unwrap_or(ReportStatus::Open),
+      assignee_id: row
+        .assignee_id
+        .map(|assignee_id| serenity::UserId::new(assignee_id.parse::<u64>().unwrap())),
+      resolution_notes: row.resolution_notes,
+      report_message_id: row
+        .report_message_id
+        .map(|message_id| serenity::MessageId::new(message_id.parse::<u64>().unwrap())),
+      reporter_count: row.reporter_count,
+      created_at: row.created_at,
+    });
+
+    Ok(report)
+  }
+
+  /// Returns the open or claimed case for a message, if there is one.
+  pub async fn get_active_report_by_message(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    message_id: &serenity::MessageId,
+  ) -> Result<Option<ReportData>> {
+    let row = sqlx::query!(
+      r#"
+        SELECT record_id FROM reports
+        WHERE guild_id = $1 AND message_id = $2 AND status IN ('open', 'claimed')
+        ORDER BY case_number DESC
+        LIMIT 1
+      "#,
+      guild_id.to_string(),
+      message_id.to_string(),
+    )
+    .fetch_optional(&mut **transaction)
+    .await?;
+
+    match row {
+      Some(row) => DatabaseHandler::get_report(transaction, &row.record_id).await,
+      None => Ok(None),
+    }
+  }
+
+  /// Adds a reporter to a case. Returns `false` if they had already reported it.
+  pub async fn add_report_reporter(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    report_id: &str,
+    reporter_id: &serenity::UserId,
+  ) -> Result<bool> {
+    let result = sqlx::query!(
+      r#"
+        INSERT INTO report_reporter (record_id, guild_id, report_id, reporter_id) VALUES ($1, $2, $3, $4)
+        ON CONFLICT (report_id, reporter_id) DO NOTHING
+      "#,
+      Ulid::new().to_string(),
+      guild_id.to_string(),
+      report_id,
+      reporter_id.to_string(),
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(result.rows_affected() == 1)
+  }
+
+  /// Counts the reports a user has made since the given time, including reports merged into
+  /// existing cases.
+  pub async fn get_user_report_count_since(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    reporter_id: &serenity::UserId,
+    since: chrono::DateTime<Utc>,
+  ) -> Result<i64> {
+    let row = sqlx::query!(
+      r#"
+        SELECT COUNT(*) AS "count!" FROM report_reporter
+        WHERE guild_id = $1 AND reporter_id = $2 AND created_at >= $3
+      "#,
+      guild_id.to_string(),
+      reporter_id.to_string(),
+      since,
+    )
+    .fetch_one(&mut **transaction)
+    .await?;
+
+    Ok(row.count)
+  }
+
+  /// Returns the reports for a guild, newest first, optionally filtered by status.
+  pub async fn get_reports(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    status: Option<ReportStatus>,
+  ) -> Result<Vec<ReportData>> {
+    let rows = sqlx::query!(
+      r#"
+        SELECT record_id, guild_id, case_number, reporter_id, author_id, channel_id, message_id, message_content, source, status, assignee_id, resolution_notes, report_message_id, created_at,
+          (SELECT COUNT(*) FROM report_reporter WHERE report_id = reports.record_id) AS "reporter_count!"
+        FROM reports
+        WHERE guild_id = $1 AND ($2::text IS NULL OR status = $2)
+        ORDER BY case_number DESC
+      "#,
+      guild_id.to_string(),
+      status.map(|status| status.as_str()),
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    let reports = rows
+      .into_iter()
+      .map(|row| ReportData {
+        id: row.record_id,
+        guild_id: serenity::GuildId::new(row.guild_id.parse::<u64>().unwrap()),
+        case_number: row.case_number,
+        reporter_id: serenity::UserId::new(row.reporter_id.parse::<u64>().unwrap()),
+        author_id: serenity::UserId::new(row.author_id.parse::<u64>().unwrap()),
+        channel_id: serenity::ChannelId::new(row.channel_id.parse::<u64>().unwrap()),
+        message_id: serenity::MessageId::new(row.message_id.parse::<u64>().unwrap()),
+        message_content: row.message_content,
+        source: row.source,
+        status: ReportStatus::from_str(&row.status).unwrap_or(ReportStatus::Open),
+        assignee_id: row
+          .assignee_id
+          .map(|assignee_id| serenity::UserId::new(assignee_id.parse::<u64>().unwrap())),
+        resolution_notes: row.resolution_notes,
+        report_message_id: row
+          .report_message_id
+          .map(|message_id| serenity::MessageId::new(message_id.parse::<u64>().unwrap())),
+        reporter_count: row.reporter_count,
+        created_at: row.created_at,
+      })
+      .collect();
+
+    Ok(reports)
+  }
+
+  pub async fn set_report_message(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    record_id: &str,
+    report_message_id: &serenity::MessageId,
+  ) -> Result<()> {
+    sqlx::query!(
+      r#"
+        UPDATE reports SET report_message_id = $1 WHERE record_id = $2
+      "#,
+      report_message_id.to_string(),
+      record_id,
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+  }
+
+  pub async fn update_report_status(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    record_id: &str,
+    status: ReportStatus,
+    assignee_id: Option<&serenity::UserId>,
+    resolution_notes: Option<&str>,
+  ) -> Result<()> {
+    sqlx::query!(
+      r#"
+        UPDATE reports SET status = $1, assignee_id = $2, resolution_notes = $3, updated_at = NOW() WHERE record_id = $4
+      "#,
+      status.as_str(),
+      assignee_id.map(|assignee_id| assignee_id.to_string()),
+      resolution_notes,
+      record_id,
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+  }
+
+  /// Adds a case to the moderation log with the next case number for the guild.
+  #[allow(clippy::too_many_arguments)]
+  pub async fn add_mod_case(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    user_id: &serenity::UserId,
+    moderator_id: Option<&serenity::UserId>,
+    action: ModAction,
+    reason: Option<&str>,
+    reason_key: Option<&str>,
+    evidence_link: Option<&str>,
+    notification_method: Option<NotificationMethod>,
+    created_at: chrono::DateTime<Utc>,
+  ) -> Result<ModCase> {
+    let row = sqlx::query!(
+      r#"
+        WITH counter AS (
+          INSERT INTO mod_case_counter (guild_id, last_number) VALUES ($2, 1)
+          ON CONFLICT (guild_id) DO UPDATE SET last_number = mod_case_counter.last_number + 1
+          RETURNING last_number
+        )
+        INSERT INTO mod_case (record_id, guild_id, case_number, user_id, moderator_id, action, reason, reason_key, evidence_link, notification_method, created_at)
+        VALUES ($1, $2, (SELECT last_number FROM counter), $3, $4, $5, $6, $7, $8, $9, $10)
+        RETURNING record_id, case_number
+      "#,
+      Ulid::new().to_string(),
+      guild_id.to_string(),
+      user_id.to_string(),
+      moderator_id.map(|moderator_id| moderator_id.to_string()),
+      action.as_str(),
+      reason,
+      reason_key,
+      evidence_link,
+      notification_method.map(|method| method.as_str()),
+      created_at,
+    )
+    .fetch_one(&mut **transaction)
+    .await?;
+
+    Ok(ModCase {
+      id: row.record_id,
+      guild_id: *guild_id,
+      case_number: row.case_number,
+      user_id: *user_id,
+      moderator_id: moderator_id.copied(),
+      action,
+      reason: reason.map(str::to_string),
+      evidence_link: evidence_link.map(str::to_string),
+      notification_method,
+      created_at,
+    })
+  }
+
+  /// Returns the moderation log for a user, newest first, optionally filtered by action.
+  pub async fn get_mod_cases(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    user_id: &serenity::UserId,
+    action: Option<ModAction>,
+  ) -> Result<Vec<ModCase>> {
+    let rows = sqlx::query!(
+      r#"
+        SELECT record_id, case_number, user_id, moderator_id, action, reason, evidence_link, notification_method, created_at
+        FROM mod_case
+        WHERE guild_id = $1 AND user_id = $2 AND ($3::text IS NULL OR action = $3)
+        ORDER BY created_at DESC, case_number DESC
+      "#,
+      guild_id.to_string(),
+      user_id.to_string(),
+      action.map(|action| action.as_str()),
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    let cases = rows
+      .into_iter()
+      .map(|row| ModCase {
+        id: row.record_id,
+        guild_id: *guild_id,
+        case_number: row.case_number,
+        user_id: serenity::UserId::new(row.user_id.parse::<u64>().unwrap()),
+        moderator_id: row
+          .moderator_id
+          .map(|moderator_id| serenity::UserId::new(moderator_id.parse::<u64>().unwrap())),
+        action: ModAction::from_str(&row.action).unwrap_or(ModAction::Note),
+        reason: row.reason,
+        evidence_link: row.evidence_link,
+        notification_method: row
+          .notification_method
+          .and_then(|method| NotificationMethod::from_str(&method)),
+        created_at: row.created_at,
+      })
+      .collect();
+
+    Ok(cases)
+  }
+
+  /// Adds a reason template. Returns `false` if the guild already has one with that key.
+  pub async fn add_mod_reason(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    reason_key: &str,
+    reason_text: &str,
+    rule_link: Option<&str>,
+  ) -> Result<bool> {
+    let result = sqlx::query!(
+      r#"
+        INSERT INTO mod_reason (record_id, guild_id, reason_key, reason_text, rule_link) VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (guild_id, reason_key) DO NOTHING
+      "#,
+      Ulid::new().to_string(),
+      guild_id.to_string(),
+      reason_key,
+      reason_text,
+      rule_link,
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(result.rows_affected() == 1)
+  }
+
+  /// Updates the text and/or rule link of a reason template. Returns `false` if it doesn't exist.
+  pub async fn update_mod_reason(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    reason_key: &str,
+    reason_text: Option<&str>,
+    rule_link: Option<&str>,
+  ) -> Result<bool> {
+    let result = sqlx::query!(
+      r#"
+        UPDATE mod_reason SET reason_text = COALESCE($3, reason_text), rule_link = COALESCE($4, rule_link)
+        WHERE guild_id = $1 AND LOWER(reason_key) = LOWER($2)
+      "#,
+      guild_id.to_string(),
+      reason_key,
+      reason_text,
+      rule_link,
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(result.rows_affected() == 1)
+  }
+
+  /// Removes a reason template. Cases that used it keep their reason text.
+  pub async fn remove_mod_reason(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    reason_key: &str,
+  ) -> Result<bool> {
+    let result = sqlx::query!(
+      r#"
+        DELETE FROM mod_reason WHERE guild_id = $1 AND LOWER(reason_key) = LOWER($2)
+      "#,
+      guild_id.to_string(),
+      reason_key,
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(result.rows_affected() == 1)
+  }
+
+  pub async fn get_mod_reason(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    reason_key: &str,
+  ) -> Result<Option<ModReason>> {
+    let row = sqlx::query!(
+      r#"
+        SELECT record_id, reason_key, reason_text, rule_link,
+          (SELECT COUNT(*) FROM mod_case WHERE mod_case.guild_id = mod_reason.guild_id AND mod_case.reason_key = mod_reason.reason_key) AS "uses!"
+        FROM mod_reason
+        WHERE guild_id = $1 AND LOWER(reason_key) = LOWER($2)
+      "#,
+      guild_id.to_string(),
+      reason_key,
+    )
+    .fetch_optional(&mut **transaction)
+    .await?;
+
+    Ok(row.map(|row| ModReason {
+      id: row.record_id,
+      key: row.reason_key,
+      text: row.reason_text,
+      rule_link: row.rule_link,
+      uses: row.uses,
+    }))
+  }
+
+  /// Returns the reason templates for a guild, most used first. Uses can be limited to cases of
+  /// one action.
+  pub async fn get_mod_reasons(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    action: Option<ModAction>,
+  ) -> Result<Vec<ModReason>> {
+    let rows = sqlx::query!(
+      r#"
+        SELECT record_id, reason_key, reason_text, rule_link,
+          (SELECT COUNT(*) FROM mod_case WHERE mod_case.guild_id = mod_reason.guild_id AND mod_case.reason_key = mod_reason.reason_key AND ($2::text IS NULL OR mod_case.action = $2)) AS "uses!"
+        FROM mod_reason
+        WHERE guild_id = $1
+        ORDER BY "uses!" DESC, reason_key ASC
+      "#,
+      guild_id.to_string(),
+      action.map(|action| action.as_str()),
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    let reasons = rows
+      .into_iter()
+      .map(|row| ModReason {
+        id: row.record_id,
+        key: row.reason_key,
+        text: row.reason_text,
+        rule_link: row.rule_link,
+        uses: row.uses,
+      })
+      .collect();
+
+    Ok(reasons)
+  }
+
+  pub async fn get_mod_case(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    record_id: &str,
+  ) -> Result<Option<ModCase>> {
+    let row = sqlx::query!(
+      r#"
+        SELECT record_id, guild_id, case_number, user_id, moderator_id, action, reason, evidence_link, notification_method, created_at
+        FROM mod_case
+        WHERE record_id = $1
+      "#,
+      record_id,
+    )
+    .fetch_optional(&mut **transaction)
+    .await?;
+
+    let case = row.map(|row| ModCase {
+      id: row.record_id,
+      guild_id: serenity::GuildId::new(row.guild_id.parse::<u64>().unwrap()),
+      case_number: row.case_number,
+      user_id: serenity::UserId::new(row.user_id.parse::<u64>().unwrap()),
+      moderator_id: row
+        .moderator_id
+        .map(|moderator_id| serenity::UserId::new(moderator_id.parse::<u64>().unwrap())),
+      action: ModAction::from_str(&row.action).unwrap_or(ModAction::Note),
+      reason: row.reason,
+      evidence_link: row.evidence_link,
+      notification_method: row
+        .notification_method
+        .and_then(|method| NotificationMethod::from_str(&method)),
+      created_at: row.created_at,
+    });
+
+    Ok(case)
+  }
+
+  pub async fn set_mod_case_notification_method(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    record_id: &str,
+    notification_method: NotificationMethod,
+  ) -> Result<()> {
+    sqlx::query!(
+      r#"
+        UPDATE mod_case SET notification_method = $1 WHERE record_id = $2
+      "#,
+      notification_method.as_str(),
+      record_id,
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+  }
+
+  /// Adds an appeal for a case. Returns `None` if the case has already been appealed.
+  pub async fn add_appeal(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    case_id: &str,
+    user_id: &serenity::UserId,
+    appeal_text: &str,
+  ) -> Result<Option<AppealData>> {
+    let row = sqlx::query!(
+      r#"
+        INSERT INTO appeal (record_id, guild_id, case_id, user_id, appeal_text) VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (case_id) DO NOTHING
+        RETURNING record_id
+      "#,
+      Ulid::new().to_string(),
+      guild_id.to_string(),
+      case_id,
+      user_id.to_string(),
+      appeal_text,
+    )
+    .fetch_optional(&mut **transaction)
+    .await?;
+
+    match row {
+      Some(row) => DatabaseHandler::get_appeal(transaction, &row.record_id).await,
+      None => Ok(None),
+    }
+  }
+
+  pub async fn get_appeal(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    record_id: &str,
+  ) -> Result<Option<AppealData>> {
+    let row = sqlx::query!(
+      r#"
+        SELECT record_id, guild_id, case_id, user_id, appeal_text, status, reviewer_id, decision_notes, staff_message_id, created_at
+        FROM appeal
+        WHERE record_id = $1
+      "#,
+      record_id,
+    )
+    .fetch_optional(&mut **transaction)
+    .await?;
+
+    let appeal = row.map(|row| AppealData {
+      id: row.record_id,
+      guild_id: serenity::GuildId::new(row.guild_id.parse::<u64>().unwrap()),
+      case_id: row.case_id,
+      user_id: serenity::UserId::new(row.user_id.parse::<u64>().unwrap()),
+      appeal_text: row.appeal_text,
+      status: AppealStatus::from_str(&row.status).unwrap_or(AppealStatus::Pending),
+      reviewer_id: row
+        .reviewer_id
+        .map(|reviewer_id| serenity::UserId::new(reviewer_id.parse::<u64>().unwrap())),
+      decision_notes: row.decision_notes,
+      staff_message_id: row
+        .staff_message_id
+        .map(|message_id| serenity::MessageId::new(message_id.parse::<u64>().unwrap())),
+      created_at: row.created_at,
+    });
+
+    Ok(appeal)
+  }
+
+  pub async fn appeal_exists(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    case_id: &str,
+  ) -> Result<bool> {
+    let row = sqlx::query!(
+      r#"
+        SELECT EXISTS (SELECT 1 FROM appeal WHERE case_id = $1) AS "exists!"
+      "#,
+      case_id,
+    )
+    .fetch_one(&mut **transaction)
+    .await?;
+
+    Ok(row.exists)
+  }
+
+  pub async fn set_appeal_message(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    record_id: &str,
+    staff_message_id: &serenity::MessageId,
+  ) -> Result<()> {
+    sqlx::query!(
+      r#"
+        UPDATE appeal SET staff_message_id = $1 WHERE record_id = $2
+      "#,
+      staff_message_id.to_string(),
+      record_id,
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+  }
+
+  pub async fn update_appeal_status(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    record_id: &str,
+    status: AppealStatus,
+    reviewer_id: &serenity::UserId,
+    decision_notes: Option<&str>,
+  ) -> Result<()> {
+    sqlx::query!(
+      r#"
+        UPDATE appeal SET status = $1, reviewer_id = $2, decision_notes = $3, updated_at = NOW() WHERE record_id = $4
+      "#,
+      status.as_str(),
+      reviewer_id.to_string(),
+      decision_notes,
+      record_id,
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+  }
+
+  /// Adds a suggestion for a glossary term. Returns `None` if a suggestion for the same term is
+  /// already waiting for review.
+  pub async fn add_term_suggestion(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    term_name: &str,
+    meaning: Option<&str>,
+    suggester_id: &serenity::UserId,
+  ) -> Result<Option<TermSuggestion>> {
+    let row = sqlx::query!(
+      r#"
+        INSERT INTO term_suggestion (record_id, guild_id, term_name, meaning, suggester_id) VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (guild_id, LOWER(term_name)) WHERE status = 'pending' DO NOTHING
+        RETURNING record_id
+      "#,
+      Ulid::new().to_string(),
+      guild_id.to_string(),
+      term_name,
+      meaning,
+      suggester_id.to_string(),
+    )
+    .fetch_optional(&mut **transaction)
+    .await?;
+
+    match row {
+      Some(row) => DatabaseHandler::get_term_suggestion(transaction, &row.record_id).await,
+      None => Ok(None),
+    }
+  }
+
+  pub async fn get_term_suggestion(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    record_id: &str,
+  ) -> Result<Option<TermSuggestion>> {
+    let row = sqlx::query!(
+      r#"
+        SELECT record_id, guild_id, term_name, meaning, suggester_id, status, reviewer_id, decision_notes, created_at
+        FROM term_suggestion
+        WHERE record_id = $1
+      "#,
+      record_id,
+    )
+    .fetch_optional(&mut **transaction)
+    .await?;
+
+    let suggestion = row.map(|row| TermSuggestion {
+      id: row.record_id,
+      guild_id: serenity::GuildId::new(row.guild_id.parse::<u64>().unwrap()),
+      term_name: row.term_name,
+      meaning: row.meaning,
+      suggester_id: serenity::UserId::new(row.suggester_id.parse::<u64>().unwrap()),
+      status: SuggestionStatus::from_str(&row.status).unwrap_or(SuggestionStatus::Pending),
+      reviewer_id: row
+        .reviewer_id
+        .map(|reviewer_id| serenity::UserId::new(reviewer_id.parse::<u64>().unwrap())),
+      decision_notes: row.decision_notes,
+      created_at: row.created_at,
+    });
+
+    Ok(suggestion)
+  }
+
+  pub async fn set_term_suggestion_message(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    record_id: &str,
+    staff_message_id: &serenity::MessageId,
+  ) -> Result<()> {
+    sqlx::query!(
+      r#"
+        UPDATE term_suggestion SET staff_message_id = $1 WHERE record_id = $2
+      "#,
+      staff_message_id.to_string(),
+      record_id,
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+  }
+
+  /// Records the decision on a suggestion. Returns `false` if the suggestion was already decided.
+  pub async fn decide_term_suggestion(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    record_id: &str,
+    status: SuggestionStatus,
+    reviewer_id: &serenity::UserId,
+    decision_notes: Option<&str>,
+  ) -> Result<bool> {
+    let result = sqlx::query!(
+      r#"
+        UPDATE term_suggestion SET status = $1, reviewer_id = $2, decision_notes = $3, updated_at = NOW()
+        WHERE record_id = $4 AND status = 'pending'
+      "#,
+      status.as_str(),
+      reviewer_id.to_string(),
+      decision_notes,
+      record_id,
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+  }
+
+  pub async fn set_term_of_the_day_channel(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    channel_id: &serenity::ChannelId,
+  ) -> Result<()> {
+    sqlx::query!(
+      r#"
+        INSERT INTO term_of_the_day_channel (guild_id, channel_id) VALUES ($1, $2)
+        ON CONFLICT (guild_id) DO UPDATE SET channel_id = EXCLUDED.channel_id
+      "#,
+      guild_id.to_string(),
+      channel_id.to_string(),
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+  }
+
+  /// Stops posting a term of the day in a guild. Returns whether it was being posted.
+  pub async fn remove_term_of_the_day_channel(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+  ) -> Result<bool> {
+    let result = sqlx::query!(
+      r#"
+        DELETE FROM term_of_the_day_channel WHERE guild_id = $1
+      "#,
+      guild_id.to_string(),
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+  }
+
+  pub async fn get_term_of_the_day_channel(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+  ) -> Result<Option<serenity::ChannelId>> {
+    let row = sqlx::query!(
+      r#"
+        SELECT channel_id FROM term_of_the_day_channel WHERE guild_id = $1
+      "#,
+      guild_id.to_string(),
+    )
+    .fetch_optional(&mut **transaction)
+    .await?;
+
+    Ok(row.map(|row| serenity::ChannelId::new(row.channel_id.parse::<u64>().unwrap())))
+  }
+
+  /// Gets the guilds that haven't had a term of the day posted yet today, in UTC.
+  pub async fn get_due_terms_of_the_day(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+  ) -> Result<Vec<TermOfTheDayChannel>> {
+    let rows = sqlx::query!(
+      r#"
+        SELECT guild_id, channel_id
+        FROM term_of_the_day_channel AS channel
+        WHERE NOT EXISTS (
+          SELECT 1 FROM term_of_the_day AS posted
+          WHERE posted.guild_id = channel.guild_id AND NOT posted.skipped
+          AND posted.posted_at >= DATE_TRUNC('day', NOW() AT TIME ZONE 'UTC') AT TIME ZONE 'UTC'
+        )
+      "#,
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    let channels = rows
+      .into_iter()
+      .map(|row| TermOfTheDayChannel {
+        guild_id: serenity::GuildId::new(row.guild_id.parse::<u64>().unwrap()),
+        channel_id: serenity::ChannelId::new(row.channel_id.parse::<u64>().unwrap()),
+      })
+      .collect();
+
+    Ok(channels)
+  }
+
+  /// Gets the current pass through the glossary, starting from 1.
+  pub async fn get_term_of_the_day_cycle(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+  ) -> Result<i32> {
+    let row = sqlx::query!(
+      r#"
+        SELECT COALESCE(MAX(cycle), 1) AS "cycle!" FROM term_of_the_day WHERE guild_id = $1
+      "#,
+      guild_id.to_string(),
+    )
+    .fetch_one(&mut **transaction)
+    .await?;
+
+    Ok(row.cycle)
+  }
+
+  /// Gets the number of terms posted or skipped in a cycle, and the number of terms in the
+  /// glossary.
+  pub async fn get_term_of_the_day_progress(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    cycle: i32,
+  ) -> Result<(i64, i64)> {
+    let row = sqlx::query!(
+      r#"
+        SELECT
+          (SELECT COUNT(*) FROM term
+            WHERE guild_id = $1 AND EXISTS (
+              SELECT 1 FROM term_of_the_day AS posted
+              WHERE posted.guild_id = term.guild_id AND posted.cycle = $2
+              AND LOWER(posted.term_name) = LOWER(term.term_name)
+            )) AS "done!",
+          (SELECT COUNT(*) FROM term WHERE guild_id = $1) AS "total!"
+      "#,
+      guild_id.to_string(),
+      cycle,
+    )
+    .fetch_one(&mut **transaction)
+    .await?;
+
+    Ok((row.done, row.total))
+  }
+
+  /// Picks a random term that hasn't been posted or skipped in a cycle.
+  pub async fn get_random_unposted_term(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    cycle: i32,
+  ) -> Result<Option<Term>> {
+    let row = sqlx::query!(
+      r#"
+        SELECT record_id, term_name, meaning, usage, links, category, aliases
+        FROM term
+        WHERE guild_id = $1 AND NOT EXISTS (
+          SELECT 1 FROM term_of_the_day AS posted
+          WHERE posted.guild_id = term.guild_id AND posted.cycle = $2
+          AND LOWER(posted.term_name) = LOWER(term.term_name)
+        )
+        ORDER BY RANDOM()
+        LIMIT 1
+      "#,
+      guild_id.to_string(),
+      cycle,
+    )
+    .fetch_optional(&mut **transaction)
+    .await?;
+
+    let term = row.map(|row| Term {
+      id: row.record_id,
+      term_name: row.term_name,
+      meaning: row.meaning,
+      usage: row.usage,
+      links: row.links,
+      category: row.category,
+      aliases: row.aliases,
+    });
+
+    Ok(term)
+  }
+
+  /// Records a term as posted or skipped in a cycle. Returns the record ID, so that the message
+  /// of a posted term can be added once it has been sent.
+  pub async fn add_term_of_the_day(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    term_name: &str,
+    cycle: i32,
+    skipped: bool,
+  ) -> Result<String> {
+    let row = sqlx::query!(
+      r#"
+        INSERT INTO term_of_the_day (record_id, guild_id, term_name, cycle, skipped) VALUES ($1, $2, $3, $4, $5)
+        RETURNING record_id
+      "#,
+      Ulid::new().to_string(),
+      guild_id.to_string(),
+      term_name,
+      cycle,
+      skipped,
+    )
+    .fetch_one(&mut **transaction)
+    .await?;
+
+    Ok(row.record_id)
+  }
+
+  pub async fn set_term_of_the_day_message(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    record_id: &str,
+    message_id: &serenity::MessageId,
+  ) -> Result<()> {
+    sqlx::query!(
+      r#"
+        UPDATE term_of_the_day SET message_id = $1 WHERE record_id = $2
+      "#,
+      message_id.to_string(),
+      record_id,
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+  }
+
+  /// Whether a term has been posted or skipped in a cycle.
+  pub async fn term_of_the_day_done(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    term_name: &str,
+    cycle: i32,
+  ) -> Result<bool> {
+    let row = sqlx::query!(
+      r#"
+        SELECT EXISTS(
+          SELECT 1 FROM term_of_the_day
+          WHERE guild_id = $1 AND LOWER(term_name) = LOWER($2) AND cycle = $3
+        ) AS "exists!"
+      "#,
+      guild_id.to_string(),
+      term_name,
+      cycle,
+    )
+    .fetch_one(&mut **transaction)
+    .await?;
+
+    Ok(row.exists)
+  }
+
+  /// Queues a term to be posted as a term of the day before any randomly chosen term. Returns
+  /// false if it is already queued.
+  pub async fn add_term_of_the_day_pin(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    term_name: &str,
+    pinned_by: &serenity::UserId,
+  ) -> Result<bool> {
+    let result = sqlx::query!(
+      r#"
+        INSERT INTO term_of_the_day_pin (record_id, guild_id, term_name, pinned_by) VALUES ($1, $2, $3, $4)
+        ON CONFLICT (guild_id, LOWER(term_name)) DO NOTHING
+      "#,
+      Ulid::new().to_string(),
+      guild_id.to_string(),
+      term_name,
+      pinned_by.to_string(),
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+  }
+
+  /// Returns whether the term was queued.
+  pub async fn remove_term_of_the_day_pin(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+    term_name: &str,
+  ) -> Result<bool> {
+    let result = sqlx::query!(
+      r#"
+        DELETE FROM term_of_the_day_pin WHERE guild_id = $1 AND LOWER(term_name) = LOWER($2)
+      "#,
+      guild_id.to_string(),
+      term_name,
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+  }
+
+  /// Gets the queued terms that are still in the glossary, in the order they will be posted.
+  pub async fn get_term_of_the_day_pins(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &serenity::GuildId,
+  ) -> Result<Vec<Term>> {
+    let rows = sqlx::query!(
+      r#"
+        SELECT term.record_id, term.term_name, term.meaning, term.usage, term.links, term.category, term.aliases
+        FROM term_of_the_day_pin AS pin
+        JOIN term ON term.guild_id = pin.guild_id AND LOWER(term.term_name) = LOWER(pin.term_name)
+        WHERE pin.guild_id = $1
+        ORDER BY pin.pinned_at ASC
+      "#,
+      guild_id.to_string(),
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    let terms = rows
+      .into_iter()
+      .map(|row| Term {
+        id: row.record_id,
+        term_name: row.term_name,
+        meaning: row.meaning,
+        usage: row.usage,
+        links: row.links,
+        category: row.category,
+        aliases: row.aliases,
+      })
+      .collect();
+
+    Ok(terms)
+  }
 }
@@ -1,32 +1,371 @@
+use crate::config::TERM_EMBEDDING_TEMPLATE;
+use crate::database::{DatabaseHandler, SearchVector, TermEmbedding};
 use anyhow::{Context, Result};
 use async_openai::{
   config::OpenAIConfig,
   types::{CreateEmbeddingRequest, EmbeddingInput},
   Client,
 };
+use async_trait::async_trait;
+use hashlink::LruCache;
+use log::{info, warn};
 use poise::serenity_prelude as serenity;
 use std::env;
+use std::sync::{Arc, Mutex};
 
-pub struct OpenAIHandler {
+/// The size of the vectors created by [`HashingProvider`].
+const HASHING_DIMENSIONS: usize = 1536;
+
+/// Turns text into vectors for semantic search.
+#[async_trait]
+pub trait EmbeddingProvider: Send + Sync {
+  /// A short name for the provider, used in logs and configuration.
+  fn name(&self) -> &'static str;
+
+  /// The model the provider uses to create embeddings.
+  fn model(&self) -> &str;
+
+  async fn create_embedding(&self, input: String, user: serenity::UserId) -> Result<Vec<f32>>;
+}
+
+/// Selects the embedding provider with the `EMBEDDINGS_PROVIDER` environment variable, which
+/// can be `openai`, `hashing` or `none`. When it isn't set, OpenAI is used if `OPENAI_API_KEY` is
+/// set. Returns `None` if no provider is available, in which case semantic search is disabled.
+pub fn provider_from_env() -> Option<Arc<dyn EmbeddingProvider>> {
+  let provider = env::var("EMBEDDINGS_PROVIDER").ok();
+
+  let provider: Result<Option<Arc<dyn EmbeddingProvider>>> = match provider.as_deref() {
+    Some("openai") => OpenAIProvider::new().map(|provider| Some(Arc::new(provider) as _)),
+    Some("hashing") => Ok(Some(Arc::new(HashingProvider))),
+    Some("none") => Ok(None),
+    Some(other) => Err(anyhow::anyhow!("Unknown embeddings provider: {}", other)),
+    None => match env::var("OPENAI_API_KEY") {
+      Ok(_) => OpenAIProvider::new().map(|provider| Some(Arc::new(provider) as _)),
+      Err(_) => Ok(None),
+    },
+  };
+
+  match provider {
+    Ok(Some(provider)) => {
+      info!(
+        "Using {} embeddings with model {}",
+        provider.name(),
+        provider.model()
+      );
+      Some(provider)
+    }
+    Ok(None) => {
+      warn!("No embeddings provider configured, glossary search will only match names and aliases");
+      None
+    }
+    Err(e) => {
+      warn!(
+        "Could not set up embeddings provider, glossary search will only match names and aliases: {:?}",
+        e
+      );
+      None
+    }
+  }
+}
+
+/// The fields of a glossary term that go into its embedding.
+pub struct TermText<'a> {
+  pub term_name: &'a str,
+  pub meaning: &'a str,
+  pub usage: Option<&'a str>,
+  pub category: Option<&'a str>,
+  pub aliases: &'a [String],
+}
+
+impl TermText<'_> {
+  /// Renders [`TERM_EMBEDDING_TEMPLATE`] for the term.
+  pub fn render(&self) -> String {
+    let aliases = self.aliases.join(", ");
+    let values = [
+      ("{term}", self.term_name),
+      ("{meaning}", self.meaning),
+      ("{aliases}", aliases.as_str()),
+      ("{usage}", self.usage.unwrap_or_default()),
+      ("{category}", self.category.unwrap_or_default()),
+    ];
+
+    TERM_EMBEDDING_TEMPLATE
+      .lines()
+      .filter_map(|line| {
+        let placeholders: Vec<&(&str, &str)> = values
+          .iter()
+          .filter(|(placeholder, _)| line.contains(placeholder))
+          .collect();
+
+        if !placeholders.is_empty()
+          && placeholders
+            .iter()
+            .all(|(_, value)| value.trim().is_empty())
+        {
+          return None;
+        }
+
+        Some(
+          placeholders
+            .iter()
+            .fold(line.to_string(), |line, (placeholder, value)| {
+              line.replace(placeholder, value.trim())
+            }),
+        )
+      })
+      .collect::<Vec<String>>()
+      .join("\n")
+  }
+}
+
+/// Creates the embedding for a glossary term from [`TERM_EMBEDDING_TEMPLATE`], along with the
+/// model, size and input hash stored next to it.
+pub async fn embed_term(
+  provider: &dyn EmbeddingProvider,
+  term: &TermText<'_>,
+  user: serenity::UserId,
+) -> Result<TermEmbedding> {
+  let input = term.render();
+  let input_hash = input_hash(&input);
+  let vector = provider.create_embedding(input, user).await?;
+
+  Ok(TermEmbedding {
+    dimensions: vector.len() as i32,
+    vector: pgvector::Vector::from(vector),
+    model: provider.model().to_string(),
+    input_hash,
+  })
+}
+
+/// Where the embedding of a search query came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheSource {
+  Memory,
+  Database,
+  Provider,
+}
+
+/// Lookup counts for the query embedding cache.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CacheStats {
+  pub memory_hits: u64,
+  pub database_hits: u64,
+  pub misses: u64,
+}
+
+impl CacheStats {
+  pub fn lookups(&self) -> u64 {
+    self.memory_hits + self.database_hits + self.misses
+  }
+
+  /// The share of lookups, from 0 to 1, that didn't need the embeddings provider.
+  pub fn hit_rate(&self) -> f64 {
+    match self.lookups() {
+      0 => 0.0,
+      lookups => (self.memory_hits + self.database_hits) as f64 / lookups as f64,
+    }
+  }
+}
+
+/// Caches the embeddings of search queries by normalized query and model. Recently used queries
+/// are kept in memory, in front of the `query_embedding` table, which persists across restarts.
+pub struct QueryEmbeddingCache {
+  entries: Mutex<LruCache<(String, String), pgvector::Vector>>,
+  stats: Mutex<CacheStats>,
+}
+
+impl QueryEmbeddingCache {
+  pub fn new(capacity: usize) -> Self {
+    Self {
+      entries: Mutex::new(LruCache::new(capacity)),
+      stats: Mutex::new(CacheStats::default()),
+    }
+  }
+
+  fn get(&self, key: &(String, String)) -> Option<pgvector::Vector> {
+    self.entries.lock().unwrap().get(key).cloned()
+  }
+
+  fn insert(&self, key: (String, String), vector: pgvector::Vector) {
+    self.entries.lock().unwrap().insert(key, vector);
+  }
+
+  fn record(&self, source: CacheSource) -> CacheStats {
+    let mut stats = self.stats.lock().unwrap();
+    match source {
+      CacheSource::Memory => stats.memory_hits += 1,
+      CacheSource::Database => stats.database_hits += 1,
+      CacheSource::Provider => stats.misses += 1,
+    }
+    *stats
+  }
+}
+
+/// Normalizes a search query for caching, so that differences in case and spacing share an
+/// embedding.
+fn normalize_query(search: &str) -> String {
+  search
+    .split_whitespace()
+    .collect::<Vec<&str>>()
+    .join(" ")
+    .to_lowercase()
+}
+
+/// Looks up a cached query embedding in the database. Errors are logged rather than returned,
+/// since the provider can always be asked instead.
+async fn get_persisted_embedding(
+  db: &DatabaseHandler,
+  query_text: &str,
+  model: &str,
+) -> Option<pgvector::Vector> {
+  let result = async {
+    let mut transaction = db.start_transaction_with_retry(5).await?;
+    let vector = DatabaseHandler::get_query_embedding(&mut transaction, query_text, model).await?;
+    DatabaseHandler::commit_transaction(transaction).await?;
+    Ok::<_, anyhow::Error>(vector)
+  }
+  .await;
+
+  match result {
+    Ok(vector) => vector,
+    Err(e) => {
+      warn!("Could not read query embedding cache: {:?}", e);
+      None
+    }
+  }
+}
+
+async fn persist_embedding(
+  db: &DatabaseHandler,
+  query_text: &str,
+  model: &str,
+  vector: pgvector::Vector,
+) -> Result<()> {
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  DatabaseHandler::add_query_embedding(&mut transaction, query_text, model, vector).await?;
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  Ok(())
+}
+
+/// Embeds a glossary search, using the query embedding cache when the same query was searched
+/// before. Returns `None` when there is no provider or the provider fails, so that the search can
+/// still match term names and aliases.
+pub async fn embed_search(
+  db: &DatabaseHandler,
+  cache: &QueryEmbeddingCache,
+  provider: Option<&Arc<dyn EmbeddingProvider>>,
+  search: &str,
+  user: serenity::UserId,
+) -> Option<SearchVector> {
+  let provider = provider?;
+  let model = provider.model().to_string();
+  let key = (normalize_query(search), model.clone());
+
+  let (vector, source) = match cache.get(&key) {
+    Some(vector) => (vector, CacheSource::Memory),
+    None => match get_persisted_embedding(db, &key.0, &model).await {
+      Some(vector) => {
+        cache.insert(key.clone(), vector.clone());
+        (vector, CacheSource::Database)
+      }
+      None => match provider.create_embedding(key.0.clone(), user).await {
+        Ok(vector) => {
+          let vector = pgvector::Vector::from(vector);
+          if let Err(e) = persist_embedding(db, &key.0, &model, vector.clone()).await {
+            warn!("Could not save query embedding: {:?}", e);
+          }
+          cache.insert(key.clone(), vector.clone());
+          (vector, CacheSource::Provider)
+        }
+        Err(e) => {
+          warn!("Could not embed glossary search: {:?}", e);
+          return None;
+        }
+      },
+    },
+  };
+
+  let stats = cache.record(source);
+  info!(
+    "Query embedding from {:?} (hit rate {:.1}% over {} lookups: {} memory, {} database, {} misses)",
+    source,
+    stats.hit_rate() * 100.0,
+    stats.lookups(),
+    stats.memory_hits,
+    stats.database_hits,
+    stats.misses
+  );
+
+  Some(SearchVector { vector, model })
+}
+
+/// Returns the hash stored with a term's embedding, used to tell whether the term has changed
+/// since it was embedded.
+pub fn term_input_hash(term: &TermText<'_>) -> String {
+  input_hash(&term.render())
+}
+
+fn input_hash(input: &str) -> String {
+  format!("{:016x}", fnv1a(input))
+}
+
+/// FNV-1a, used instead of the standard library hasher because its output must not change
+/// between Rust versions, or stored hashes and embeddings would stop matching new ones.
+fn fnv1a(input: &str) -> u64 {
+  input.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+    (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+  })
+}
+
+pub struct OpenAIProvider {
   client: Client<OpenAIConfig>,
+  model: String,
+  dimensions: Option<u32>,
 }
 
-impl OpenAIHandler {
+impl OpenAIProvider {
   pub fn new() -> Result<Self> {
     let api_key =
       env::var("OPENAI_API_KEY").with_context(|| "Missing OPENAI_API_KEY environment variable")?;
     let config = OpenAIConfig::new();
     let config = config.with_api_key(api_key);
     let client = Client::with_config(config);
+    let model = env::var("OPENAI_EMBEDDING_MODEL").unwrap_or("text-embedding-ada-002".to_string());
+    // Only newer models support choosing the size of their embeddings
+    let dimensions = match env::var("OPENAI_EMBEDDING_DIMENSIONS") {
+      Ok(dimensions) => Some(
+        dimensions
+          .parse::<u32>()
+          .with_context(|| "Invalid OPENAI_EMBEDDING_DIMENSIONS environment variable")?,
+      ),
+      Err(_) => None,
+    };
+
+    Ok(Self {
+      client,
+      model,
+      dimensions,
+    })
+  }
+}
+
+#[async_trait]
+impl EmbeddingProvider for OpenAIProvider {
+  fn name(&self) -> &'static str {
+    "openai"
+  }
 
-    Ok(Self { client })
+  fn model(&self) -> &str {
+    &self.model
   }
 
-  pub async fn create_embedding(&self, input: String, user: serenity::UserId) -> Result<Vec<f32>> {
+  async fn create_embedding(&self, input: String, user: serenity::UserId) -> Result<Vec<f32>> {
     let input = CreateEmbeddingRequest {
-      model: "text-embedding-ada-002".to_string(),
+      model: self.model.clone(),
       input: EmbeddingInput::String(input),
       user: Some(user.to_string()),
+      dimensions: self.dimensions,
       ..Default::default()
     };
 
@@ -45,3 +384,119 @@ impl OpenAIHandler {
     Ok(embedding)
   }
 }
+
+/// A deterministic, offline embedder that hashes words and character trigrams into a fixed-size
+/// vector. It only captures lexical overlap, not meaning, but needs no network access or model
+/// files, which makes it useful for development and tests.
+pub struct HashingProvider;
+
+impl HashingProvider {
+  fn embed(input: &str) -> Vec<f32> {
+    let mut vector = vec![0.0f32; HASHING_DIMENSIONS];
+    let input = input.to_lowercase();
+
+    for word in input
+      .split(|c: char| !c.is_alphanumeric())
+      .filter(|w| !w.is_empty())
+    {
+      let mut features = vec![format!("w:{}", word)];
+      let padded: Vec<char> = format!(" {} ", word).chars().collect();
+      features.extend(
+        padded
+          .windows(3)
+          .map(|trigram| format!("t:{}", trigram.iter().collect::<String>())),
+      );
+
+      for feature in features {
+        let index = (fnv1a(&feature) % HASHING_DIMENSIONS as u64) as usize;
+        // Take the sign from a second hash, so it is independent of the index and collisions tend
+        // to cancel out
+        let sign = match fnv1a(&format!("sign:{}", feature)) >> 63 {
+          0 => 1.0,
+          _ => -1.0,
+        };
+        vector[index] += sign;
+      }
+    }
+
+    let norm = vector.iter().map(|value| value * value).sum::<f32>().sqrt();
+    if norm > 0.0 {
+      for value in vector.iter_mut() {
+        *value /= norm;
+      }
+    }
+
+    vector
+  }
+}
+
+#[async_trait]
+impl EmbeddingProvider for HashingProvider {
+  fn name(&self) -> &'static str {
+    "hashing"
+  }
+
+  fn model(&self) -> &str {
+    "fnv-trigram-v2"
+  }
+
+  async fn create_embedding(&self, input: String, _user: serenity::UserId) -> Result<Vec<f32>> {
+    Ok(HashingProvider::embed(&input))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn hashing_embeddings_are_deterministic() {
+    assert_eq!(
+      HashingProvider::embed("Loving-kindness meditation"),
+      HashingProvider::embed("Loving-kindness meditation")
+    );
+    assert_eq!(
+      HashingProvider::embed("Metta"),
+      HashingProvider::embed("metta")
+    );
+  }
+
+  #[test]
+  fn hashing_embeddings_have_the_configured_dimensions() {
+    assert_eq!(HashingProvider::embed("metta").len(), HASHING_DIMENSIONS);
+    assert_eq!(HashingProvider::embed("").len(), HASHING_DIMENSIONS);
+  }
+
+  #[test]
+  fn hashing_embeddings_are_normalized() {
+    for input in ["metta", "loving-kindness meditation", "a b c a b c"] {
+      let norm = HashingProvider::embed(input)
+        .iter()
+        .map(|value| value * value)
+        .sum::<f32>()
+        .sqrt();
+      assert!(
+        (norm - 1.0).abs() < 1e-5,
+        "norm of {:?} was {}",
+        input,
+        norm
+      );
+    }
+
+    // Input without any words has nothing to normalize
+    assert!(HashingProvider::embed(" !? ")
+      .iter()
+      .all(|value| *value == 0.0));
+  }
+
+  #[test]
+  fn hashing_embeddings_of_similar_text_are_closer() {
+    let cosine = |a: &[f32], b: &[f32]| a.iter().zip(b).map(|(a, b)| a * b).sum::<f32>();
+    let query = HashingProvider::embed("breathing meditation");
+
+    assert!(
+      cosine(&query, &HashingProvider::embed("breath meditation"))
+        > cosine(&query, &HashingProvider::embed("walking retreat"))
+    );
+  }
+}
@@ -0,0 +1,48 @@
+use crate::appeals;
+use crate::reports;
+use crate::term_suggestions;
+use crate::Data;
+use anyhow::Result;
+use poise::serenity_prelude::{Context, Interaction};
+
+/// Handles interactions that must keep working after a restart, such as the buttons on report
+/// embeds, appeals and glossary suggestions. Short-lived buttons are handled by collectors in their commands instead.
+pub async fn interaction_create(
+  ctx: &Context,
+  data: &Data,
+  interaction: &Interaction,
+) -> Result<()> {
+  match interaction {
+    Interaction::Component(press) if press.data.custom_id.starts_with(reports::REPORT_PREFIX) => {
+      reports::handle_button(ctx, &data.db, press).await?;
+    }
+    Interaction::Modal(submit) if submit.data.custom_id.starts_with(reports::REPORT_PREFIX) => {
+      reports::handle_modal(ctx, &data.db, submit).await?;
+    }
+    Interaction::Component(press) if press.data.custom_id.starts_with(appeals::APPEAL_PREFIX) => {
+      appeals::handle_button(ctx, &data.db, press).await?;
+    }
+    Interaction::Modal(submit) if submit.data.custom_id.starts_with(appeals::APPEAL_PREFIX) => {
+      appeals::handle_modal(ctx, &data.db, submit).await?;
+    }
+    Interaction::Component(press)
+      if press
+        .data
+        .custom_id
+        .starts_with(term_suggestions::SUGGESTION_PREFIX) =>
+    {
+      term_suggestions::handle_button(ctx, data, press).await?;
+    }
+    Interaction::Modal(submit)
+      if submit
+        .data
+        .custom_id
+        .starts_with(term_suggestions::SUGGESTION_PREFIX) =>
+    {
+      term_suggestions::handle_modal(ctx, data, submit).await?;
+    }
+    _ => {}
+  }
+
+  Ok(())
+}
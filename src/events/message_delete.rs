@@ -1,22 +1,28 @@
 use crate::database::DatabaseHandler;
+use crate::reaction_boards;
 use anyhow::Result;
 use poise::serenity_prelude::{self as serenity};
 
 pub async fn message_delete(
+  ctx: &serenity::Context,
   database: &DatabaseHandler,
+  guild_id: &Option<serenity::GuildId>,
   deleted_message_id: &serenity::MessageId,
 ) -> Result<()> {
-  let mut transaction = database.start_transaction().await?;
+  reaction_boards::remove_deleted_message(ctx, database, *guild_id, *deleted_message_id).await?;
 
-  let star_message =
-    DatabaseHandler::get_star_message_by_message_id(&mut transaction, deleted_message_id).await?;
+  Ok(())
+}
 
-  if let Some(star_message) = star_message {
-    let star_message_id = star_message.record_id;
-    DatabaseHandler::delete_star_message(&mut transaction, &star_message_id).await?;
+pub async fn message_delete_bulk(
+  ctx: &serenity::Context,
+  database: &DatabaseHandler,
+  guild_id: &Option<serenity::GuildId>,
+  deleted_message_ids: &[serenity::MessageId],
+) -> Result<()> {
+  for deleted_message_id in deleted_message_ids {
+    reaction_boards::remove_deleted_message(ctx, database, *guild_id, *deleted_message_id).await?;
   }
 
-  transaction.commit().await?;
-
   Ok(())
 }
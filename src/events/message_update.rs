@@ -0,0 +1,14 @@
+use crate::database::DatabaseHandler;
+use crate::reaction_boards;
+use anyhow::Result;
+use poise::serenity_prelude::{self as serenity};
+
+pub async fn message_update(
+  ctx: &serenity::Context,
+  database: &DatabaseHandler,
+  event: &serenity::MessageUpdateEvent,
+) -> Result<()> {
+  reaction_boards::refresh_board_messages(ctx, database, event.channel_id, event.id).await?;
+
+  Ok(())
+}
@@ -1,13 +1,17 @@
 // mod guild_member_addition;
 mod guild_member_removal;
 mod guild_member_update;
+mod interaction_create;
 mod message_delete;
+mod message_update;
 mod reaction_add;
 mod reaction_remove;
 
 // pub use guild_member_addition::guild_member_addition;
 pub use guild_member_removal::guild_member_removal;
 pub use guild_member_update::guild_member_update;
-pub use message_delete::message_delete;
+pub use interaction_create::interaction_create;
+pub use message_delete::{message_delete, message_delete_bulk};
+pub use message_update::message_update;
 pub use reaction_add::reaction_add;
-pub use reaction_remove::reaction_remove;
+pub use reaction_remove::{reaction_remove, reaction_remove_all, reaction_remove_emoji};
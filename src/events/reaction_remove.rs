@@ -1,62 +1,67 @@
-use crate::config::{self, EMOTES};
 use crate::database::DatabaseHandler;
+use crate::reaction_boards;
 use anyhow::Result;
-use poise::serenity_prelude::{builder::*, ChannelId, Context, Reaction, ReactionType};
+use poise::serenity_prelude::{ChannelId, Context, MessageId, Reaction};
 
 pub async fn reaction_remove(
   ctx: &Context,
   database: &DatabaseHandler,
   remove_reaction: &Reaction,
 ) -> Result<()> {
-  remove_star(ctx, database, remove_reaction).await?;
+  let guild_id = match remove_reaction.guild_id {
+    Some(guild_id) => guild_id,
+    None => return Ok(()),
+  };
+
+  reaction_boards::update_board_messages(
+    ctx,
+    database,
+    guild_id,
+    remove_reaction.channel_id,
+    remove_reaction.message_id,
+    Some(&remove_reaction.emoji),
+  )
+  .await?;
 
   Ok(())
 }
 
-async fn remove_star(ctx: &Context, database: &DatabaseHandler, reaction: &Reaction) -> Result<()> {
-  if let ReactionType::Unicode(emoji) = &reaction.emoji {
-    if emoji == EMOTES.star {
-      let mut transaction = database.start_transaction().await?;
-      let star_message =
-        DatabaseHandler::get_star_message_by_message_id(&mut transaction, &reaction.message_id)
-          .await?;
-
-      if let Some(star_message) = star_message {
-        let star_count = reaction
-          .message(&ctx)
-          .await?
-          .reactions
-          .iter()
-          .find(|r| r.reaction_type == ReactionType::Unicode(EMOTES.star.to_string()))
-          .map(|r| r.count)
-          .unwrap_or(0);
-
-        let starboard_channel = ChannelId::new(config::CHANNELS.starchannel);
-
-        if star_count >= config::MIN_STARS {
-          // Get the starboard message
-          let mut starboard_message = starboard_channel
-            .message(&ctx, star_message.board_message_id)
-            .await?;
-
-          let existing_embed = starboard_message.embeds.get(0).unwrap();
-          let updated_embed = CreateEmbed::from(existing_embed.clone()).footer(
-            CreateEmbedFooter::new(format!("⭐ Times starred: {}", star_count)),
-          );
-
-          starboard_message
-            .edit(ctx, EditMessage::new().embed(updated_embed))
-            .await?;
-        } else {
-          starboard_channel
-            .delete_message(&ctx, star_message.board_message_id)
-            .await?;
-          DatabaseHandler::delete_star_message(&mut transaction, &star_message.record_id).await?;
-          transaction.commit().await?;
-        }
-      }
-    }
-  }
+pub async fn reaction_remove_all(
+  ctx: &Context,
+  database: &DatabaseHandler,
+  channel_id: &ChannelId,
+  message_id: &MessageId,
+) -> Result<()> {
+  let guild_id = match channel_id.to_channel(ctx).await?.guild() {
+    Some(channel) => channel.guild_id,
+    None => return Ok(()),
+  };
+
+  reaction_boards::update_board_messages(ctx, database, guild_id, *channel_id, *message_id, None)
+    .await?;
+
+  Ok(())
+}
+
+pub async fn reaction_remove_emoji(
+  ctx: &Context,
+  database: &DatabaseHandler,
+  removed_reactions: &Reaction,
+) -> Result<()> {
+  let guild_id = match removed_reactions.guild_id {
+    Some(guild_id) => guild_id,
+    None => return Ok(()),
+  };
+
+  reaction_boards::update_board_messages(
+    ctx,
+    database,
+    guild_id,
+    removed_reactions.channel_id,
+    removed_reactions.message_id,
+    Some(&removed_reactions.emoji),
+  )
+  .await?;
 
   Ok(())
 }
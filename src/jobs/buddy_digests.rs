@@ -0,0 +1,62 @@
+use crate::database::{BuddyDigest, DatabaseHandler};
+use anyhow::Result;
+use log::error;
+use poise::serenity_prelude::{self as serenity, builder::*};
+
+/// Sends the daily digest of a buddy's meditation to everyone who chose digests in
+/// `/buddy notifications`. Digests are skipped when the buddy has not meditated, and leave out
+/// the buddy's minutes when their stats are private.
+pub async fn send_buddy_digests(ctx: &serenity::Context, db: &DatabaseHandler) -> Result<()> {
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  let digests = DatabaseHandler::get_due_buddy_digests(&mut transaction).await?;
+  drop(transaction);
+
+  for digest in digests {
+    if digest.sessions > 0 {
+      send_buddy_digest(ctx, &digest).await;
+    }
+
+    // Each digest is marked in its own transaction, so DMs aren't sent with one held open
+    let mut transaction = db.start_transaction_with_retry(5).await?;
+    DatabaseHandler::mark_buddy_notified(&mut transaction, &digest.record_id).await?;
+    DatabaseHandler::commit_transaction(transaction).await?;
+  }
+
+  Ok(())
+}
+
+async fn send_buddy_digest(ctx: &serenity::Context, digest: &BuddyDigest) {
+  let buddy_name = match digest.guild_id.member(ctx, digest.buddy_id).await {
+    Ok(member) => member.display_name().to_string(),
+    Err(_) => digest.buddy_id.to_string(),
+  };
+
+  let sessions = format!(
+    "{} {}",
+    digest.sessions,
+    if digest.sessions == 1 {
+      "session"
+    } else {
+      "sessions"
+    }
+  );
+
+  let content = match digest.stats_private {
+    true => format!(
+      ":sunrise: Daily buddy digest: **{}** logged {} since your last digest. Keep each other going!",
+      buddy_name, sessions
+    ),
+    false => format!(
+      ":sunrise: Daily buddy digest: **{}** logged {} for a total of {} minutes since your last digest. Keep each other going!",
+      buddy_name, sessions, digest.minutes
+    ),
+  };
+
+  if let Err(err) = digest
+    .user_id
+    .direct_message(ctx, CreateMessage::new().content(content))
+    .await
+  {
+    error!("Error sending buddy digest: {}", err);
+  }
+}
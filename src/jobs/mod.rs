@@ -0,0 +1,107 @@
+use crate::config::{
+  BUDDY_DIGEST_INTERVAL, TEAM_STANDINGS_INTERVAL, TERM_OF_THE_DAY_INTERVAL, TERM_REINDEX_INTERVAL,
+  WEEKLY_RECAP_INTERVAL,
+};
+use crate::database::DatabaseHandler;
+use crate::embeddings::EmbeddingProvider;
+use log::error;
+use poise::serenity_prelude as serenity;
+use std::sync::Arc;
+use std::time::Duration;
+
+mod buddy_digests;
+mod star_backfill;
+mod team_challenges;
+mod term_of_the_day;
+pub mod term_reindex;
+mod weekly_recaps;
+
+/// Starts the background jobs. The recurring jobs each run on their own interval, and errors
+/// are logged rather than stopping the job.
+pub fn spawn(
+  ctx: serenity::Context,
+  db: DatabaseHandler,
+  embeddings: Option<Arc<dyn EmbeddingProvider>>,
+) {
+  let backfill_ctx = ctx.clone();
+  let backfill_db = db.clone();
+  tokio::spawn(async move {
+    if let Err(err) = star_backfill::backfill_star_messages(&backfill_ctx, &backfill_db).await {
+      error!("Error backfilling starboard entries: {}", err);
+    }
+  });
+
+  let team_ctx = ctx.clone();
+  let team_db = db.clone();
+  tokio::spawn(async move {
+    let mut interval = tokio::time::interval(Duration::from_secs(TEAM_STANDINGS_INTERVAL));
+
+    loop {
+      interval.tick().await;
+
+      if let Err(err) = team_challenges::update_team_challenges(&team_ctx, &team_db).await {
+        error!("Error updating team challenges: {}", err);
+      }
+    }
+  });
+
+  let digest_ctx = ctx.clone();
+  let digest_db = db.clone();
+  tokio::spawn(async move {
+    let mut interval = tokio::time::interval(Duration::from_secs(BUDDY_DIGEST_INTERVAL));
+
+    loop {
+      interval.tick().await;
+
+      if let Err(err) = buddy_digests::send_buddy_digests(&digest_ctx, &digest_db).await {
+        error!("Error sending buddy digests: {}", err);
+      }
+    }
+  });
+
+  let term_ctx = ctx.clone();
+  let term_db = db.clone();
+  tokio::spawn(async move {
+    let mut interval = tokio::time::interval(Duration::from_secs(TERM_OF_THE_DAY_INTERVAL));
+
+    loop {
+      interval.tick().await;
+
+      if let Err(err) = term_of_the_day::post_terms_of_the_day(&term_ctx, &term_db).await {
+        error!("Error posting terms of the day: {}", err);
+      }
+    }
+  });
+
+  // Without an embeddings provider there is nothing to reindex with
+  if let Some(provider) = embeddings {
+    let reindex_user = ctx.cache.current_user().id;
+    let reindex_db = db.clone();
+    tokio::spawn(async move {
+      let mut interval = tokio::time::interval(Duration::from_secs(TERM_REINDEX_INTERVAL));
+
+      loop {
+        interval.tick().await;
+
+        if let Err(err) =
+          term_reindex::reindex_terms(&reindex_db, provider.as_ref(), None, false, reindex_user)
+            .await
+        {
+          error!("Error reindexing glossary terms: {}", err);
+        }
+      }
+    });
+  }
+
+  tokio::spawn(async move {
+    let mut interval = tokio::time::interval(Duration::from_secs(WEEKLY_RECAP_INTERVAL));
+
+    loop {
+      interval.tick().await;
+
+      if let Err(err) = weekly_recaps::send_weekly_recaps(&ctx, &db).await {
+        error!("Error sending weekly recaps: {}", err);
+      }
+    }
+  });
+}
@@ -0,0 +1,110 @@
+use crate::database::{DatabaseHandler, ReactionBoard};
+use crate::reaction_boards;
+use anyhow::{Context as AnyhowContext, Result};
+use log::{error, info};
+use poise::serenity_prelude::{self as serenity, Message, UserId};
+
+/// Reads the star count from the footer of a starboard message.
+fn parse_star_count(board_message: &Message) -> Option<i32> {
+  board_message
+    .embeds
+    .first()?
+    .footer
+    .as_ref()?
+    .text
+    .rsplit(' ')
+    .next()?
+    .parse::<i32>()
+    .ok()
+}
+
+/// Reads the author of a starred message from the avatar on the starboard embed, for when the
+/// starred message itself has been deleted. Default avatars don't include the user ID.
+fn parse_author_id(board_message: &Message) -> Option<UserId> {
+  let icon_url = board_message
+    .embeds
+    .first()?
+    .author
+    .as_ref()?
+    .icon_url
+    .as_ref()?;
+  let user_id = icon_url.split("/avatars/").nth(1)?.split('/').next()?;
+
+  user_id.parse::<u64>().ok().map(UserId::new)
+}
+
+/// Fills in the guild, author, star count and post time of starboard entries that were created
+/// before these were stored, using the starboard messages and the starred messages where they
+/// still exist.
+pub async fn backfill_star_messages(ctx: &serenity::Context, db: &DatabaseHandler) -> Result<()> {
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  let star_messages = DatabaseHandler::get_unfilled_star_messages(&mut transaction).await?;
+  drop(transaction);
+
+  if star_messages.is_empty() {
+    return Ok(());
+  }
+
+  info!("Backfilling {} starboard entries", star_messages.len());
+
+  let starboard = ReactionBoard::starboard();
+  let starboard_channel = starboard.channel_id;
+  let guild_id = starboard_channel
+    .to_channel(ctx)
+    .await?
+    .guild()
+    .with_context(|| "Starboard channel is not in a guild")?
+    .guild_id;
+
+  for star_message in star_messages {
+    let board_message = match starboard_channel
+      .message(ctx, star_message.board_message_id)
+      .await
+    {
+      Ok(board_message) => board_message,
+      Err(err) => {
+        error!(
+          "Error fetching starboard message {} for backfill: {}",
+          star_message.board_message_id, err
+        );
+        continue;
+      }
+    };
+
+    let starred_message = star_message
+      .starred_channel_id
+      .message(ctx, star_message.starred_message_id)
+      .await
+      .ok();
+
+    let (author_id, star_count) = match &starred_message {
+      Some(starred_message) => (
+        Some(starred_message.author.id),
+        match reaction_boards::count_reactions(ctx, starred_message, &starboard.emoji).await {
+          Ok(star_count) => i32::try_from(star_count).ok(),
+          Err(_) => parse_star_count(&board_message),
+        },
+      ),
+      None => (
+        parse_author_id(&board_message),
+        parse_star_count(&board_message),
+      ),
+    };
+
+    let mut transaction = db.start_transaction_with_retry(5).await?;
+    DatabaseHandler::backfill_star_message(
+      &mut transaction,
+      &star_message.record_id,
+      &guild_id,
+      author_id.as_ref(),
+      star_count.unwrap_or(0),
+      *board_message.timestamp,
+    )
+    .await?;
+    DatabaseHandler::commit_transaction(transaction).await?;
+  }
+
+  info!("Finished backfilling starboard entries");
+
+  Ok(())
+}
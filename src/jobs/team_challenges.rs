@@ -0,0 +1,81 @@
+use crate::commands::challenge::team_standings_embed;
+use crate::config::CHANNELS;
+use crate::database::{DatabaseHandler, LiveTeamChallenge};
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use log::{error, info};
+use poise::serenity_prelude::{self as serenity, builder::*};
+
+/// Refreshes the standings message of every running team challenge, and posts the final
+/// results of team challenges that have ended.
+pub async fn update_team_challenges(ctx: &serenity::Context, db: &DatabaseHandler) -> Result<()> {
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  let challenges = DatabaseHandler::get_live_team_challenges(&mut transaction).await?;
+  drop(transaction);
+
+  for live in challenges {
+    if let Err(err) = update_team_challenge(ctx, db, &live).await {
+      error!(
+        "Error updating team challenge {}: {}",
+        live.challenge.challenge_name, err
+      );
+    }
+  }
+
+  Ok(())
+}
+
+async fn update_team_challenge(
+  ctx: &serenity::Context,
+  db: &DatabaseHandler,
+  live: &LiveTeamChallenge,
+) -> Result<()> {
+  let challenge = &live.challenge;
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  let standings = DatabaseHandler::get_team_standings(&mut transaction, challenge).await?;
+  drop(transaction);
+
+  // Wait an extra day after the last day of the challenge, so that members in every
+  // timezone have had a chance to add their final times.
+  let finished = Utc::now().date_naive() - challenge.end_date >= Duration::days(1);
+
+  if let (Some(channel_id), Some(message_id)) =
+    (live.standings_channel_id, live.standings_message_id)
+  {
+    if let Err(err) = channel_id
+      .edit_message(
+        ctx,
+        message_id,
+        EditMessage::new().embed(team_standings_embed(challenge, &standings, finished)),
+      )
+      .await
+    {
+      error!("Error editing team standings message: {}", err);
+    }
+  }
+
+  if !finished {
+    return Ok(());
+  }
+
+  // Record the results before announcing them, so that a failure to save can't announce them
+  // twice
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  DatabaseHandler::record_team_results(&mut transaction, &challenge.id, &standings).await?;
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  let announcement_channel = serenity::ChannelId::new(CHANNELS.announcement);
+  announcement_channel
+    .send_message(
+      ctx,
+      CreateMessage::new().embed(team_standings_embed(challenge, &standings, true)),
+    )
+    .await?;
+
+  info!(
+    "Posted final results for team challenge {} in guild {}",
+    challenge.challenge_name, live.guild_id
+  );
+
+  Ok(())
+}
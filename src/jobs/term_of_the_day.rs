@@ -0,0 +1,95 @@
+use crate::commands::glossary::term_embed;
+use crate::config::TERM_OF_THE_DAY_HOUR;
+use crate::database::{DatabaseHandler, Term, TermOfTheDayChannel};
+use anyhow::Result;
+use chrono::{Timelike, Utc};
+use log::error;
+use poise::serenity_prelude::{self as serenity, builder::*};
+
+/// Posts the term of the day in every guild that has a channel for it and hasn't had one today,
+/// once it is past `TERM_OF_THE_DAY_HOUR` in UTC.
+pub async fn post_terms_of_the_day(ctx: &serenity::Context, db: &DatabaseHandler) -> Result<()> {
+  if Utc::now().hour() < TERM_OF_THE_DAY_HOUR {
+    return Ok(());
+  }
+
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  let channels = DatabaseHandler::get_due_terms_of_the_day(&mut transaction).await?;
+  drop(transaction);
+
+  for channel in channels {
+    if let Err(err) = post_term_of_the_day(ctx, db, &channel).await {
+      error!(
+        "Error posting term of the day in {}: {}",
+        channel.guild_id, err
+      );
+    }
+  }
+
+  Ok(())
+}
+
+/// Chooses the next term of the day and the cycle it belongs to. Pinned terms come first, oldest
+/// first. Otherwise a random term that hasn't been posted or skipped in the current cycle is
+/// chosen, and once there are none left the next cycle starts. Returns `None` if the glossary is
+/// empty.
+async fn next_term(
+  transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+  guild_id: &serenity::GuildId,
+) -> Result<Option<(Term, i32)>> {
+  let cycle = DatabaseHandler::get_term_of_the_day_cycle(transaction, guild_id).await?;
+
+  let pins = DatabaseHandler::get_term_of_the_day_pins(transaction, guild_id).await?;
+  if let Some(term) = pins.into_iter().next() {
+    return Ok(Some((term, cycle)));
+  }
+
+  if let Some(term) =
+    DatabaseHandler::get_random_unposted_term(transaction, guild_id, cycle).await?
+  {
+    return Ok(Some((term, cycle)));
+  }
+
+  let term = DatabaseHandler::get_random_unposted_term(transaction, guild_id, cycle + 1).await?;
+
+  Ok(term.map(|term| (term, cycle + 1)))
+}
+
+/// Records the term as posted before sending it, so that a failure to save can't post the same
+/// day's term twice. If sending fails, the day's term is missed rather than retried.
+async fn post_term_of_the_day(
+  ctx: &serenity::Context,
+  db: &DatabaseHandler,
+  channel: &TermOfTheDayChannel,
+) -> Result<()> {
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+
+  let (term, cycle) = match next_term(&mut transaction, &channel.guild_id).await? {
+    Some(next) => next,
+    None => return Ok(()),
+  };
+
+  let record_id = DatabaseHandler::add_term_of_the_day(
+    &mut transaction,
+    &channel.guild_id,
+    &term.term_name,
+    cycle,
+    false,
+  )
+  .await?;
+  DatabaseHandler::remove_term_of_the_day_pin(&mut transaction, &channel.guild_id, &term.term_name)
+    .await?;
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  let embed = term_embed(&term).author(CreateEmbedAuthor::new("Term of the Day"));
+  let message = channel
+    .channel_id
+    .send_message(ctx, CreateMessage::new().embed(embed))
+    .await?;
+
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  DatabaseHandler::set_term_of_the_day_message(&mut transaction, &record_id, &message.id).await?;
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  Ok(())
+}
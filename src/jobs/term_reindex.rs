@@ -0,0 +1,90 @@
+use crate::database::{DatabaseHandler, TermIndexData};
+use crate::embeddings::{embed_term, term_input_hash, EmbeddingProvider, TermText};
+use anyhow::Result;
+use log::{error, info};
+use poise::serenity_prelude as serenity;
+
+/// The number of terms checked, re-embedded, and failed by a reindex.
+#[derive(Debug, Default)]
+pub struct ReindexSummary {
+  pub checked: usize,
+  pub updated: usize,
+  pub failed: usize,
+}
+
+fn term_text(term: &TermIndexData) -> TermText<'_> {
+  TermText {
+    term_name: &term.term_name,
+    meaning: &term.meaning,
+    usage: term.usage.as_deref(),
+    category: term.category.as_deref(),
+    aliases: &term.aliases,
+  }
+}
+
+/// Whether a term has no embedding, or one that is stale because it came from another model or
+/// from a different rendering of the embedding template.
+fn needs_reindex(term: &TermIndexData, provider: &dyn EmbeddingProvider) -> bool {
+  term.embedding_model.as_deref() != Some(provider.model())
+    || term.embedding_hash.as_deref() != Some(term_input_hash(&term_text(term)).as_str())
+}
+
+/// Recomputes the embeddings of the terms in a guild, or in every guild, that are missing or
+/// stale. With `force`, every term is re-embedded. Each term is saved in its own transaction so
+/// that one failure doesn't undo the rest.
+pub async fn reindex_terms(
+  db: &DatabaseHandler,
+  provider: &dyn EmbeddingProvider,
+  guild_id: Option<&serenity::GuildId>,
+  force: bool,
+  user: serenity::UserId,
+) -> Result<ReindexSummary> {
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  let terms = DatabaseHandler::get_terms_for_reindex(&mut transaction, guild_id).await?;
+  drop(transaction);
+
+  let mut summary = ReindexSummary::default();
+
+  for term in terms {
+    summary.checked += 1;
+
+    if !force && !needs_reindex(&term, provider) {
+      continue;
+    }
+
+    match reindex_term(db, provider, &term, user).await {
+      Ok(()) => summary.updated += 1,
+      Err(err) => {
+        error!("Error reindexing term {}: {}", term.term_name, err);
+        summary.failed += 1;
+      }
+    }
+  }
+
+  if summary.updated > 0 || summary.failed > 0 {
+    info!(
+      "Reindexed {} of {} terms with {} ({} failed)",
+      summary.updated,
+      summary.checked,
+      provider.model(),
+      summary.failed
+    );
+  }
+
+  Ok(summary)
+}
+
+async fn reindex_term(
+  db: &DatabaseHandler,
+  provider: &dyn EmbeddingProvider,
+  term: &TermIndexData,
+  user: serenity::UserId,
+) -> Result<()> {
+  let embedding = embed_term(provider, &term_text(term), user).await?;
+
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  DatabaseHandler::set_term_embedding(&mut transaction, &term.id, embedding).await?;
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  Ok(())
+}
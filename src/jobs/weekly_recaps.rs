@@ -0,0 +1,178 @@
+use crate::charts;
+use crate::commands::stats::StatsType;
+use crate::config::BloomBotEmbed;
+use crate::database::{DatabaseHandler, Timeframe, TrackingProfile};
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use log::error;
+use poise::serenity_prelude::{self as serenity, builder::*};
+
+/// Sends the weekly recap DM to everyone who turned it on with `/customize recap` and for
+/// whom it is currently Sunday evening.
+pub async fn send_weekly_recaps(ctx: &serenity::Context, db: &DatabaseHandler) -> Result<()> {
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  let profiles = DatabaseHandler::get_due_weekly_recaps(&mut transaction).await?;
+  drop(transaction);
+
+  for profile in profiles {
+    if let Err(err) = send_weekly_recap(ctx, db, &profile).await {
+      error!("Error sending weekly recap: {}", err);
+    }
+  }
+
+  Ok(())
+}
+
+fn describe_change(current: i64, previous: i64) -> String {
+  match current - previous {
+    0 => "same as last week".to_string(),
+    change if change > 0 => format!("up {} from last week", change),
+    change => format!("down {} from last week", -change),
+  }
+}
+
+async fn send_weekly_recap(
+  ctx: &serenity::Context,
+  db: &DatabaseHandler,
+  profile: &TrackingProfile,
+) -> Result<()> {
+  let guild_id = profile.guild_id;
+  let user_id = profile.user_id;
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+
+  // Entries are stored in the user's local time, so the weeks are measured from local time too
+  let local_now = Utc::now() + Duration::minutes(i64::from(profile.utc_offset));
+  let week_start = local_now - Duration::days(7);
+  let previous_week_start = local_now - Duration::days(14);
+
+  let minutes = DatabaseHandler::get_user_meditation_sum_between(
+    &mut transaction,
+    &guild_id,
+    &user_id,
+    week_start,
+    local_now,
+  )
+  .await?;
+  let previous_minutes = DatabaseHandler::get_user_meditation_sum_between(
+    &mut transaction,
+    &guild_id,
+    &user_id,
+    previous_week_start,
+    week_start,
+  )
+  .await?;
+  let sessions = DatabaseHandler::get_user_meditation_count_between(
+    &mut transaction,
+    &guild_id,
+    &user_id,
+    week_start,
+    local_now,
+  )
+  .await?;
+  let previous_sessions = DatabaseHandler::get_user_meditation_count_between(
+    &mut transaction,
+    &guild_id,
+    &user_id,
+    previous_week_start,
+    week_start,
+  )
+  .await?;
+
+  let mut embed = BloomBotEmbed::new()
+    .title("Your Weekly Meditation Recap")
+    .field(
+      "Minutes This Week",
+      format!(
+        "```{}```{}",
+        minutes,
+        describe_change(minutes, previous_minutes)
+      ),
+      true,
+    )
+    .field(
+      "Sessions This Week",
+      format!(
+        "```{}```{}",
+        sessions,
+        describe_change(sessions, previous_sessions)
+      ),
+      true,
+    );
+
+  if profile.streaks_active {
+    let streak = DatabaseHandler::get_streak(&mut transaction, &guild_id, &user_id).await?;
+    embed = embed.field("Current Streak", format!("```{} days```", streak), true);
+  }
+
+  let challenges =
+    DatabaseHandler::get_user_challenges(&mut transaction, &guild_id, &user_id).await?;
+
+  if !challenges.is_empty() {
+    let goals = challenges
+      .iter()
+      .map(|(challenge, progress)| {
+        format!(
+          "**{}**: {} / {} {}{}",
+          challenge.challenge_name,
+          progress.value(challenge.goal_type),
+          challenge.goal_target,
+          challenge.goal_type.as_str(),
+          match progress.completed_at {
+            Some(_) => " :white_check_mark:",
+            None => "",
+          }
+        )
+      })
+      .collect::<Vec<String>>()
+      .join("\n");
+
+    embed = embed.field("Goal Progress", goals, false);
+  }
+
+  if let Some(quote) = DatabaseHandler::get_random_quote(&mut transaction, &guild_id).await? {
+    let author = quote.author.unwrap_or("Anonymous".to_string());
+    embed = embed.field(
+      "Quote of the Week",
+      format!("*{}*\n— {}", quote.quote, author),
+      false,
+    );
+  }
+
+  let chart_stats = DatabaseHandler::get_user_chart_stats(
+    &mut transaction,
+    &guild_id,
+    &user_id,
+    &Timeframe::Weekly,
+  )
+  .await?;
+  let chart = charts::ChartDrawer::new()?
+    .draw(
+      &chart_stats,
+      &Timeframe::Weekly,
+      &StatsType::MeditationMinutes,
+      (253, 172, 46, 1.0),
+      false,
+    )
+    .await?;
+
+  embed = embed
+    .image(chart.get_attachment_url())
+    .footer(CreateEmbedFooter::new(
+      "You can turn the weekly recap off with /customize recap",
+    ));
+
+  // Mark the recap as sent even if the DM fails, so closed DMs are not retried all evening
+  DatabaseHandler::mark_weekly_recap_sent(&mut transaction, &guild_id, &user_id).await?;
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  user_id
+    .direct_message(
+      ctx,
+      CreateMessage::new()
+        .embed(embed)
+        .add_file(CreateAttachment::path(chart.get_file_path()).await?),
+    )
+    .await?;
+
+  Ok(())
+}
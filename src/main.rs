@@ -1,10 +1,12 @@
 use anyhow::{Context as ErrorContext, Error, Result};
 use commands::{
-  add::add, challenge::challenge, coffee::coffee, complete::complete, courses::course,
+  add::add, badges::badges, boards::boards, buddy::buddy, challenge::challenge,
+  challenges::challenges, coffee::coffee, complete::complete, courses::course,
   customize::customize, erase::erase, glossary::glossary, hello::hello, help::help, keys::keys,
-  manage::manage, pick_winner::pick_winner, ping::ping, quote::quote, quotes::quotes,
-  recent::recent, remove_entry::remove_entry, report_message::report_message, stats::stats,
-  streak::streak, suggest::suggest, terms::terms, whatis::whatis,
+  manage::manage, moderation::moderation, pick_winner::pick_winner, ping::ping, quote::quote,
+  quotes::quotes, reasons::reasons, recent::recent, remove_entry::remove_entry,
+  report_message::report_message, reports::reports, starboard::starboard, stats::stats,
+  streak::streak, suggest::suggest, term_of_the_day::term_of_the_day, terms::terms, whatis::whatis,
 };
 use dotenvy::dotenv;
 use log::{error, info};
@@ -15,18 +17,29 @@ use serenity::FullEvent as Event;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+mod appeals;
+mod badges;
 mod charts;
 mod commands;
 mod config;
 mod database;
 mod embeddings;
 mod events;
+mod jobs;
 mod pagination;
+mod reaction_boards;
+mod reports;
+mod term_search;
+mod term_suggestions;
+mod term_transfer;
 
 pub struct Data {
   pub db: database::DatabaseHandler,
   pub rng: Arc<Mutex<SmallRng>>,
-  pub embeddings: Arc<embeddings::OpenAIHandler>,
+  /// `None` when no embeddings provider is available, in which case glossary search only matches
+  /// term names and aliases.
+  pub embeddings: Option<Arc<dyn embeddings::EmbeddingProvider>>,
+  pub query_embeddings: embeddings::QueryEmbeddingCache,
 }
 pub type Context<'a> = poise::Context<'a, Data, Error>;
 
@@ -50,10 +63,19 @@ async fn main() -> Result<()> {
         course(),
         pick_winner(),
         erase(),
+        moderation(),
+        reasons(),
+        reports(),
         manage(),
         quotes(),
         terms(),
+        term_of_the_day(),
+        starboard(),
+        boards(),
         challenge(),
+        challenges(),
+        buddy(),
+        badges(),
         customize(),
         add(),
         recent(),
@@ -98,10 +120,17 @@ async fn main() -> Result<()> {
           info!("Setting default activity text");
           ctx.set_activity(Some(serenity::ActivityData::custom("Tracking your meditations")));
         }
+        let db = database::DatabaseHandler::new().await?;
+        let embeddings = embeddings::provider_from_env();
+        jobs::spawn(ctx.clone(), db.clone(), embeddings.clone());
+
         Ok(Data {
-          db: database::DatabaseHandler::new().await?,
+          db,
           rng: Arc::new(Mutex::new(SmallRng::from_entropy())),
-          embeddings: Arc::new(embeddings::OpenAIHandler::new()?),
+          embeddings,
+          query_embeddings: embeddings::QueryEmbeddingCache::new(
+            config::QUERY_EMBEDDING_CACHE_SIZE,
+          ),
         })
       })
     })
@@ -241,12 +270,25 @@ async fn event_handler(
     } => {
       events::guild_member_update(ctx, old_if_available, new).await?;
     }
+    Event::InteractionCreate { interaction } => {
+      events::interaction_create(ctx, data, interaction).await?;
+    }
     Event::MessageDelete {
       channel_id: _,
       deleted_message_id,
-      guild_id: _,
+      guild_id,
+    } => {
+      events::message_delete(ctx, database, guild_id, deleted_message_id).await?;
+    }
+    Event::MessageDeleteBulk {
+      channel_id: _,
+      multiple_deleted_messages_ids,
+      guild_id,
     } => {
-      events::message_delete(database, deleted_message_id).await?;
+      events::message_delete_bulk(ctx, database, guild_id, multiple_deleted_messages_ids).await?;
+    }
+    Event::MessageUpdate { event, .. } => {
+      events::message_update(ctx, database, event).await?;
     }
     Event::ReactionAdd { add_reaction } => {
       events::reaction_add(ctx, database, add_reaction).await?;
@@ -254,6 +296,15 @@ async fn event_handler(
     Event::ReactionRemove { removed_reaction } => {
       events::reaction_remove(ctx, database, removed_reaction).await?;
     }
+    Event::ReactionRemoveAll {
+      channel_id,
+      removed_from_message_id,
+    } => {
+      events::reaction_remove_all(ctx, database, channel_id, removed_from_message_id).await?;
+    }
+    Event::ReactionRemoveEmoji { removed_reactions } => {
+      events::reaction_remove_emoji(ctx, database, removed_reactions).await?;
+    }
     Event::Ready { .. } => {
       info!("Connected!");
     }
@@ -0,0 +1,460 @@
+use crate::config;
+use crate::database::{DatabaseHandler, ReactionBoard, StarMessage};
+use anyhow::Result;
+use poise::serenity_prelude::{
+  builder::*, ChannelId, Context, GuildChannel, GuildId, Message, MessageFlags, MessageId,
+  ReactionType,
+};
+
+/// Returns every board in a guild, starting with the starboard.
+pub async fn get_boards(
+  transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+  guild_id: &GuildId,
+) -> Result<Vec<ReactionBoard>> {
+  let mut boards = vec![ReactionBoard::starboard()];
+  boards.extend(DatabaseHandler::get_reaction_boards(transaction, guild_id).await?);
+
+  Ok(boards)
+}
+
+/// Finds a board by name, ignoring case.
+pub async fn find_board(
+  transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+  guild_id: &GuildId,
+  board_name: &str,
+) -> Result<Option<ReactionBoard>> {
+  let board = get_boards(transaction, guild_id)
+    .await?
+    .into_iter()
+    .find(|board| board.name.eq_ignore_ascii_case(board_name.trim()));
+
+  Ok(board)
+}
+
+/// The board settings that apply to a channel. NSFW channels are always excluded, and threads
+/// use the settings of their parent channel unless they have their own.
+struct ChannelRules {
+  excluded: bool,
+  min_stars: Option<u64>,
+}
+
+impl ChannelRules {
+  async fn get(
+    ctx: &Context,
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    channel: &GuildChannel,
+  ) -> Result<Self> {
+    let parent = match (&channel.thread_metadata, channel.parent_id) {
+      (Some(_), Some(parent_id)) => parent_id.to_channel(ctx).await?.guild(),
+      _ => None,
+    };
+
+    if channel.nsfw || parent.as_ref().is_some_and(|parent| parent.nsfw) {
+      return Ok(Self {
+        excluded: true,
+        min_stars: None,
+      });
+    }
+
+    let mut settings =
+      DatabaseHandler::get_starboard_channel(transaction, &channel.guild_id, &channel.id)
+        .await?
+        .filter(|settings| settings.excluded || settings.min_stars.is_some());
+
+    if settings.is_none() {
+      if let Some(parent) = &parent {
+        settings =
+          DatabaseHandler::get_starboard_channel(transaction, &parent.guild_id, &parent.id).await?;
+      }
+    }
+
+    Ok(match settings {
+      Some(settings) => Self {
+        excluded: settings.excluded,
+        min_stars: settings
+          .min_stars
+          .and_then(|min_stars| u64::try_from(min_stars).ok()),
+      },
+      None => Self {
+        excluded: false,
+        min_stars: None,
+      },
+    })
+  }
+
+  /// Returns the number of reactions a message needs to reach the board, or `None` if the
+  /// channel is excluded. Excluded channels apply to every board, while per-channel thresholds
+  /// only apply to the starboard.
+  fn threshold(&self, board: &ReactionBoard) -> Option<u64> {
+    match (self.excluded, board.is_starboard()) {
+      (true, _) => None,
+      (false, true) => Some(self.min_stars.unwrap_or(board.min_reactions)),
+      (false, false) => Some(board.min_reactions),
+    }
+  }
+}
+
+/// Counts the reactions with an emoji on a message, leaving out the author's own reaction and
+/// reactions from bots.
+pub async fn count_reactions(
+  ctx: &Context,
+  message: &Message,
+  emoji: &ReactionType,
+) -> Result<u64> {
+  let mut count = 0;
+  let mut after = None;
+
+  loop {
+    let users = message
+      .reaction_users(ctx, emoji.clone(), Some(100), after)
+      .await?;
+
+    count += users
+      .iter()
+      .filter(|user| !user.bot && user.id != message.author.id)
+      .count() as u64;
+
+    match users.last() {
+      Some(last) if users.len() == 100 => after = Some(last.id),
+      _ => break,
+    }
+  }
+
+  Ok(count)
+}
+
+/// Brings the boards in line with the current reactions on a message. Only the boards for the
+/// given emoji are updated, or every board if no emoji is given.
+pub async fn update_board_messages(
+  ctx: &Context,
+  database: &DatabaseHandler,
+  guild_id: GuildId,
+  channel_id: ChannelId,
+  message_id: MessageId,
+  emoji: Option<&ReactionType>,
+) -> Result<()> {
+  // Most reactions aren't for any board, so check the emoji before fetching anything from Discord
+  let mut transaction = database.start_transaction().await?;
+  let boards = get_boards(&mut transaction, &guild_id)
+    .await?
+    .into_iter()
+    .filter(|board| board.channel_id != channel_id)
+    .filter(|board| emoji.is_none_or(|emoji| board.matches(emoji)))
+    .collect::<Vec<ReactionBoard>>();
+
+  if boards.is_empty() {
+    return Ok(());
+  }
+
+  let channel = match channel_id.to_channel(ctx).await?.guild() {
+    Some(channel) => channel,
+    None => return Ok(()),
+  };
+
+  let rules = ChannelRules::get(ctx, &mut transaction, &channel).await?;
+  let message = channel_id.message(ctx, message_id).await?;
+
+  for board in &boards {
+    update_board_message(ctx, &mut transaction, board, &rules, &channel, &message).await?;
+  }
+
+  transaction.commit().await?;
+
+  Ok(())
+}
+
+/// Refreshes the board posts of an edited message, keeping their current counts.
+pub async fn refresh_board_messages(
+  ctx: &Context,
+  database: &DatabaseHandler,
+  channel_id: ChannelId,
+  message_id: MessageId,
+) -> Result<()> {
+  let mut transaction = database.start_transaction().await?;
+  let star_messages =
+    DatabaseHandler::get_star_messages_by_message_id(&mut transaction, &message_id).await?;
+
+  if star_messages.is_empty() {
+    return Ok(());
+  }
+
+  let channel = match channel_id.to_channel(ctx).await?.guild() {
+    Some(channel) => channel,
+    None => return Ok(()),
+  };
+
+  let boards = get_boards(&mut transaction, &channel.guild_id).await?;
+  drop(transaction);
+
+  let message = channel_id.message(ctx, message_id).await?;
+
+  for star_message in star_messages {
+    let board = match boards
+      .iter()
+      .find(|board| board.id == star_message.board_id)
+    {
+      Some(board) => board,
+      None => continue,
+    };
+
+    let count = u64::try_from(star_message.star_count).unwrap_or(0);
+    let embed = board_embed(ctx, board, channel.guild_id, &message, count).await;
+
+    // If the board post is gone, it will be posted again on the next reaction
+    let _ = board
+      .channel_id
+      .edit_message(
+        ctx,
+        star_message.board_message_id,
+        EditMessage::new().embed(embed),
+      )
+      .await;
+  }
+
+  Ok(())
+}
+
+/// Removes a deleted message from every board it was posted to, deleting the board posts so
+/// they don't link to a message that no longer exists.
+pub async fn remove_deleted_message(
+  ctx: &Context,
+  database: &DatabaseHandler,
+  guild_id: Option<GuildId>,
+  message_id: MessageId,
+) -> Result<()> {
+  let mut transaction = database.start_transaction().await?;
+  let star_messages =
+    DatabaseHandler::get_star_messages_by_message_id(&mut transaction, &message_id).await?;
+
+  if star_messages.is_empty() {
+    return Ok(());
+  }
+
+  let boards = match guild_id {
+    Some(guild_id) => get_boards(&mut transaction, &guild_id).await?,
+    None => vec![ReactionBoard::starboard()],
+  };
+
+  for star_message in star_messages {
+    if let Some(board) = boards
+      .iter()
+      .find(|board| board.id == star_message.board_id)
+    {
+      // The board post may already have been deleted by hand
+      let _ = board
+        .channel_id
+        .delete_message(ctx, star_message.board_message_id)
+        .await;
+    }
+
+    DatabaseHandler::delete_star_message(&mut transaction, &star_message.record_id).await?;
+  }
+
+  transaction.commit().await?;
+
+  Ok(())
+}
+
+/// Posts a message to a board once it reaches the threshold, updates the count while it stays
+/// above, and removes it from the board when it drops below or the channel is excluded.
+async fn update_board_message(
+  ctx: &Context,
+  transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+  board: &ReactionBoard,
+  rules: &ChannelRules,
+  channel: &GuildChannel,
+  message: &Message,
+) -> Result<()> {
+  let threshold = rules.threshold(board);
+  let star_message =
+    DatabaseHandler::get_star_message_by_message_id(transaction, &board.id, &message.id).await?;
+
+  let raw_count = message
+    .reactions
+    .iter()
+    .find(|r| board.matches(&r.reaction_type))
+    .map(|r| r.count)
+    .unwrap_or(0);
+
+  // Excluded reactions can only lower the count, so new messages below the threshold can be skipped
+  let count = match threshold {
+    Some(threshold) if raw_count >= threshold || star_message.is_some() => {
+      count_reactions(ctx, message, &board.emoji).await?
+    }
+    _ => 0,
+  };
+  let meets_threshold = threshold.is_some_and(|threshold| count >= threshold);
+
+  match (star_message, meets_threshold) {
+    (Some(star_message), true) => {
+      if edit_board_footer(ctx, board, &star_message, count).await {
+        DatabaseHandler::update_star_count(
+          transaction,
+          &star_message.record_id,
+          i32::try_from(count).unwrap_or(i32::MAX),
+        )
+        .await?;
+      } else {
+        // The board message is gone or can't be edited, so post it again
+        let _ = board
+          .channel_id
+          .delete_message(ctx, star_message.board_message_id)
+          .await;
+        DatabaseHandler::delete_star_message(transaction, &star_message.record_id).await?;
+        create_board_message(ctx, transaction, board, channel, message, count).await?;
+      }
+    }
+    (Some(star_message), false) => {
+      // The board message may already have been deleted by hand
+      let _ = board
+        .channel_id
+        .delete_message(ctx, star_message.board_message_id)
+        .await;
+      DatabaseHandler::delete_star_message(transaction, &star_message.record_id).await?;
+    }
+    (None, true) => {
+      create_board_message(ctx, transaction, board, channel, message, count).await?;
+    }
+    (None, false) => {}
+  }
+
+  Ok(())
+}
+
+/// Updates the count in the footer of an existing board message. Returns `false` if the board
+/// message could not be updated.
+async fn edit_board_footer(
+  ctx: &Context,
+  board: &ReactionBoard,
+  star_message: &StarMessage,
+  count: u64,
+) -> bool {
+  let mut board_message = match board
+    .channel_id
+    .message(ctx, star_message.board_message_id)
+    .await
+  {
+    Ok(board_message) => board_message,
+    Err(_) => return false,
+  };
+
+  let updated_embed = match board_message.embeds.first() {
+    Some(existing_embed) => {
+      CreateEmbed::from(existing_embed.clone()).footer(CreateEmbedFooter::new(board.footer(count)))
+    }
+    None => return false,
+  };
+
+  board_message
+    .edit(ctx, EditMessage::new().embed(updated_embed))
+    .await
+    .is_ok()
+}
+
+/// Builds the board embed for a message. Used both for new board posts and to refresh them when
+/// the message is edited.
+async fn board_embed(
+  ctx: &Context,
+  board: &ReactionBoard,
+  guild_id: GuildId,
+  starred_message: &Message,
+  count: u64,
+) -> CreateEmbed {
+  let author_nick_or_name = match starred_message.author.nick_in(&ctx, guild_id).await {
+    Some(nick) => nick,
+    None => starred_message.author.name.clone(),
+  };
+
+  let message_type = match starred_message.flags {
+    Some(flags) if flags.contains(MessageFlags::IS_VOICE_MESSAGE) => "voice message",
+    _ => "message",
+  };
+
+  let mut embed = match starred_message.embeds.first() {
+    Some(embed) => config::BloomBotEmbed::from(embed.clone().to_owned()),
+    None => config::BloomBotEmbed::new().description(starred_message.content.clone()),
+  };
+
+  embed = embed
+    .author(CreateEmbedAuthor::new(author_nick_or_name).icon_url(starred_message.author.face()))
+    .field(
+      "Link",
+      format!(
+        "**[Click to jump to {}.]({})**",
+        message_type,
+        starred_message.link().clone()
+      ),
+      false,
+    )
+    .footer(CreateEmbedFooter::new(board.footer(count)))
+    .color(board.color)
+    .to_owned();
+
+  if let Some(sticker) = &starred_message.sticker_items.first() {
+    if let Some(sticker_url) = sticker.image_url() {
+      embed = embed.image(sticker_url.clone()).to_owned();
+    }
+  }
+
+  if let Some(attachment) = &starred_message.attachments.first() {
+    if let Some(content_type) = &attachment.content_type {
+      if content_type.starts_with("image") {
+        embed = embed.image(attachment.url.clone()).to_owned();
+      }
+    }
+  }
+
+  embed
+}
+
+async fn create_board_message(
+  ctx: &Context,
+  transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+  board: &ReactionBoard,
+  channel: &GuildChannel,
+  starred_message: &Message,
+  count: u64,
+) -> Result<()> {
+  let embed = board_embed(ctx, board, channel.guild_id, starred_message, count).await;
+
+  let board_channel = board.channel_id;
+
+  let board_message = match &starred_message.attachments.first() {
+    Some(attachment) => match &attachment.content_type {
+      Some(content_type) if content_type.starts_with("image") => {
+        board_channel
+          .send_message(ctx, CreateMessage::new().embed(embed))
+          .await?
+      }
+      _ => {
+        board_channel
+          .send_message(
+            ctx,
+            CreateMessage::new()
+              .embed(embed)
+              .add_file(CreateAttachment::url(ctx, attachment.url.as_str()).await?),
+          )
+          .await?
+      }
+    },
+    None => {
+      board_channel
+        .send_message(ctx, CreateMessage::new().embed(embed))
+        .await?
+    }
+  };
+
+  DatabaseHandler::insert_star_message(
+    transaction,
+    &board.id,
+    &channel.guild_id,
+    &starred_message.id,
+    &board_message.id,
+    &channel.id,
+    &starred_message.author.id,
+    i32::try_from(count).unwrap_or(i32::MAX),
+  )
+  .await?;
+
+  Ok(())
+}
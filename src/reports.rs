@@ -0,0 +1,463 @@
+use crate::config::{
+  BloomBotEmbed, CHANNELS, REPORT_PING_THRESHOLD, REPORT_RATE_LIMIT, REPORT_RATE_LIMIT_WINDOW,
+  ROLES,
+};
+use crate::database::{DatabaseHandler, ReportData, ReportStatus};
+use anyhow::{Context, Result};
+use poise::serenity_prelude::{
+  self as serenity, builder::*, ActionRowComponent, ButtonStyle, ComponentInteraction,
+  InputTextStyle, Mentionable, ModalInteraction,
+};
+use poise::ChoiceParameter;
+
+/// Custom IDs of the report buttons and modals start with this prefix, followed by the action
+/// and the report ID, e.g. `report:claim:<id>`.
+pub const REPORT_PREFIX: &str = "report:";
+
+fn report_custom_id(action: &str, report_id: &str) -> String {
+  format!("{}{}:{}", REPORT_PREFIX, action, report_id)
+}
+
+/// Splits a report custom ID into its action and report ID.
+fn parse_custom_id(custom_id: &str) -> Option<(&str, &str)> {
+  custom_id.strip_prefix(REPORT_PREFIX)?.split_once(':')
+}
+
+pub async fn report_embed(ctx: &serenity::Context, report: &ReportData) -> Result<CreateEmbed> {
+  let author = report.author_id.to_user(ctx).await?;
+  let reporter = report.reporter_id.to_user(ctx).await?;
+  let channel_name = report.channel_id.name(ctx).await?;
+
+  let status = match (report.status, report.assignee_id) {
+    (ReportStatus::Open, _) => "Open".to_string(),
+    (status, Some(assignee_id)) => format!("{} by {}", status.name(), assignee_id.mention()),
+    (status, None) => status.name().to_string(),
+  };
+
+  let mut embed = BloomBotEmbed::new()
+    .title(format!("Case #{}", report.case_number))
+    .author(CreateEmbedAuthor::new(author.name.clone()).icon_url(author.face()))
+    .description(report.message_content.clone())
+    .field(
+      "Link",
+      format!("[Go to message]({})", report.message_link()),
+      false,
+    )
+    .field("Status", status, true)
+    .field("Reporters", report.reporter_count.to_string(), true)
+    .footer(CreateEmbedFooter::new(format!(
+      "Author ID: {}\nReported via {} in #{} by {} ({})",
+      author.id, report.source, channel_name, reporter.name, reporter.id
+    )))
+    .timestamp(serenity::Timestamp::from(report.created_at));
+
+  if let Some(resolution_notes) = &report.resolution_notes {
+    embed = embed.field("Resolution Notes", resolution_notes, false);
+  }
+
+  embed = match report.status {
+    ReportStatus::Resolved => embed.color(serenity::Color::DARK_GREEN),
+    ReportStatus::Dismissed => embed.color(serenity::Color::LIGHT_GREY),
+    _ => embed,
+  };
+
+  Ok(embed)
+}
+
+/// Buttons for a report that still needs attention. Closed reports have no buttons.
+pub fn report_buttons(report: &ReportData) -> Vec<CreateActionRow> {
+  match report.status.is_active() {
+    true => vec![CreateActionRow::Buttons(vec![
+      CreateButton::new(report_custom_id("claim", &report.id))
+        .label("Claim")
+        .style(ButtonStyle::Primary),
+      CreateButton::new(report_custom_id("resolve", &report.id))
+        .label("Resolve")
+        .style(ButtonStyle::Success),
+      CreateButton::new(report_custom_id("dismiss", &report.id))
+        .label("Dismiss")
+        .style(ButtonStyle::Secondary),
+    ])],
+    false => vec![],
+  }
+}
+
+pub enum ReportOutcome {
+  /// A new case was opened.
+  Created,
+  /// The report was added to the open case for the message.
+  Merged,
+  /// The user had already reported the message.
+  AlreadyReported,
+  /// The user has made too many reports recently.
+  RateLimited,
+}
+
+impl ReportOutcome {
+  /// The message shown to the reporter.
+  pub fn reply(&self) -> String {
+    match self {
+      ReportOutcome::Created | ReportOutcome::Merged => {
+        "Your report has been sent to the moderation team.".to_string()
+      }
+      ReportOutcome::AlreadyReported => {
+        "You have already reported this message. The moderation team is looking into it."
+          .to_string()
+      }
+      ReportOutcome::RateLimited => format!(
+        "You have made too many reports recently. Please wait a while before reporting again, or contact a moderator directly if something needs urgent attention. You can make {} reports every {} minutes.",
+        REPORT_RATE_LIMIT, REPORT_RATE_LIMIT_WINDOW
+      ),
+    }
+  }
+}
+
+/// Records a report and posts it to the report channel for staff. Reports of a message that
+/// already has an open case are merged into that case, and staff are only pinged again when the
+/// case reaches [`REPORT_PING_THRESHOLD`] reporters.
+pub async fn file_report(
+  ctx: &serenity::Context,
+  database: &DatabaseHandler,
+  guild_id: &serenity::GuildId,
+  reporter: &serenity::User,
+  message: &serenity::Message,
+  source: &str,
+) -> Result<ReportOutcome> {
+  let mut transaction = database.start_transaction_with_retry(5).await?;
+
+  let since = chrono::Utc::now() - chrono::Duration::minutes(REPORT_RATE_LIMIT_WINDOW);
+  let recent_reports =
+    DatabaseHandler::get_user_report_count_since(&mut transaction, guild_id, &reporter.id, since)
+      .await?;
+
+  if recent_reports >= REPORT_RATE_LIMIT {
+    return Ok(ReportOutcome::RateLimited);
+  }
+
+  let report_channel = serenity::ChannelId::new(CHANNELS.reportchannel);
+
+  if let Some(report) =
+    DatabaseHandler::get_active_report_by_message(&mut transaction, guild_id, &message.id).await?
+  {
+    return merge_report(ctx, transaction, guild_id, reporter, report).await;
+  }
+
+  let message_content = match message.content.is_empty() {
+    true => match message.attachments.first() {
+      Some(attachment) => format!("**Attachment**\n{}", attachment.url.clone()),
+      None => message.content.clone(),
+    },
+    false => message.content.clone(),
+  };
+
+  let report = DatabaseHandler::add_report(
+    &mut transaction,
+    guild_id,
+    &reporter.id,
+    &message.author.id,
+    &message.channel_id,
+    &message.id,
+    &message_content,
+    source,
+  )
+  .await?;
+
+  let report = match report {
+    Some(report) => report,
+    // Another report of the message opened a case at the same time
+    None => {
+      let report =
+        DatabaseHandler::get_active_report_by_message(&mut transaction, guild_id, &message.id)
+          .await?
+          .with_context(|| "Failed to get the case a report conflicted with")?;
+      return merge_report(ctx, transaction, guild_id, reporter, report).await;
+    }
+  };
+
+  let report_message = report_channel
+    .send_message(
+      ctx,
+      CreateMessage::new()
+        .content(format!("<@&{}> Message Reported", ROLES.staff))
+        .embed(report_embed(ctx, &report).await?)
+        .components(report_buttons(&report)),
+    )
+    .await?;
+
+  DatabaseHandler::set_report_message(&mut transaction, &report.id, &report_message.id).await?;
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  Ok(ReportOutcome::Created)
+}
+
+/// Adds a reporter to the open case for a message, and pings staff again once enough members have
+/// reported it.
+async fn merge_report(
+  ctx: &serenity::Context,
+  mut transaction: sqlx::Transaction<'_, sqlx::Postgres>,
+  guild_id: &serenity::GuildId,
+  reporter: &serenity::User,
+  report: ReportData,
+) -> Result<ReportOutcome> {
+  if !DatabaseHandler::add_report_reporter(&mut transaction, guild_id, &report.id, &reporter.id)
+    .await?
+  {
+    return Ok(ReportOutcome::AlreadyReported);
+  }
+
+  let report = DatabaseHandler::get_report(&mut transaction, &report.id)
+    .await?
+    .unwrap_or(report);
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  let report_channel = serenity::ChannelId::new(CHANNELS.reportchannel);
+
+  if let Some(report_message_id) = report.report_message_id {
+    report_channel
+      .edit_message(
+        ctx,
+        report_message_id,
+        EditMessage::new().embed(report_embed(ctx, &report).await?),
+      )
+      .await?;
+
+    if report.reporter_count == REPORT_PING_THRESHOLD {
+      report_channel
+        .send_message(
+          ctx,
+          CreateMessage::new()
+            .content(format!(
+              "<@&{}> Case #{} has now been reported by {} members",
+              ROLES.staff, report.case_number, report.reporter_count
+            ))
+            .reference_message((report_channel, report_message_id)),
+        )
+        .await?;
+    }
+  }
+
+  Ok(ReportOutcome::Merged)
+}
+
+pub async fn is_staff(
+  ctx: &serenity::Context,
+  guild_id: Option<serenity::GuildId>,
+  user: &serenity::User,
+) -> Result<bool> {
+  match guild_id {
+    Some(guild_id) => Ok(user.has_role(ctx, guild_id, ROLES.staff).await?),
+    None => Ok(false),
+  }
+}
+
+pub fn ephemeral_response(content: &str) -> CreateInteractionResponse {
+  CreateInteractionResponse::Message(
+    CreateInteractionResponseMessage::new()
+      .content(content)
+      .ephemeral(true),
+  )
+}
+
+/// Handles the Claim, Resolve and Dismiss buttons on a report. Claiming updates the report right
+/// away, while resolving and dismissing first ask for resolution notes.
+pub async fn handle_button(
+  ctx: &serenity::Context,
+  database: &DatabaseHandler,
+  press: &ComponentInteraction,
+) -> Result<()> {
+  let (action, report_id) = match parse_custom_id(&press.data.custom_id) {
+    Some(parsed) => parsed,
+    None => return Ok(()),
+  };
+
+  if !is_staff(ctx, press.guild_id, &press.user).await? {
+    press
+      .create_response(
+        ctx,
+        ephemeral_response(":x: Only staff can manage reports."),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let mut transaction = database.start_transaction_with_retry(5).await?;
+  let report = match DatabaseHandler::get_report(&mut transaction, report_id).await? {
+    Some(report) if report.status.is_active() => report,
+    Some(_) => {
+      press
+        .create_response(
+          ctx,
+          ephemeral_response(":x: This report has already been closed."),
+        )
+        .await?;
+      return Ok(());
+    }
+    None => {
+      press
+        .create_response(ctx, ephemeral_response(":x: Report does not exist."))
+        .await?;
+      return Ok(());
+    }
+  };
+
+  match action {
+    "claim" => {
+      DatabaseHandler::update_report_status(
+        &mut transaction,
+        &report.id,
+        ReportStatus::Claimed,
+        Some(&press.user.id),
+        None,
+      )
+      .await?;
+
+      let report = DatabaseHandler::get_report(&mut transaction, &report.id)
+        .await?
+        .unwrap_or(report);
+      DatabaseHandler::commit_transaction(transaction).await?;
+
+      press
+        .create_response(
+          ctx,
+          CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::new()
+              .embed(report_embed(ctx, &report).await?)
+              .components(report_buttons(&report)),
+          ),
+        )
+        .await?;
+    }
+    "resolve" | "dismiss" => {
+      let title = match action {
+        "resolve" => format!("Resolve Case #{}", report.case_number),
+        _ => format!("Dismiss Case #{}", report.case_number),
+      };
+
+      press
+        .create_response(
+          ctx,
+          CreateInteractionResponse::Modal(
+            CreateModal::new(report_custom_id(action, &report.id), title).components(vec![
+              CreateActionRow::InputText(
+                CreateInputText::new(InputTextStyle::Paragraph, "Resolution notes", "notes")
+                  .required(false)
+                  .max_length(1000),
+              ),
+            ]),
+          ),
+        )
+        .await?;
+    }
+    _ => {}
+  }
+
+  Ok(())
+}
+
+/// Handles the resolution notes submitted when resolving or dismissing a report.
+pub async fn handle_modal(
+  ctx: &serenity::Context,
+  database: &DatabaseHandler,
+  submit: &ModalInteraction,
+) -> Result<()> {
+  let (action, report_id) = match parse_custom_id(&submit.data.custom_id) {
+    Some(parsed) => parsed,
+    None => return Ok(()),
+  };
+
+  let status = match action {
+    "resolve" => ReportStatus::Resolved,
+    "dismiss" => ReportStatus::Dismissed,
+    _ => return Ok(()),
+  };
+
+  if !is_staff(ctx, submit.guild_id, &submit.user).await? {
+    submit
+      .create_response(
+        ctx,
+        ephemeral_response(":x: Only staff can manage reports."),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let notes = submit
+    .data
+    .components
+    .iter()
+    .flat_map(|row| row.components.iter())
+    .find_map(|component| match component {
+      ActionRowComponent::InputText(input) if input.custom_id == "notes" => input.value.clone(),
+      _ => None,
+    })
+    .filter(|notes| !notes.trim().is_empty());
+
+  let mut transaction = database.start_transaction_with_retry(5).await?;
+  let report = match DatabaseHandler::get_report(&mut transaction, report_id).await? {
+    Some(report) if report.status.is_active() => report,
+    Some(_) => {
+      submit
+        .create_response(
+          ctx,
+          ephemeral_response(":x: This report has already been closed."),
+        )
+        .await?;
+      return Ok(());
+    }
+    None => {
+      submit
+        .create_response(ctx, ephemeral_response(":x: Report does not exist."))
+        .await?;
+      return Ok(());
+    }
+  };
+
+  DatabaseHandler::update_report_status(
+    &mut transaction,
+    &report.id,
+    status,
+    Some(&submit.user.id),
+    notes.as_deref(),
+  )
+  .await?;
+
+  let report = DatabaseHandler::get_report(&mut transaction, &report.id)
+    .await?
+    .unwrap_or(report);
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  let embed = report_embed(ctx, &report).await?;
+
+  match submit.message {
+    Some(_) => {
+      submit
+        .create_response(
+          ctx,
+          CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::new()
+              .embed(embed)
+              .components(report_buttons(&report)),
+          ),
+        )
+        .await?;
+    }
+    None => {
+      submit
+        .create_response(
+          ctx,
+          CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::new()
+              .content(format!(
+                ":white_check_mark: Case #{} has been {}.",
+                report.case_number,
+                status.as_str()
+              ))
+              .ephemeral(true),
+          ),
+        )
+        .await?;
+    }
+  }
+
+  Ok(())
+}
@@ -0,0 +1,254 @@
+use std::collections::HashMap;
+
+/// The constant added to each rank in reciprocal-rank fusion. Larger values flatten the
+/// difference between the top ranks and the rest.
+pub const RRF_K: f64 = 60.0;
+/// Minimum similarity for a term name or alias to count as a trigram match.
+pub const MIN_TRIGRAM_SIMILARITY: f64 = 0.3;
+/// Minimum cosine similarity for an embedding to count as a match.
+pub const MIN_VECTOR_SIMILARITY: f64 = 0.7;
+/// Minimum trigram similarity of the name or an alias for `/whatis` to answer with a term other
+/// than the one asked for, rather than only suggesting it.
+pub const MIN_ANSWER_SIMILARITY: f64 = 0.7;
+
+/// One of the rankings that hybrid search fuses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+  /// Trigram similarity of the term name.
+  Trigram,
+  /// Trigram similarity of the closest alias.
+  Alias,
+  /// Cosine similarity of the term's embedding.
+  Vector,
+}
+
+impl Signal {
+  pub fn min_similarity(self) -> f64 {
+    match self {
+      Signal::Trigram | Signal::Alias => MIN_TRIGRAM_SIMILARITY,
+      Signal::Vector => MIN_VECTOR_SIMILARITY,
+    }
+  }
+}
+
+/// A term matched by one signal, with its similarity to the query from 0 to 1.
+#[derive(Debug, Clone)]
+pub struct Candidate {
+  pub id: String,
+  pub similarity: f64,
+}
+
+/// The fused score of a term, along with its similarity for each signal that matched it.
+#[derive(Debug, Clone, Default)]
+pub struct HybridScore {
+  pub fused: f64,
+  pub trigram: Option<f64>,
+  pub alias: Option<f64>,
+  pub vector: Option<f64>,
+}
+
+impl HybridScore {
+  fn set(&mut self, signal: Signal, similarity: f64) {
+    match signal {
+      Signal::Trigram => self.trigram = Some(similarity),
+      Signal::Alias => self.alias = Some(similarity),
+      Signal::Vector => self.vector = Some(similarity),
+    }
+  }
+
+  /// The highest similarity of any signal that matched the term.
+  pub fn best_similarity(&self) -> f64 {
+    [self.trigram, self.alias, self.vector]
+      .into_iter()
+      .flatten()
+      .fold(0.0, f64::max)
+  }
+
+  /// The highest trigram similarity of the term's name or aliases, ignoring its embedding.
+  pub fn best_trigram_similarity(&self) -> f64 {
+    [self.trigram, self.alias]
+      .into_iter()
+      .flatten()
+      .fold(0.0, f64::max)
+  }
+
+  /// Describes how closely the term matched, for display.
+  pub fn relevance(&self) -> &'static str {
+    match (self.best_similarity() * 100.0) as i32 {
+      100..=i32::MAX => "Exact match",
+      90..=99 => "High",
+      80..=89 => "Medium",
+      _ => "Low",
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct RankedTerm {
+  pub id: String,
+  pub score: HybridScore,
+}
+
+/// Fuses the rankings of each signal with reciprocal-rank fusion. Candidates below their signal's
+/// minimum similarity are dropped, then the rest are ranked by similarity, and each term scores
+/// `1 / (RRF_K + rank)` for every ranking it appears in. Terms matched by several signals
+/// therefore rise above terms matched well by only one. Returns at most `limit` terms, best first.
+pub fn fuse(rankings: Vec<(Signal, Vec<Candidate>)>, limit: usize) -> Vec<RankedTerm> {
+  let mut terms: Vec<RankedTerm> = Vec::new();
+  let mut positions: HashMap<String, usize> = HashMap::new();
+
+  for (signal, mut candidates) in rankings {
+    candidates.retain(|candidate| candidate.similarity >= signal.min_similarity());
+    candidates.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+
+    for (rank, candidate) in candidates.into_iter().enumerate() {
+      let position = match positions.get(&candidate.id) {
+        Some(position) => *position,
+        None => {
+          terms.push(RankedTerm {
+            id: candidate.id.clone(),
+            score: HybridScore::default(),
+          });
+          positions.insert(candidate.id, terms.len() - 1);
+          terms.len() - 1
+        }
+      };
+
+      let score = &mut terms[position].score;
+      // Ranks start at 1
+      score.fused += 1.0 / (RRF_K + rank as f64 + 1.0);
+      score.set(signal, candidate.similarity);
+    }
+  }
+
+  terms.sort_by(|a, b| {
+    b.score
+      .fused
+      .total_cmp(&a.score.fused)
+      .then_with(|| {
+        b.score
+          .best_similarity()
+          .total_cmp(&a.score.best_similarity())
+      })
+      .then_with(|| a.id.cmp(&b.id))
+  });
+  terms.truncate(limit);
+
+  terms
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn candidates(matches: &[(&str, f64)]) -> Vec<Candidate> {
+    matches
+      .iter()
+      .map(|(id, similarity)| Candidate {
+        id: id.to_string(),
+        similarity: *similarity,
+      })
+      .collect()
+  }
+
+  fn ids(terms: &[RankedTerm]) -> Vec<&str> {
+    terms.iter().map(|term| term.id.as_str()).collect()
+  }
+
+  #[test]
+  fn ranks_terms_matched_by_several_signals_first() {
+    let ranked = fuse(
+      vec![
+        (Signal::Trigram, candidates(&[("a", 0.9), ("b", 0.8)])),
+        (Signal::Vector, candidates(&[("c", 0.95), ("b", 0.9)])),
+      ],
+      10,
+    );
+
+    assert_eq!(ids(&ranked), vec!["b", "c", "a"]);
+    assert!((ranked[0].score.fused - (1.0 / 62.0 + 1.0 / 62.0)).abs() < 1e-12);
+    assert!((ranked[1].score.fused - 1.0 / 61.0).abs() < 1e-12);
+  }
+
+  #[test]
+  fn ranks_candidates_by_similarity_within_a_signal() {
+    let ranked = fuse(
+      vec![(
+        Signal::Trigram,
+        candidates(&[("a", 0.4), ("b", 0.9), ("c", 0.6)]),
+      )],
+      10,
+    );
+
+    assert_eq!(ids(&ranked), vec!["b", "c", "a"]);
+  }
+
+  #[test]
+  fn drops_candidates_below_their_signals_threshold() {
+    let ranked = fuse(
+      vec![
+        (Signal::Trigram, candidates(&[("a", 0.3), ("b", 0.29)])),
+        (Signal::Alias, candidates(&[("c", 0.5), ("d", 0.1)])),
+        (Signal::Vector, candidates(&[("e", 0.7), ("f", 0.5)])),
+      ],
+      10,
+    );
+
+    let mut found = ids(&ranked);
+    found.sort();
+    assert_eq!(found, vec!["a", "c", "e"]);
+  }
+
+  #[test]
+  fn breaks_ties_by_best_similarity_then_id() {
+    let ranked = fuse(
+      vec![
+        (Signal::Trigram, candidates(&[("b", 0.5)])),
+        (Signal::Alias, candidates(&[("c", 0.9)])),
+        (Signal::Vector, candidates(&[("a", 0.8)])),
+      ],
+      10,
+    );
+    assert_eq!(ids(&ranked), vec!["c", "a", "b"]);
+
+    let ranked = fuse(
+      vec![
+        (Signal::Trigram, candidates(&[("b", 0.5)])),
+        (Signal::Alias, candidates(&[("a", 0.5)])),
+      ],
+      10,
+    );
+    assert_eq!(ids(&ranked), vec!["a", "b"]);
+  }
+
+  #[test]
+  fn merges_a_term_found_by_several_signals() {
+    let ranked = fuse(
+      vec![
+        (Signal::Trigram, candidates(&[("a", 0.6)])),
+        (Signal::Alias, candidates(&[("a", 0.8)])),
+        (Signal::Vector, candidates(&[("a", 0.75)])),
+      ],
+      10,
+    );
+
+    assert_eq!(ranked.len(), 1);
+    let score = &ranked[0].score;
+    assert_eq!(score.trigram, Some(0.6));
+    assert_eq!(score.alias, Some(0.8));
+    assert_eq!(score.vector, Some(0.75));
+    assert_eq!(score.best_similarity(), 0.8);
+    assert!((score.fused - 3.0 / 61.0).abs() < 1e-12);
+  }
+
+  #[test]
+  fn returns_at_most_limit_terms() {
+    let rankings = vec![(
+      Signal::Trigram,
+      candidates(&[("a", 0.9), ("b", 0.8), ("c", 0.7), ("d", 0.6)]),
+    )];
+
+    assert_eq!(ids(&fuse(rankings.clone(), 2)), vec!["a", "b"]);
+    assert!(fuse(rankings, 0).is_empty());
+  }
+}
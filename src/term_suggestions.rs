@@ -0,0 +1,407 @@
+use crate::commands::terms::{embed_term_data, insert_term, AddTermModal};
+use crate::config::BloomBotEmbed;
+use crate::database::{DatabaseHandler, SuggestionStatus, TermSuggestion};
+use crate::reports::{ephemeral_response, is_staff};
+use crate::Data;
+use anyhow::Result;
+use log::{info, warn};
+use poise::serenity_prelude::{
+  self as serenity, builder::*, ActionRowComponent, ButtonStyle, ComponentInteraction,
+  InputTextStyle, Mentionable, ModalInteraction,
+};
+
+/// Custom IDs of the suggestion buttons and modals start with this prefix, followed by the action
+/// and the suggestion ID, e.g. `suggestion:approve:<suggestion id>`.
+pub const SUGGESTION_PREFIX: &str = "suggestion:";
+
+/// How long staff have to fill in the term form after pressing Approve.
+const APPROVE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(900);
+
+fn suggestion_custom_id(action: &str, id: &str) -> String {
+  format!("{}{}:{}", SUGGESTION_PREFIX, action, id)
+}
+
+/// Splits a suggestion custom ID into its action and ID.
+fn parse_custom_id(custom_id: &str) -> Option<(&str, &str)> {
+  custom_id.strip_prefix(SUGGESTION_PREFIX)?.split_once(':')
+}
+
+pub async fn suggestion_embed(
+  ctx: &serenity::Context,
+  suggestion: &TermSuggestion,
+) -> Result<CreateEmbed> {
+  let user = suggestion.suggester_id.to_user(ctx).await?;
+
+  let status = match (suggestion.status, suggestion.reviewer_id) {
+    (SuggestionStatus::Pending, _) => "Pending".to_string(),
+    (SuggestionStatus::Approved, Some(reviewer_id)) => {
+      format!("Approved by {}", reviewer_id.mention())
+    }
+    (SuggestionStatus::Rejected, Some(reviewer_id)) => {
+      format!("Rejected by {}", reviewer_id.mention())
+    }
+    (SuggestionStatus::Approved, None) => "Approved".to_string(),
+    (SuggestionStatus::Rejected, None) => "Rejected".to_string(),
+  };
+
+  let mut embed = BloomBotEmbed::new()
+    .title("Term Suggestion")
+    .description(format!("**Suggestion**: {}", suggestion.term_name));
+
+  if let Some(meaning) = &suggestion.meaning {
+    embed = embed.field("Suggested Meaning", meaning, false);
+  }
+
+  embed = embed.field("Status", status, false);
+
+  if let Some(decision_notes) = &suggestion.decision_notes {
+    embed = embed.field("Decision Notes", decision_notes, false);
+  }
+
+  embed = embed
+    .footer(
+      CreateEmbedFooter::new(format!("Suggested by {} ({})", user.name, user.id))
+        .icon_url(user.avatar_url().unwrap_or_default()),
+    )
+    .timestamp(serenity::Timestamp::from(suggestion.created_at));
+
+  embed = match suggestion.status {
+    SuggestionStatus::Approved => embed.color(serenity::Color::DARK_GREEN),
+    SuggestionStatus::Rejected => embed.color(serenity::Color::LIGHT_GREY),
+    SuggestionStatus::Pending => embed,
+  };
+
+  Ok(embed)
+}
+
+/// Buttons for a suggestion that is waiting for review. Decided suggestions have no buttons.
+pub fn suggestion_buttons(suggestion: &TermSuggestion) -> Vec<CreateActionRow> {
+  match suggestion.status {
+    SuggestionStatus::Pending => vec![CreateActionRow::Buttons(vec![
+      CreateButton::new(suggestion_custom_id("approve", &suggestion.id))
+        .label("Approve")
+        .style(ButtonStyle::Success),
+      CreateButton::new(suggestion_custom_id("reject", &suggestion.id))
+        .label("Reject")
+        .style(ButtonStyle::Danger),
+    ])],
+    _ => vec![],
+  }
+}
+
+/// Returns the value of a text input in a submitted modal, if it isn't blank.
+fn input_value(submit: &ModalInteraction, custom_id: &str) -> Option<String> {
+  submit
+    .data
+    .components
+    .iter()
+    .flat_map(|row| row.components.iter())
+    .find_map(|component| match component {
+      ActionRowComponent::InputText(input) if input.custom_id == custom_id => input.value.clone(),
+      _ => None,
+    })
+    .filter(|value| !value.trim().is_empty())
+}
+
+/// Tells the member who made a suggestion what was decided. Returns whether the DM was sent.
+async fn notify_suggester(ctx: &serenity::Context, suggestion: &TermSuggestion) -> bool {
+  let mut embed = match suggestion.status {
+    SuggestionStatus::Approved => BloomBotEmbed::new().title(format!(
+      "Your suggestion `{}` has been added to the glossary. Thank you!",
+      suggestion.term_name
+    )),
+    _ => BloomBotEmbed::new().title(format!(
+      "Your suggestion `{}` was not added to the glossary.",
+      suggestion.term_name
+    )),
+  };
+  if let Some(notes) = &suggestion.decision_notes {
+    embed = embed.description(format!("**Notes**: {}", notes));
+  }
+
+  let sent = match suggestion.suggester_id.create_dm_channel(ctx).await {
+    Ok(channel) => channel
+      .send_message(ctx, CreateMessage::new().embed(embed))
+      .await
+      .is_ok(),
+    Err(_) => false,
+  };
+
+  if !sent {
+    info!(
+      "Could not notify {} of the decision on their glossary suggestion",
+      suggestion.suggester_id
+    );
+  }
+
+  sent
+}
+
+/// Handles the Approve button, which opens the add term form prefilled with the suggestion and
+/// adds the term, and the Reject button, which asks staff for decision notes.
+pub async fn handle_button(
+  ctx: &serenity::Context,
+  data: &Data,
+  press: &ComponentInteraction,
+) -> Result<()> {
+  let (action, id) = match parse_custom_id(&press.data.custom_id) {
+    Some(parsed) => parsed,
+    None => return Ok(()),
+  };
+
+  if !is_staff(ctx, press.guild_id, &press.user).await? {
+    press
+      .create_response(
+        ctx,
+        ephemeral_response(":x: Only staff can review suggestions."),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+  let suggestion = match DatabaseHandler::get_term_suggestion(&mut transaction, id).await? {
+    Some(suggestion) if suggestion.status == SuggestionStatus::Pending => suggestion,
+    Some(_) => {
+      press
+        .create_response(
+          ctx,
+          ephemeral_response(":x: This suggestion has already been reviewed."),
+        )
+        .await?;
+      return Ok(());
+    }
+    None => {
+      press
+        .create_response(ctx, ephemeral_response(":x: Suggestion does not exist."))
+        .await?;
+      return Ok(());
+    }
+  };
+
+  match action {
+    "approve" => {
+      if DatabaseHandler::get_term(
+        &mut transaction,
+        &suggestion.guild_id,
+        &suggestion.term_name,
+      )
+      .await?
+      .is_some()
+      {
+        press
+          .create_response(
+            ctx,
+            ephemeral_response(
+              ":x: A term with this name or alias was added since the suggestion was made. Reject the suggestion instead.",
+            ),
+          )
+          .await?;
+        return Ok(());
+      }
+      drop(transaction);
+
+      let defaults = AddTermModal {
+        definition: suggestion.meaning.clone().unwrap_or_default(),
+        example: None,
+        category: None,
+        links: None,
+        aliases: None,
+      };
+      // poise needs a context that implements `AsRef<serenity::Context>`, which a bare context
+      // outside of a command doesn't
+      let term_data = match poise::execute_modal_on_component_interaction(
+        Box::new(ctx.clone()),
+        press.clone(),
+        Some(defaults),
+        Some(APPROVE_TIMEOUT),
+      )
+      .await?
+      {
+        Some(term_data) => term_data,
+        None => return Ok(()),
+      };
+
+      // Embed the term before locking the suggestion. The form was already acknowledged, so a
+      // provider error shouldn't stop the approval, and the reindex job embeds the term later.
+      let embedding = match embed_term_data(
+        data.embeddings.as_ref(),
+        &suggestion.term_name,
+        &term_data,
+        &press.user.id,
+      )
+      .await
+      {
+        Ok(embedding) => embedding,
+        Err(err) => {
+          warn!(
+            "Could not embed approved suggestion {}, it will be embedded when reindexed: {}",
+            suggestion.term_name, err
+          );
+          None
+        }
+      };
+
+      let mut transaction = data.db.start_transaction_with_retry(5).await?;
+      // Someone else may have reviewed the suggestion while the form was open
+      if !DatabaseHandler::decide_term_suggestion(
+        &mut transaction,
+        &suggestion.id,
+        SuggestionStatus::Approved,
+        &press.user.id,
+        None,
+      )
+      .await?
+      {
+        // The form was already acknowledged, so follow up instead of responding
+        press
+          .create_followup(
+            ctx,
+            CreateInteractionResponseFollowup::new()
+              .content(":x: This suggestion has already been reviewed.")
+              .ephemeral(true),
+          )
+          .await?;
+        return Ok(());
+      }
+
+      insert_term(
+        &mut transaction,
+        &suggestion.guild_id,
+        &suggestion.term_name,
+        term_data,
+        embedding,
+        &press.user.id,
+      )
+      .await?;
+
+      let suggestion = DatabaseHandler::get_term_suggestion(&mut transaction, &suggestion.id)
+        .await?
+        .unwrap_or(suggestion);
+      DatabaseHandler::commit_transaction(transaction).await?;
+
+      press
+        .message
+        .clone()
+        .edit(
+          ctx,
+          EditMessage::new()
+            .embed(suggestion_embed(ctx, &suggestion).await?)
+            .components(suggestion_buttons(&suggestion)),
+        )
+        .await?;
+
+      if !notify_suggester(ctx, &suggestion).await {
+        press
+          .create_followup(
+            ctx,
+            CreateInteractionResponseFollowup::new()
+              .content(":warning: Could not notify the member of the decision via DM.")
+              .ephemeral(true),
+          )
+          .await?;
+      }
+    }
+    "reject" => {
+      press
+        .create_response(
+          ctx,
+          CreateInteractionResponse::Modal(
+            CreateModal::new(suggestion_custom_id("reject", id), "Reject Suggestion").components(
+              vec![CreateActionRow::InputText(
+                CreateInputText::new(
+                  InputTextStyle::Paragraph,
+                  "Decision notes (sent to the member)",
+                  "notes",
+                )
+                .required(false)
+                .max_length(1000),
+              )],
+            ),
+          ),
+        )
+        .await?;
+    }
+    _ => {}
+  }
+
+  Ok(())
+}
+
+/// Handles the decision notes submitted when rejecting a suggestion, which are sent to the member.
+pub async fn handle_modal(
+  ctx: &serenity::Context,
+  data: &Data,
+  submit: &ModalInteraction,
+) -> Result<()> {
+  let id = match parse_custom_id(&submit.data.custom_id) {
+    Some(("reject", id)) => id,
+    _ => return Ok(()),
+  };
+
+  if !is_staff(ctx, submit.guild_id, &submit.user).await? {
+    submit
+      .create_response(
+        ctx,
+        ephemeral_response(":x: Only staff can review suggestions."),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let notes = input_value(submit, "notes");
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+  if !DatabaseHandler::decide_term_suggestion(
+    &mut transaction,
+    id,
+    SuggestionStatus::Rejected,
+    &submit.user.id,
+    notes.as_deref(),
+  )
+  .await?
+  {
+    submit
+      .create_response(
+        ctx,
+        ephemeral_response(":x: This suggestion has already been reviewed."),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let suggestion = match DatabaseHandler::get_term_suggestion(&mut transaction, id).await? {
+    Some(suggestion) => suggestion,
+    None => {
+      submit
+        .create_response(ctx, ephemeral_response(":x: Suggestion does not exist."))
+        .await?;
+      return Ok(());
+    }
+  };
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  submit
+    .create_response(
+      ctx,
+      CreateInteractionResponse::UpdateMessage(
+        CreateInteractionResponseMessage::new()
+          .embed(suggestion_embed(ctx, &suggestion).await?)
+          .components(suggestion_buttons(&suggestion)),
+      ),
+    )
+    .await?;
+
+  if !notify_suggester(ctx, &suggestion).await {
+    submit
+      .create_followup(
+        ctx,
+        CreateInteractionResponseFollowup::new()
+          .content(":warning: Could not notify the member of the decision via DM.")
+          .ephemeral(true),
+      )
+      .await?;
+  }
+
+  Ok(())
+}
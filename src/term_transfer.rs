@@ -0,0 +1,357 @@
+use crate::database::Term;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// The longest meaning an imported term may have, matching the add term form.
+pub const MAX_MEANING_LENGTH: usize = 1000;
+
+/// A glossary term as it is exported and imported, without anything specific to one database.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TermRecord {
+  pub term_name: String,
+  pub meaning: String,
+  #[serde(default)]
+  pub usage: Option<String>,
+  #[serde(default)]
+  pub links: Vec<String>,
+  #[serde(default)]
+  pub category: Option<String>,
+  #[serde(default)]
+  pub aliases: Vec<String>,
+}
+
+impl From<&Term> for TermRecord {
+  fn from(term: &Term) -> Self {
+    TermRecord {
+      term_name: term.term_name.clone(),
+      meaning: term.meaning.clone(),
+      usage: term.usage.clone(),
+      links: term.links.clone().unwrap_or_default(),
+      category: term.category.clone(),
+      aliases: term.aliases.clone().unwrap_or_default(),
+    }
+  }
+}
+
+fn normalize_text(text: Option<String>) -> Option<String> {
+  text
+    .map(|text| text.trim().to_string())
+    .filter(|text| !text.is_empty())
+}
+
+fn normalize_list(list: Vec<String>) -> Vec<String> {
+  list
+    .into_iter()
+    .map(|item| item.trim().to_string())
+    .filter(|item| !item.is_empty())
+    .collect()
+}
+
+impl TermRecord {
+  /// Trims every field and drops blank optional values, so that whitespace alone never counts as
+  /// a change.
+  fn normalize(self) -> Self {
+    TermRecord {
+      term_name: self.term_name.trim().to_string(),
+      meaning: self.meaning.trim().to_string(),
+      usage: normalize_text(self.usage),
+      links: normalize_list(self.links),
+      category: normalize_text(self.category),
+      aliases: normalize_list(self.aliases),
+    }
+  }
+
+  /// The names of the fields that differ between two versions of a term. The term name is
+  /// compared elsewhere, so it is not included.
+  fn changed_fields(&self, other: &TermRecord) -> Vec<&'static str> {
+    let mut fields = Vec::new();
+    if self.meaning != other.meaning {
+      fields.push("meaning");
+    }
+    if self.usage != other.usage {
+      fields.push("usage");
+    }
+    if self.links != other.links {
+      fields.push("links");
+    }
+    if self.category != other.category {
+      fields.push("category");
+    }
+    if self.aliases != other.aliases {
+      fields.push("aliases");
+    }
+    fields
+  }
+}
+
+pub fn to_json(terms: &[Term]) -> Result<String> {
+  let records: Vec<TermRecord> = terms.iter().map(TermRecord::from).collect();
+  Ok(serde_json::to_string_pretty(&records)?)
+}
+
+/// Renders the glossary as a Markdown document, with one section per term.
+pub fn to_markdown(guild_name: &str, terms: &[Term]) -> String {
+  let mut document = format!(
+    "# {} Glossary\n\n_{} terms, exported {}_\n",
+    guild_name,
+    terms.len(),
+    chrono::Utc::now().format("%Y-%m-%d")
+  );
+
+  for term in terms {
+    document.push_str(&format!("\n## {}\n\n", term.term_name));
+
+    if let Some(category) = &term.category {
+      document.push_str(&format!("**Category:** {}\n\n", category));
+    }
+    if let Some(aliases) = term.aliases.as_ref().filter(|aliases| !aliases.is_empty()) {
+      document.push_str(&format!("**Aliases:** {}\n\n", aliases.join(", ")));
+    }
+
+    document.push_str(&format!("{}\n", term.meaning));
+
+    if let Some(usage) = &term.usage {
+      document.push_str(&format!("\n> _{}_\n", usage));
+    }
+    if let Some(links) = term.links.as_ref().filter(|links| !links.is_empty()) {
+      document.push_str("\n**Further reading:**\n\n");
+      for link in links {
+        document.push_str(&format!("- {}\n", link));
+      }
+    }
+  }
+
+  document
+}
+
+/// Reads terms from an exported JSON file. Fails if a term is missing its name or meaning, has a
+/// meaning that is too long, or appears more than once.
+pub fn parse_json(contents: &[u8]) -> Result<Vec<TermRecord>> {
+  let records: Vec<TermRecord> = serde_json::from_slice(contents)?;
+
+  let mut seen = HashSet::new();
+  let mut terms = Vec::with_capacity(records.len());
+
+  for (index, record) in records.into_iter().enumerate() {
+    let record = record.normalize();
+
+    if record.term_name.is_empty() {
+      return Err(anyhow!("Term #{} has no name", index + 1));
+    }
+    if record.meaning.is_empty() {
+      return Err(anyhow!("`{}` has no meaning", record.term_name));
+    }
+    if record.meaning.chars().count() > MAX_MEANING_LENGTH {
+      return Err(anyhow!(
+        "The meaning of `{}` is longer than {} characters",
+        record.term_name,
+        MAX_MEANING_LENGTH
+      ));
+    }
+    if !seen.insert(record.term_name.to_lowercase()) {
+      return Err(anyhow!("`{}` appears more than once", record.term_name));
+    }
+
+    terms.push(record);
+  }
+
+  Ok(terms)
+}
+
+/// An imported term that already exists with different content.
+#[derive(Debug)]
+pub struct ChangedTerm {
+  /// The record ID of the existing term.
+  pub id: String,
+  pub record: TermRecord,
+  pub fields: Vec<&'static str>,
+}
+
+/// How an import would change the glossary. Terms that aren't in the imported file are left
+/// alone.
+#[derive(Debug, Default)]
+pub struct ImportPlan {
+  pub new: Vec<TermRecord>,
+  pub changed: Vec<ChangedTerm>,
+  pub unchanged: Vec<String>,
+}
+
+/// Compares imported terms with the existing terms of a guild, matching them by name without
+/// regard to case.
+pub fn plan_import(existing: &[Term], imported: Vec<TermRecord>) -> ImportPlan {
+  let existing: HashMap<String, &Term> = existing
+    .iter()
+    .map(|term| (term.term_name.to_lowercase(), term))
+    .collect();
+
+  let mut plan = ImportPlan::default();
+
+  for record in imported {
+    match existing.get(&record.term_name.to_lowercase()) {
+      Some(term) => {
+        // Imported records are normalized, so stored whitespace and blank values shouldn't
+        // count as changes either
+        let fields = TermRecord::from(*term).normalize().changed_fields(&record);
+        match fields.is_empty() {
+          true => plan.unchanged.push(term.term_name.clone()),
+          false => plan.changed.push(ChangedTerm {
+            id: term.id.clone(),
+            // Keep the existing spelling of the name, since names can't be edited
+            record: TermRecord {
+              term_name: term.term_name.clone(),
+              ..record
+            },
+            fields,
+          }),
+        }
+      }
+      None => plan.new.push(record),
+    }
+  }
+
+  plan
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn term(term_name: &str, meaning: &str) -> Term {
+    Term {
+      id: format!("id-{}", term_name),
+      term_name: term_name.to_string(),
+      meaning: meaning.to_string(),
+      usage: None,
+      links: None,
+      category: None,
+      aliases: None,
+    }
+  }
+
+  fn record(term_name: &str, meaning: &str) -> TermRecord {
+    TermRecord {
+      term_name: term_name.to_string(),
+      meaning: meaning.to_string(),
+      usage: None,
+      links: vec![],
+      category: None,
+      aliases: vec![],
+    }
+  }
+
+  #[test]
+  fn parse_json_normalizes_records() {
+    let records = parse_json(
+      br#"[{"term_name": " Metta ", "meaning": " Loving-kindness. ", "usage": "  ", "links": ["", " https://example.com "], "aliases": [" loving-kindness "]}]"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+      records,
+      vec![TermRecord {
+        term_name: "Metta".to_string(),
+        meaning: "Loving-kindness.".to_string(),
+        usage: None,
+        links: vec!["https://example.com".to_string()],
+        category: None,
+        aliases: vec!["loving-kindness".to_string()],
+      }]
+    );
+  }
+
+  #[test]
+  fn parse_json_rejects_invalid_records() {
+    assert!(parse_json(b"not json").is_err());
+    assert!(parse_json(br#"[{"term_name": " ", "meaning": "Something"}]"#).is_err());
+    assert!(parse_json(br#"[{"term_name": "Metta", "meaning": " "}]"#).is_err());
+    assert!(parse_json(br#"[{"term_name": "Metta"}]"#).is_err());
+
+    let long_meaning = "a".repeat(MAX_MEANING_LENGTH + 1);
+    let json = format!(
+      r#"[{{"term_name": "Metta", "meaning": "{}"}}]"#,
+      long_meaning
+    );
+    assert!(parse_json(json.as_bytes()).is_err());
+  }
+
+  #[test]
+  fn parse_json_rejects_duplicate_names() {
+    let result = parse_json(
+      br#"[{"term_name": "Metta", "meaning": "One"}, {"term_name": "metta ", "meaning": "Two"}]"#,
+    );
+
+    assert!(result.is_err());
+  }
+
+  #[test]
+  fn plan_import_sorts_terms_into_new_changed_and_unchanged() {
+    let existing = vec![
+      term("Metta", "Loving-kindness."),
+      term("Jhana", "Absorption."),
+    ];
+    let plan = plan_import(
+      &existing,
+      vec![
+        record("metta", "Loving-kindness."),
+        record("Jhana", "Meditative absorption."),
+        record("Dukkha", "Unsatisfactoriness."),
+      ],
+    );
+
+    assert_eq!(plan.new, vec![record("Dukkha", "Unsatisfactoriness.")]);
+    assert_eq!(plan.unchanged, vec!["Metta".to_string()]);
+    assert_eq!(plan.changed.len(), 1);
+    assert_eq!(plan.changed[0].id, "id-Jhana");
+    assert_eq!(plan.changed[0].fields, vec!["meaning"]);
+    assert_eq!(
+      plan.changed[0].record,
+      record("Jhana", "Meditative absorption.")
+    );
+  }
+
+  #[test]
+  fn plan_import_keeps_the_existing_name() {
+    let plan = plan_import(
+      &[term("Metta", "Loving-kindness.")],
+      vec![record("METTA", "Friendliness.")],
+    );
+
+    assert_eq!(plan.changed[0].record.term_name, "Metta");
+  }
+
+  #[test]
+  fn plan_import_ignores_stored_whitespace_and_blank_values() {
+    let existing = Term {
+      usage: Some("  ".to_string()),
+      links: Some(vec![" https://example.com ".to_string(), "".to_string()]),
+      category: Some("".to_string()),
+      aliases: Some(vec![" loving-kindness".to_string()]),
+      ..term("Metta", "Loving-kindness.\n")
+    };
+    let exported = to_json(std::slice::from_ref(&existing)).unwrap();
+
+    let plan = plan_import(&[existing], parse_json(exported.as_bytes()).unwrap());
+
+    assert!(plan.new.is_empty());
+    assert!(plan.changed.is_empty());
+    assert_eq!(plan.unchanged, vec!["Metta".to_string()]);
+  }
+
+  #[test]
+  fn plan_import_lists_every_changed_field() {
+    let existing = Term {
+      usage: Some("Practice metta daily.".to_string()),
+      ..term("Metta", "Loving-kindness.")
+    };
+    let imported = TermRecord {
+      category: Some("Practices".to_string()),
+      aliases: vec!["Maitri".to_string()],
+      ..record("Metta", "Loving-kindness.")
+    };
+
+    let plan = plan_import(&[existing], vec![imported]);
+
+    assert_eq!(plan.changed[0].fields, vec!["usage", "category", "aliases"]);
+  }
+}